@@ -0,0 +1,255 @@
+//! Webhook delivery
+//!
+//! Payloads are POSTed as JSON, signed with HMAC-SHA256 when the webhook
+//! has a secret, and retried with exponential backoff. `trx webhooks test`
+//! runs `trx-api webhook-test` so the CLI itself needs no HTTP client.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+use trx_core::webhook::{DeliveryLog, DeliveryRecord, Payload};
+use trx_core::{Config, Issue, ServiceManager, UnifiedStore, WebhookConfig, WebhookEvent};
+
+/// Header carrying the `sha256=<hex>` payload signature
+pub const SIGNATURE_HEADER: &str = "X-Trx-Signature";
+
+/// Header carrying the event name
+pub const EVENT_HEADER: &str = "X-Trx-Event";
+
+/// Header carrying the unique delivery ID
+pub const DELIVERY_HEADER: &str = "X-Trx-Delivery";
+
+/// Compute the `sha256=<hex>` signature of a payload body
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Retry behaviour for deliveries
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first
+    pub attempts: u32,
+    /// Delay before the first retry; doubled after each failure
+    pub initial_delay: Duration,
+    /// Timeout for each request
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            initial_delay: Duration::from_secs(1),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Send a payload once
+///
+/// Returns the HTTP status on success, or the status (if any) and an error
+/// message on failure.
+fn send(
+    hook: &WebhookConfig,
+    payload: &Payload,
+    body: &str,
+    timeout: Duration,
+) -> Result<u16, (Option<u16>, String)> {
+    let mut request = ureq::post(&hook.url)
+        .timeout(timeout)
+        .set("Content-Type", "application/json")
+        .set("User-Agent", concat!("trx/", env!("CARGO_PKG_VERSION")))
+        .set(EVENT_HEADER, &payload.event.to_string())
+        .set(DELIVERY_HEADER, &payload.delivery_id);
+    if let Some(secret) = &hook.secret {
+        request = request.set(SIGNATURE_HEADER, &sign(secret, body.as_bytes()));
+    }
+
+    match request.send_string(body) {
+        Ok(response) => Ok(response.status()),
+        Err(ureq::Error::Status(code, _)) => Err((Some(code), format!("HTTP {}", code))),
+        Err(e) => Err((None, e.to_string())),
+    }
+}
+
+/// Deliver a payload, retrying with exponential backoff
+///
+/// Blocks for the duration of all attempts.
+pub fn deliver(hook: &WebhookConfig, payload: &Payload, policy: RetryPolicy) -> DeliveryRecord {
+    let mut record = DeliveryRecord {
+        delivery_id: payload.delivery_id.clone(),
+        webhook: hook.name.clone(),
+        url: hook.url.clone(),
+        event: payload.event,
+        issue_id: payload.issue.id.clone(),
+        timestamp: payload.timestamp,
+        attempts: 0,
+        success: false,
+        status: None,
+        error: None,
+    };
+
+    let body = match serde_json::to_string(payload) {
+        Ok(body) => body,
+        Err(e) => {
+            record.error = Some(e.to_string());
+            return record;
+        }
+    };
+
+    let mut delay = policy.initial_delay;
+    while record.attempts < policy.attempts.max(1) {
+        if record.attempts > 0 {
+            std::thread::sleep(delay);
+            delay *= 2;
+        }
+        record.attempts += 1;
+
+        match send(hook, payload, &body, policy.timeout) {
+            Ok(status) => {
+                record.success = true;
+                record.status = Some(status);
+                record.error = None;
+                break;
+            }
+            Err((status, message)) => {
+                record.status = status;
+                record.error = Some(message);
+            }
+        }
+    }
+
+    record
+}
+
+/// Send a sample payload to the named webhook of the current repository,
+/// once and without retries, and log the delivery
+pub fn send_test(name: &str, event: WebhookEvent) -> anyhow::Result<DeliveryRecord> {
+    let store = UnifiedStore::open()?;
+    let config = Config::load(&store.trx_dir().join("config.toml"))?;
+    let hook = config
+        .webhooks
+        .iter()
+        .find(|h| h.name == name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown webhook: {}", name))?;
+
+    let mut issue = Issue::new(
+        format!("{}-test", store.prefix()?),
+        "Sample issue from trx webhooks test".to_string(),
+    );
+    issue.description = Some("This payload was sent by 'trx webhooks test'.".to_string());
+    let payload = Payload::new(event, issue);
+
+    let policy = RetryPolicy {
+        attempts: 1,
+        ..RetryPolicy::default()
+    };
+    let record = deliver(hook, &payload, policy);
+
+    let manager = ServiceManager::new()?;
+    DeliveryLog::new(manager.webhook_log_file()).append(&record)?;
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+    use axum::extract::State;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::{Router, routing::post};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Stand-in receiver that fails the first request and records the rest
+    #[derive(Default)]
+    struct Receiver {
+        calls: AtomicUsize,
+        received: Mutex<Vec<(HeaderMap, Bytes)>>,
+    }
+
+    async fn receive(
+        State(receiver): State<Arc<Receiver>>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> StatusCode {
+        if receiver.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            return StatusCode::SERVICE_UNAVAILABLE;
+        }
+        receiver.received.lock().unwrap().push((headers, body));
+        StatusCode::NO_CONTENT
+    }
+
+    async fn spawn_receiver() -> (Arc<Receiver>, String) {
+        let receiver = Arc::new(Receiver::default());
+        let app = Router::new()
+            .route("/hook", post(receive))
+            .with_state(receiver.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (receiver, format!("http://{}/hook", addr))
+    }
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_delivery_retries_and_signs() {
+        let (receiver, url) = spawn_receiver().await;
+        let hook = WebhookConfig {
+            name: "test".into(),
+            url,
+            events: vec![WebhookEvent::Closed],
+            filter: None,
+            secret: Some("s3cret".into()),
+        };
+        let payload = Payload::new(
+            WebhookEvent::Closed,
+            Issue::new("trx-abcd".into(), "Webhook test".into()),
+        );
+        let policy = RetryPolicy {
+            attempts: 3,
+            initial_delay: Duration::from_millis(10),
+            timeout: Duration::from_secs(5),
+        };
+
+        let log_dir = tempfile::tempdir().unwrap();
+        let log = DeliveryLog::new(log_dir.path().join("webhooks.jsonl"));
+
+        let record = tokio::task::spawn_blocking(move || {
+            let record = deliver(&hook, &payload, policy);
+            log.append(&record).unwrap();
+            record
+        })
+        .await
+        .unwrap();
+
+        assert!(record.success);
+        assert_eq!(record.attempts, 2);
+        assert_eq!(record.status, Some(204));
+
+        let received = receiver.received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let (headers, body) = &received[0];
+        assert_eq!(headers[EVENT_HEADER], "closed");
+        assert_eq!(
+            headers[SIGNATURE_HEADER].to_str().unwrap(),
+            sign("s3cret", body)
+        );
+        let json: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(json["issue"]["id"], "trx-abcd");
+
+        let logged = std::fs::read_to_string(log_dir.path().join("webhooks.jsonl")).unwrap();
+        assert_eq!(logged.lines().count(), 1);
+    }
+}
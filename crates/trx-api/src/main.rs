@@ -5,19 +5,105 @@
 use axum::{
     Json, Router,
     extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
     routing::{delete, get, post},
 };
+use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, RwLock};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
-use trx_core::{Issue, IssueType, Status, Store, generate_id};
+use trx_core::query::{paginate_keyed, parse_fields, select_fields};
+use trx_core::webhook::DeliveryLog;
+use trx_core::{
+    BatchOp, BatchOutcome, Config, DEFAULT_PORT, Issue, IssueGraph, IssueType, ServiceManager,
+    SortSpec, Status, UnifiedStore, WebhookEvent, Workspace, check_priority, generate_id,
+};
+
+mod delivery;
+mod metrics;
+mod repos;
+mod ui;
+
+use metrics::Metrics;
+use repos::{RegisterError, Registrations, Registry, RepoCtx};
+
+/// Maximum number of operations accepted in one batch request
+const MAX_BATCH_OPS: usize = 1000;
+
+/// How long shutdown waits for webhook deliveries still in progress
+const DELIVERY_GRACE: Duration = Duration::from_secs(10);
+
+/// REST API server for trx issue tracker
+#[derive(Parser)]
+#[command(name = "trx-api")]
+#[command(version)]
+struct Args {
+    /// Repository root to serve (repeatable; defaults to the current repository)
+    #[arg(long = "repo", value_name = "PATH")]
+    repos: Vec<PathBuf>,
+
+    /// Workspace file listing repositories to serve
+    #[arg(long, value_name = "FILE")]
+    workspace: Option<PathBuf>,
+
+    /// Name to serve the current repository under (defaults to its
+    /// directory name)
+    #[arg(long, conflicts_with_all = ["repos", "workspace"])]
+    name: Option<String>,
+
+    /// Listen on a Unix socket instead of a TCP port
+    /// (defaults to $XDG_RUNTIME_DIR/trx/trx-api.sock)
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    socket: Option<Option<PathBuf>>,
+
+    #[command(subcommand)]
+    command: Option<ApiCommand>,
+}
+
+#[derive(Subcommand)]
+enum ApiCommand {
+    /// Send a sample payload to a configured webhook and print the delivery
+    /// record as JSON (run by `trx webhooks test`)
+    WebhookTest {
+        /// Webhook name from config
+        name: String,
+
+        /// Event to simulate (created, updated, closed, deleted)
+        #[arg(short, long, default_value = "created")]
+        event: String,
+    },
+}
 
 /// Shared application state
 struct AppState {
-    store: RwLock<Store>,
+    repos: Registry,
+    registrations: Registrations,
+    metrics: Metrics,
+}
+
+/// Path parameters for single-issue routes
+#[derive(Debug, Deserialize)]
+struct IssuePath {
+    id: String,
+}
+
+/// Path parameters for dependency routes
+#[derive(Debug, Deserialize)]
+struct DependencyPath {
+    id: String,
+    dep_id: String,
+}
+
+/// Request to register a repository with the daemon
+#[derive(Debug, Deserialize)]
+struct RegisterRepoRequest {
+    path: PathBuf,
+    #[serde(default)]
+    name: Option<String>,
 }
 
 /// Request to create a new issue
@@ -75,6 +161,12 @@ struct DeleteIssueRequest {
     reason: Option<String>,
 }
 
+/// Request to apply several operations at once
+#[derive(Debug, Deserialize)]
+struct BatchRequest {
+    operations: Vec<BatchOp>,
+}
+
 /// Query parameters for listing issues
 #[derive(Debug, Deserialize)]
 struct ListQuery {
@@ -86,6 +178,45 @@ struct ListQuery {
     priority: Option<u8>,
     #[serde(default)]
     include_tombstones: Option<bool>,
+    /// Repository to list, or `*` for all repositories
+    #[serde(default)]
+    repo: Option<String>,
+}
+
+/// Pagination, sorting and field selection shared by list endpoints
+#[derive(Debug, Deserialize)]
+struct PageQuery {
+    /// Maximum number of issues to return
+    #[serde(default)]
+    limit: Option<usize>,
+    /// ID of the last issue on the previous page
+    #[serde(default)]
+    cursor: Option<String>,
+    /// Sort keys, e.g. `updated_at:desc,priority`
+    #[serde(default)]
+    sort: Option<String>,
+    /// Comma-separated fields to include, e.g. `id,title,status`
+    #[serde(default)]
+    fields: Option<String>,
+}
+
+/// Query parameters for the graph endpoints
+#[derive(Debug, Deserialize)]
+struct GraphQuery {
+    /// Include closed issues as nodes
+    #[serde(default)]
+    include_closed: Option<bool>,
+}
+
+/// Query parameters for insights
+#[derive(Debug, Deserialize)]
+struct InsightsQuery {
+    /// Number of entries in each ranked list
+    #[serde(default)]
+    top: Option<usize>,
+    /// Days without updates before an open issue counts as stale
+    #[serde(default)]
+    stale_days: Option<i64>,
 }
 
 /// API response wrapper
@@ -95,6 +226,10 @@ struct ApiResponse<T> {
     #[serde(skip_serializing_if = "Option::is_none")]
     data: Option<T>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
 
@@ -103,86 +238,391 @@ impl<T> ApiResponse<T> {
         Self {
             success: true,
             data: Some(data),
+            total: None,
+            next_cursor: None,
             error: None,
         }
     }
 
+    fn page(data: T, total: usize, next_cursor: Option<String>) -> Self {
+        Self {
+            success: true,
+            data: Some(data),
+            total: Some(total),
+            next_cursor,
+            error: None,
+        }
+    }
+
+    fn err_with(data: T, message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            data: Some(data),
+            total: None,
+            next_cursor: None,
+            error: Some(message.into()),
+        }
+    }
+
     fn err(message: impl Into<String>) -> Self {
         Self {
             success: false,
             data: None,
+            total: None,
+            next_cursor: None,
             error: Some(message.into()),
         }
     }
 }
 
+/// Response type shared by list endpoints
+type ListResponse = (StatusCode, Json<ApiResponse<Vec<serde_json::Value>>>);
+
+/// Sort, paginate and project a filtered issue listing
+fn page_response(store: &UnifiedStore, issues: Vec<&Issue>, query: &PageQuery) -> ListResponse {
+    paged_response(
+        issues.into_iter().map(|i| (None, i)).collect(),
+        |repo, id| repo.is_none().then(|| store.get(id)).flatten(),
+        query,
+    )
+}
+
+/// Sort, paginate and project issues that may come from several stores
+///
+/// Issues tagged with a repository name get an extra `repo` field, and
+/// their cursors take the form `repo/id` so equal IDs in different
+/// repositories stay distinct. `find` resolves a cursor's repository and ID.
+fn paged_response<'a>(
+    issues: Vec<(Option<&'a str>, &'a Issue)>,
+    find: impl Fn(Option<&str>, &str) -> Option<&'a Issue>,
+    query: &PageQuery,
+) -> ListResponse {
+    let sort = match query.sort.as_deref().map(str::parse::<SortSpec>) {
+        Some(Ok(sort)) => sort,
+        Some(Err(e)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::err(e.to_string())),
+            );
+        }
+        None => SortSpec::default(),
+    };
+
+    let cursor = match query.cursor.as_deref() {
+        Some(cursor) => {
+            let (repo, id) = match cursor.split_once('/') {
+                Some((repo, id)) => (Some(repo), id),
+                None => (None, cursor),
+            };
+            match find(repo, id) {
+                Some(issue) => Some((repo, issue)),
+                None => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(ApiResponse::err(format!("Invalid cursor: {}", cursor))),
+                    );
+                }
+            }
+        }
+        None => None,
+    };
+
+    let fields = query
+        .fields
+        .as_deref()
+        .map(parse_fields)
+        .unwrap_or_default();
+    let cursor = cursor.as_ref().map(|(repo, issue)| (repo, *issue));
+    let page = match paginate_keyed(issues, &sort, cursor, query.limit) {
+        Ok(page) => page,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::err(e.to_string())),
+            );
+        }
+    };
+    let next_cursor = page.next_cursor.map(|(repo, id)| match repo {
+        Some(repo) => format!("{}/{}", repo, id),
+        None => id,
+    });
+
+    let data: Result<Vec<_>, _> = page
+        .items
+        .iter()
+        .map(|(repo, issue)| {
+            let mut value = select_fields(issue, &fields)?;
+            if let Some(repo) = repo
+                && let serde_json::Value::Object(map) = &mut value
+            {
+                map.insert("repo".into(), (*repo).into());
+            }
+            Ok::<_, trx_core::Error>(value)
+        })
+        .collect();
+
+    match data {
+        Ok(data) => (
+            StatusCode::OK,
+            Json(ApiResponse::page(data, page.total, next_cursor)),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::err(e.to_string())),
+        ),
+    }
+}
+
 /// Health check endpoint
 async fn health() -> impl IntoResponse {
     Json(serde_json::json!({ "status": "ok" }))
 }
 
 /// List all issues
+///
+/// `?repo=*` lists issues from every registered repository, each tagged
+/// with a `repo` field; `?repo=NAME` lists a single repository.
 async fn list_issues(
     State(state): State<Arc<AppState>>,
+    RepoCtx(repo): RepoCtx,
     axum::extract::Query(query): axum::extract::Query<ListQuery>,
+    axum::extract::Query(page): axum::extract::Query<PageQuery>,
 ) -> impl IntoResponse {
-    let store = state.store.read().unwrap();
+    let repos = match query.repo.as_deref() {
+        Some("*") => state.repos.all(),
+        Some(name) => match state.repos.get(name) {
+            Some(repo) => vec![repo],
+            None => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::err(format!("Repository {} not found", name))),
+                );
+            }
+        },
+        None => vec![repo],
+    };
+
+    let stores: Vec<_> = repos
+        .iter()
+        .map(|r| (r.name.as_str(), r.store.read().unwrap()))
+        .collect();
     let include_tombstones = query.include_tombstones.unwrap_or(false);
-    let mut issues: Vec<_> = store
-        .list(include_tombstones)
-        .into_iter()
-        .cloned()
+    let mut issues: Vec<(&str, &Issue)> = stores
+        .iter()
+        .flat_map(|(name, store)| {
+            store
+                .list(include_tombstones)
+                .into_iter()
+                .map(|i| (*name, i))
+        })
         .collect();
 
     // Filter by status
-    if let Some(status_str) = &query.status {
-        if let Ok(status) = status_str.parse::<Status>() {
-            issues.retain(|i| i.status == status);
-        }
+    if let Some(status_str) = &query.status
+        && let Ok(status) = status_str.parse::<Status>()
+    {
+        issues.retain(|(_, i)| i.status == status);
     }
 
     // Filter by type
-    if let Some(type_str) = &query.issue_type {
-        if let Ok(issue_type) = type_str.parse::<IssueType>() {
-            issues.retain(|i| i.issue_type == issue_type);
-        }
+    if let Some(type_str) = &query.issue_type
+        && let Ok(issue_type) = type_str.parse::<IssueType>()
+    {
+        issues.retain(|(_, i)| i.issue_type == issue_type);
     }
 
     // Filter by priority
     if let Some(priority) = query.priority {
-        issues.retain(|i| i.priority == priority);
+        issues.retain(|(_, i)| i.priority == priority);
     }
 
-    // Sort by priority, then by created_at
-    issues.sort_by(|a, b| {
-        a.priority
-            .cmp(&b.priority)
-            .then_with(|| b.created_at.cmp(&a.created_at))
-    });
+    if query.repo.as_deref() != Some("*") {
+        let (_, store) = &stores[0];
+        return page_response(store, issues.into_iter().map(|(_, i)| i).collect(), &page);
+    }
 
-    (StatusCode::OK, Json(ApiResponse::ok(issues)))
+    paged_response(
+        issues
+            .into_iter()
+            .map(|(name, issue)| (Some(name), issue))
+            .collect(),
+        |repo, id| {
+            let (_, store) = stores.iter().find(|(name, _)| Some(*name) == repo)?;
+            store.get(id)
+        },
+        &page,
+    )
 }
 
 /// List open issues (unblocked)
-async fn list_ready(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let store = state.store.read().unwrap();
-    let all_open: Vec<_> = store.list_open();
-    let ready: Vec<_> = all_open
+async fn list_ready(
+    RepoCtx(repo): RepoCtx,
+    axum::extract::Query(page): axum::extract::Query<PageQuery>,
+) -> impl IntoResponse {
+    let store = repo.store.read().unwrap();
+    let open = store.list_open();
+    let graph = IssueGraph::from_issues(&open);
+    let ready = graph.ready_issues(&open);
+
+    page_response(&store, ready, &page)
+}
+
+/// List all issues blocking an issue, directly or transitively
+async fn list_blockers(
+    RepoCtx(repo): RepoCtx,
+    Path(IssuePath { id }): Path<IssuePath>,
+    axum::extract::Query(page): axum::extract::Query<PageQuery>,
+) -> impl IntoResponse {
+    let store = repo.store.read().unwrap();
+    if store.get(&id).is_none() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::err(format!("Issue {} not found", id))),
+        );
+    }
+
+    let open = store.list_open();
+    let graph = IssueGraph::from_issues(&open);
+    let blockers: Vec<_> = graph
+        .transitive_blockers(&id)
+        .iter()
+        .filter_map(|blocker| store.get(blocker))
+        .collect();
+
+    page_response(&store, blockers, &page)
+}
+
+/// Dependency graph as JSON, or as DOT/Mermaid depending on `Accept`
+async fn get_graph(
+    RepoCtx(repo): RepoCtx,
+    headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<GraphQuery>,
+) -> Response {
+    let store = repo.store.read().unwrap();
+    let issues = if query.include_closed.unwrap_or(false) {
+        store.list(false)
+    } else {
+        store.list_open()
+    };
+    let graph = IssueGraph::from_issues(&issues);
+
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if accept.contains("text/vnd.graphviz") {
+        return (
+            [(header::CONTENT_TYPE, "text/vnd.graphviz")],
+            graph.to_dot(),
+        )
+            .into_response();
+    }
+    if accept.contains("mermaid") {
+        return (
+            [(header::CONTENT_TYPE, "text/vnd.mermaid")],
+            graph.to_mermaid(),
+        )
+            .into_response();
+    }
+
+    let nodes: Vec<_> = issues
+        .iter()
+        .map(|i| {
+            serde_json::json!({
+                "id": i.id,
+                "title": i.title,
+                "status": i.status,
+                "priority": i.priority,
+                "issue_type": i.issue_type,
+            })
+        })
+        .collect();
+
+    Json(ApiResponse::ok(serde_json::json!({
+        "nodes": nodes,
+        "edges": graph.edges(),
+    })))
+    .into_response()
+}
+
+/// Dependency cycles among open issues
+async fn get_cycles(RepoCtx(repo): RepoCtx) -> impl IntoResponse {
+    let store = repo.store.read().unwrap();
+    let open = store.list_open();
+    let graph = IssueGraph::from_issues(&open);
+
+    Json(ApiResponse::ok(graph.find_cycles()))
+}
+
+/// Longest chain of blocking dependencies among open issues
+async fn get_critical_path(RepoCtx(repo): RepoCtx) -> impl IntoResponse {
+    let store = repo.store.read().unwrap();
+    let open = store.list_open();
+    let graph = IssueGraph::from_issues(&open);
+
+    match graph.critical_path() {
+        Some(path) => (StatusCode::OK, Json(ApiResponse::ok(path))),
+        None => (
+            StatusCode::CONFLICT,
+            Json(ApiResponse::err(
+                "Dependency graph contains cycles; see /graph/cycles",
+            )),
+        ),
+    }
+}
+
+/// Graph analytics: PageRank, top blockers and stale issues
+async fn get_insights(
+    RepoCtx(repo): RepoCtx,
+    axum::extract::Query(query): axum::extract::Query<InsightsQuery>,
+) -> impl IntoResponse {
+    let store = repo.store.read().unwrap();
+    let open = store.list_open();
+    let graph = IssueGraph::from_issues(&open);
+    let top = query.top.unwrap_or(10);
+
+    let mut pagerank: Vec<_> = graph.pagerank(0.85, 20).into_iter().collect();
+    pagerank.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    pagerank.truncate(top);
+
+    let mut blockers: Vec<_> = graph.blocks_count().into_iter().collect();
+    blockers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    blockers.truncate(top);
+
+    let stale_before = chrono::Utc::now() - chrono::Duration::days(query.stale_days.unwrap_or(30));
+    let mut stale: Vec<_> = open
         .iter()
-        .filter(|issue| !issue.is_blocked_by(&all_open))
-        .cloned()
-        .cloned()
+        .filter(|i| i.updated_at < stale_before)
         .collect();
+    stale.sort_by_key(|i| i.updated_at);
+    stale.truncate(top);
 
-    (StatusCode::OK, Json(ApiResponse::ok(ready)))
+    Json(ApiResponse::ok(serde_json::json!({
+        "total_open": open.len(),
+        "ready": graph.ready_issues(&open).len(),
+        "blocked": graph.blocked_issues(&open).len(),
+        "has_cycles": graph.has_cycles(),
+        "pagerank": pagerank
+            .iter()
+            .map(|(id, score)| serde_json::json!({ "id": id, "score": score }))
+            .collect::<Vec<_>>(),
+        "blockers": blockers
+            .iter()
+            .map(|(id, blocks)| serde_json::json!({ "id": id, "blocks": blocks }))
+            .collect::<Vec<_>>(),
+        "stale": stale
+            .iter()
+            .map(|i| serde_json::json!({ "id": i.id, "title": i.title, "updated_at": i.updated_at }))
+            .collect::<Vec<_>>(),
+    })))
 }
 
 /// Get a single issue by ID
 async fn get_issue(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
+    RepoCtx(repo): RepoCtx,
+    Path(IssuePath { id }): Path<IssuePath>,
 ) -> impl IntoResponse {
-    let store = state.store.read().unwrap();
+    let store = repo.store.read().unwrap();
     match store.get(&id) {
         Some(issue) => (StatusCode::OK, Json(ApiResponse::ok(issue.clone()))),
         None => (
@@ -194,10 +634,17 @@ async fn get_issue(
 
 /// Create a new issue
 async fn create_issue(
-    State(state): State<Arc<AppState>>,
+    RepoCtx(repo): RepoCtx,
     Json(req): Json<CreateIssueRequest>,
 ) -> impl IntoResponse {
-    let mut store = state.store.write().unwrap();
+    if let Some(Err(e)) = req.priority.map(check_priority) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<Issue>::err(e.to_string())),
+        );
+    }
+
+    let mut store = repo.store.write().unwrap();
 
     // Get prefix for ID generation
     let prefix = store.prefix().unwrap_or_else(|_| "trx".to_string());
@@ -227,12 +674,12 @@ async fn create_issue(
         issue.description = Some(desc);
     }
     if let Some(priority) = req.priority {
-        issue.priority = priority.min(4);
+        issue.priority = priority;
     }
-    if let Some(type_str) = req.issue_type {
-        if let Ok(t) = type_str.parse::<IssueType>() {
-            issue.issue_type = t;
-        }
+    if let Some(type_str) = req.issue_type
+        && let Ok(t) = type_str.parse::<IssueType>()
+    {
+        issue.issue_type = t;
     }
     if let Some(labels) = req.labels {
         issue.labels = labels;
@@ -247,7 +694,10 @@ async fn create_issue(
     }
 
     match store.create(issue.clone()) {
-        Ok(()) => (StatusCode::CREATED, Json(ApiResponse::ok(issue))),
+        Ok(()) => {
+            repo.emit(WebhookEvent::Created, &issue);
+            (StatusCode::CREATED, Json(ApiResponse::ok(issue)))
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ApiResponse::<Issue>::err(e.to_string())),
@@ -257,13 +707,20 @@ async fn create_issue(
 
 /// Update an existing issue
 async fn update_issue(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
+    RepoCtx(repo): RepoCtx,
+    Path(IssuePath { id }): Path<IssuePath>,
     Json(req): Json<UpdateIssueRequest>,
 ) -> impl IntoResponse {
-    let mut store = state.store.write().unwrap();
+    if let Some(Err(e)) = req.priority.map(check_priority) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<Issue>::err(e.to_string())),
+        );
+    }
 
-    let issue = match store.get_mut(&id) {
+    let mut store = repo.store.write().unwrap();
+
+    let mut issue = match store.get(&id).cloned() {
         Some(i) => i,
         None => {
             return (
@@ -273,6 +730,8 @@ async fn update_issue(
         }
     };
 
+    let was_closed = issue.status.is_closed();
+
     // Apply updates
     if let Some(title) = req.title {
         issue.title = title;
@@ -280,18 +739,18 @@ async fn update_issue(
     if let Some(desc) = req.description {
         issue.description = Some(desc);
     }
-    if let Some(status_str) = req.status {
-        if let Ok(status) = status_str.parse::<Status>() {
-            issue.status = status;
-        }
+    if let Some(status_str) = req.status
+        && let Ok(status) = status_str.parse::<Status>()
+    {
+        issue.set_status(status);
     }
     if let Some(priority) = req.priority {
-        issue.priority = priority.min(4);
+        issue.priority = priority;
     }
-    if let Some(type_str) = req.issue_type {
-        if let Ok(t) = type_str.parse::<IssueType>() {
-            issue.issue_type = t;
-        }
+    if let Some(type_str) = req.issue_type
+        && let Ok(t) = type_str.parse::<IssueType>()
+    {
+        issue.issue_type = t;
     }
     if let Some(labels) = req.labels {
         issue.labels = labels;
@@ -306,8 +765,16 @@ async fn update_issue(
     issue.updated_at = chrono::Utc::now();
     let updated = issue.clone();
 
-    match store.save() {
-        Ok(()) => (StatusCode::OK, Json(ApiResponse::ok(updated))),
+    match store.update(issue) {
+        Ok(()) => {
+            let event = if !was_closed && updated.status.is_closed() {
+                WebhookEvent::Closed
+            } else {
+                WebhookEvent::Updated
+            };
+            repo.emit(event, &updated);
+            (StatusCode::OK, Json(ApiResponse::ok(updated)))
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ApiResponse::<Issue>::err(e.to_string())),
@@ -317,13 +784,13 @@ async fn update_issue(
 
 /// Close an issue
 async fn close_issue(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
+    RepoCtx(repo): RepoCtx,
+    Path(IssuePath { id }): Path<IssuePath>,
     Json(req): Json<CloseIssueRequest>,
 ) -> impl IntoResponse {
-    let mut store = state.store.write().unwrap();
+    let mut store = repo.store.write().unwrap();
 
-    let issue = match store.get_mut(&id) {
+    let mut issue = match store.get(&id).cloned() {
         Some(i) => i,
         None => {
             return (
@@ -336,8 +803,11 @@ async fn close_issue(
     issue.close(req.reason);
     let closed = issue.clone();
 
-    match store.save() {
-        Ok(()) => (StatusCode::OK, Json(ApiResponse::ok(closed))),
+    match store.update(issue) {
+        Ok(()) => {
+            repo.emit(WebhookEvent::Closed, &closed);
+            (StatusCode::OK, Json(ApiResponse::ok(closed)))
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ApiResponse::<Issue>::err(e.to_string())),
@@ -347,17 +817,22 @@ async fn close_issue(
 
 /// Delete an issue (tombstone)
 async fn delete_issue(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
+    RepoCtx(repo): RepoCtx,
+    Path(IssuePath { id }): Path<IssuePath>,
     axum::extract::Query(req): axum::extract::Query<DeleteIssueRequest>,
 ) -> impl IntoResponse {
-    let mut store = state.store.write().unwrap();
+    let mut store = repo.store.write().unwrap();
 
     match store.delete(&id, req.by, req.reason) {
-        Ok(()) => (
-            StatusCode::OK,
-            Json(ApiResponse::ok(serde_json::json!({ "deleted": id }))),
-        ),
+        Ok(()) => {
+            if let Some(issue) = store.get(&id) {
+                repo.emit(WebhookEvent::Deleted, issue);
+            }
+            (
+                StatusCode::OK,
+                Json(ApiResponse::ok(serde_json::json!({ "deleted": id }))),
+            )
+        }
         Err(trx_core::Error::NotFound(_)) => (
             StatusCode::NOT_FOUND,
             Json(ApiResponse::<serde_json::Value>::err(format!(
@@ -372,6 +847,99 @@ async fn delete_issue(
     }
 }
 
+/// Apply a batch of operations atomically
+///
+/// The whole batch is validated before anything is written. On success the
+/// store is saved once and, with `auto_sync` enabled, committed once.
+async fn batch_issues(RepoCtx(repo): RepoCtx, Json(req): Json<BatchRequest>) -> impl IntoResponse {
+    if req.operations.is_empty() || req.operations.len() > MAX_BATCH_OPS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<BatchOutcome>::err(format!(
+                "Batch must contain between 1 and {} operations",
+                MAX_BATCH_OPS
+            ))),
+        );
+    }
+
+    let mut store = repo.store.write().unwrap();
+    // Status updates close an issue only if it was not closed already
+    let closed_before: Vec<bool> = req
+        .operations
+        .iter()
+        .map(|op| match op {
+            BatchOp::Update { id, .. } => store.get(id).is_some_and(|i| i.status.is_closed()),
+            _ => false,
+        })
+        .collect();
+    let outcome = match store.apply_batch(&req.operations) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::err(e.to_string())),
+            );
+        }
+    };
+
+    if !outcome.applied {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ApiResponse::err_with(
+                outcome,
+                "Batch rejected; no changes were applied",
+            )),
+        );
+    }
+
+    auto_sync(&store, &outcome);
+    for result in &outcome.results {
+        let Some(issue) = result.id.as_deref().and_then(|id| store.get(id)) else {
+            continue;
+        };
+        let closes = matches!(
+            &req.operations[result.index],
+            BatchOp::Update {
+                status: Some(_),
+                ..
+            }
+        ) && !closed_before[result.index]
+            && issue.status == Status::Closed;
+        let event = match result.op {
+            "create" => WebhookEvent::Created,
+            "close" => WebhookEvent::Closed,
+            "delete" => WebhookEvent::Deleted,
+            "update" if closes => WebhookEvent::Closed,
+            _ => WebhookEvent::Updated,
+        };
+        repo.emit(event, issue);
+    }
+    (StatusCode::OK, Json(ApiResponse::ok(outcome)))
+}
+
+/// Commit .trx/ after a batch if `auto_sync` is enabled
+fn auto_sync(store: &UnifiedStore, outcome: &BatchOutcome) {
+    let trx_dir = store.trx_dir();
+    let config = match Config::load(&trx_dir.join("config.toml")) {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!("Skipping auto-sync, failed to load config: {}", e);
+            return;
+        }
+    };
+    if !config.auto_sync {
+        return;
+    }
+
+    let ids = outcome.touched_ids().join(", ");
+    let title = format!("{} operations", outcome.results.len());
+    let message =
+        trx_core::sync::render_message(&config.sync_message_template, "batch", &ids, &title);
+    if let Err(e) = trx_core::sync::commit(&trx_dir, &message) {
+        tracing::warn!("Auto-sync failed: {}", e);
+    }
+}
+
 /// Add a dependency to an issue
 #[derive(Debug, Deserialize)]
 struct AddDependencyRequest {
@@ -381,11 +949,11 @@ struct AddDependencyRequest {
 }
 
 async fn add_dependency(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
+    RepoCtx(repo): RepoCtx,
+    Path(IssuePath { id }): Path<IssuePath>,
     Json(req): Json<AddDependencyRequest>,
 ) -> impl IntoResponse {
-    let mut store = state.store.write().unwrap();
+    let mut store = repo.store.write().unwrap();
 
     // Check that target exists
     if store.get(&req.depends_on).is_none() {
@@ -398,7 +966,7 @@ async fn add_dependency(
         );
     }
 
-    let issue = match store.get_mut(&id) {
+    let mut issue = match store.get(&id).cloned() {
         Some(i) => i,
         None => {
             return (
@@ -421,8 +989,11 @@ async fn add_dependency(
     issue.add_dependency(req.depends_on, dep_type);
     let updated = issue.clone();
 
-    match store.save() {
-        Ok(()) => (StatusCode::OK, Json(ApiResponse::ok(updated))),
+    match store.update(issue) {
+        Ok(()) => {
+            repo.emit(WebhookEvent::Updated, &updated);
+            (StatusCode::OK, Json(ApiResponse::ok(updated)))
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ApiResponse::<Issue>::err(e.to_string())),
@@ -432,12 +1003,12 @@ async fn add_dependency(
 
 /// Remove a dependency from an issue
 async fn remove_dependency(
-    State(state): State<Arc<AppState>>,
-    Path((id, dep_id)): Path<(String, String)>,
+    RepoCtx(repo): RepoCtx,
+    Path(DependencyPath { id, dep_id }): Path<DependencyPath>,
 ) -> impl IntoResponse {
-    let mut store = state.store.write().unwrap();
+    let mut store = repo.store.write().unwrap();
 
-    let issue = match store.get_mut(&id) {
+    let mut issue = match store.get(&id).cloned() {
         Some(i) => i,
         None => {
             return (
@@ -450,8 +1021,11 @@ async fn remove_dependency(
     issue.remove_dependency(&dep_id);
     let updated = issue.clone();
 
-    match store.save() {
-        Ok(()) => (StatusCode::OK, Json(ApiResponse::ok(updated))),
+    match store.update(issue) {
+        Ok(()) => {
+            repo.emit(WebhookEvent::Updated, &updated);
+            (StatusCode::OK, Json(ApiResponse::ok(updated)))
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ApiResponse::<Issue>::err(e.to_string())),
@@ -459,55 +1033,377 @@ async fn remove_dependency(
     }
 }
 
+/// List registered repositories
+async fn list_repos(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let default = state.repos.default_name();
+    let repos: Vec<_> = state
+        .repos
+        .all()
+        .iter()
+        .map(|repo| {
+            let store = repo.store.read().unwrap();
+            serde_json::json!({
+                "name": repo.name,
+                "root": repo.root,
+                "storage_version": store.version(),
+                "default": default.as_deref() == Some(repo.name.as_str()),
+                "open_issues": store.list_open().len(),
+            })
+        })
+        .collect();
+
+    Json(ApiResponse::ok(repos))
+}
+
+/// Register a repository with the running daemon
+///
+/// The registration is recorded in the service state dir, so the daemon
+/// serves the repository again after a restart.
+async fn register_repo(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RegisterRepoRequest>,
+) -> impl IntoResponse {
+    match state.repos.register(req.name, &req.path) {
+        Ok(repo) => {
+            tracing::info!("Serving {} from {}", repo.name, repo.root.display());
+            state.registrations.add(&repo);
+            (
+                StatusCode::CREATED,
+                Json(ApiResponse::ok(serde_json::json!({
+                    "name": repo.name,
+                    "root": repo.root,
+                }))),
+            )
+        }
+        Err(e @ RegisterError::NameTaken(_)) => {
+            (StatusCode::CONFLICT, Json(ApiResponse::err(e.to_string())))
+        }
+        Err(RegisterError::Open(trx_core::Error::NotInitialized)) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::err(format!(
+                "{} is not a trx repository",
+                req.path.display()
+            ))),
+        ),
+        Err(RegisterError::Open(e)) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::err(format!(
+                "Cannot open {}: {}",
+                req.path.display(),
+                e
+            ))),
+        ),
+    }
+}
+
+/// Routes for a single repository, mounted at `/` and `/repos/{repo}`
+fn repo_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/issues", get(list_issues).post(create_issue))
+        .route("/issues/ready", get(list_ready))
+        .route("/issues/batch", post(batch_issues))
+        .route("/graph", get(get_graph))
+        .route("/graph/cycles", get(get_cycles))
+        .route("/graph/critical-path", get(get_critical_path))
+        .route("/insights", get(get_insights))
+        .route(
+            "/issues/{id}",
+            get(get_issue).patch(update_issue).delete(delete_issue),
+        )
+        .route("/issues/{id}/close", post(close_issue))
+        .route("/issues/{id}/blockers", get(list_blockers))
+        .route("/issues/{id}/dependencies", post(add_dependency))
+        .route(
+            "/issues/{id}/dependencies/{dep_id}",
+            delete(remove_dependency),
+        )
+}
+
 fn init_tracing() {
+    use std::io::IsTerminal;
+
     let filter = std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
     tracing_subscriber::fmt()
         .with_env_filter(filter)
         .with_target(false)
+        .with_ansi(std::io::stdout().is_terminal())
         .init();
 }
 
+/// Bind the listener
+///
+/// An explicit `TRX_API_PORT` must be available. Otherwise the default port
+/// is preferred, falling back to any free port if it is taken.
+async fn bind() -> anyhow::Result<tokio::net::TcpListener> {
+    let explicit: Option<u16> = std::env::var("TRX_API_PORT")
+        .ok()
+        .and_then(|p| p.parse().ok());
+    let port = explicit.unwrap_or(DEFAULT_PORT);
+
+    match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => Ok(listener),
+        Err(e) if explicit.is_none() && e.kind() == std::io::ErrorKind::AddrInUse => {
+            tracing::warn!("Port {} is in use, picking another", port);
+            Ok(tokio::net::TcpListener::bind(("127.0.0.1", 0)).await?)
+        }
+        Err(e) => Err(anyhow::anyhow!("Failed to bind port {}: {}", port, e)),
+    }
+}
+
+/// Resolve when SIGINT or SIGTERM is received
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.ok();
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutting down");
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    if let Some(ApiCommand::WebhookTest { name, event }) = &args.command {
+        let record = delivery::send_test(name, event.parse()?)?;
+        println!("{}", serde_json::to_string(&record)?);
+        return Ok(());
+    }
     init_tracing();
 
-    // Open the store
-    let store = Store::open().map_err(|e| anyhow::anyhow!("Failed to open store: {}", e))?;
+    let manager = ServiceManager::new()
+        .map_err(|e| anyhow::anyhow!("Failed to initialize service manager: {}", e))?;
+    if manager.is_running() {
+        let pid = manager.read_pid().unwrap_or_default();
+        anyhow::bail!(
+            "trx-api is already running (PID: {}); use 'trx service start' to add a repository to it",
+            pid
+        );
+    }
+
+    let delivery_log = Some(Arc::new(DeliveryLog::new(manager.webhook_log_file())));
+    let registry = Registry::new(delivery_log);
+
+    // Collect repositories from the workspace file and --repo flags,
+    // falling back to the repository containing the current directory
+    let mut workspace = match &args.workspace {
+        Some(path) => {
+            Workspace::load(path).map_err(|e| anyhow::anyhow!("Failed to load workspace: {}", e))?
+        }
+        None => Workspace::default(),
+    };
+    for root in &args.repos {
+        workspace
+            .add(trx_core::WorkspaceRepo::from_root(root.clone()))
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+    }
+    if workspace.repos.is_empty() {
+        let root = UnifiedStore::find_root()
+            .map_err(|e| anyhow::anyhow!("Failed to open store: {}", e))?;
+        let mut repo = trx_core::WorkspaceRepo::from_root(root);
+        if let Some(name) = args.name {
+            repo.name = name;
+        }
+        workspace.repos.push(repo);
+    }
+
+    for repo in workspace.repos {
+        let repo = registry
+            .register(Some(repo.name.clone()), &repo.root)
+            .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", repo.root.display(), e))?;
+        tracing::info!("Serving {} from {}", repo.name, repo.root.display());
+    }
+
+    // Serve again the repositories registered with a previous instance,
+    // forgetting those that no longer open
+    let registrations = Registrations::load(manager.repos_file());
+    for saved in registrations.repos() {
+        match registry.register(Some(saved.name.clone()), &saved.root) {
+            Ok(repo) => tracing::info!("Serving {} from {}", repo.name, repo.root.display()),
+            Err(e) => {
+                tracing::warn!("Dropping registered {}: {}", saved.root.display(), e);
+                registrations.retain(|r| r.root != saved.root);
+            }
+        }
+    }
 
     let state = Arc::new(AppState {
-        store: RwLock::new(store),
+        repos: registry,
+        registrations,
+        metrics: Metrics::default(),
     });
+    let shutdown_state = state.clone();
 
     // Build router
     let app = Router::new()
         .route("/health", get(health))
-        .route("/issues", get(list_issues).post(create_issue))
-        .route("/issues/ready", get(list_ready))
-        .route(
-            "/issues/{id}",
-            get(get_issue).patch(update_issue).delete(delete_issue),
-        )
-        .route("/issues/{id}/close", post(close_issue))
-        .route("/issues/{id}/dependencies", post(add_dependency))
-        .route(
-            "/issues/{id}/dependencies/{dep_id}",
-            delete(remove_dependency),
-        )
+        .route("/metrics", get(metrics::metrics))
+        .route("/repos", get(list_repos).post(register_repo))
+        .merge(repo_routes())
+        .nest("/repos/{repo}", repo_routes())
+        .merge(ui::routes())
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            metrics::track_requests,
+        ))
         .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any))
         .layer(TraceLayer::new_for_http())
         .with_state(state);
 
-    // Get port from env or default
-    let port: u16 = std::env::var("TRX_API_PORT")
-        .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(3847);
+    let result = match args.socket {
+        Some(socket) => {
+            let socket = socket.unwrap_or_else(|| manager.default_socket());
+            serve_unix(&manager, app, &socket).await
+        }
+        None => serve_tcp(&manager, app).await,
+    };
 
-    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    shutdown_state.repos.flush(DELIVERY_GRACE).await;
+    manager.cleanup();
+    result
+}
+
+/// Serve over TCP, recording the chosen port
+async fn serve_tcp(manager: &ServiceManager, app: Router) -> anyhow::Result<()> {
+    let listener = bind().await?;
+    let addr = listener.local_addr()?;
     tracing::info!("Starting trx-api on {}", addr);
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    manager.write_pid(std::process::id())?;
+    manager.write_port(addr.port())?;
+    std::fs::remove_file(manager.socket_file()).ok();
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+    Ok(())
+}
+
+/// Serve over a Unix socket, recording its path
+///
+/// The socket is only accessible to the current user, which stands in for
+/// authentication on local connections.
+#[cfg(unix)]
+async fn serve_unix(
+    manager: &ServiceManager,
+    app: Router,
+    socket: &std::path::Path,
+) -> anyhow::Result<()> {
+    let listener = bind_unix(socket)?;
+    tracing::info!("Starting trx-api on {}", socket.display());
+
+    manager.write_pid(std::process::id())?;
+    manager.write_socket(socket)?;
+    std::fs::remove_file(manager.port_file()).ok();
 
+    let result = axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await;
+    std::fs::remove_file(socket).ok();
+    result?;
     Ok(())
 }
+
+/// Bind a Unix socket readable and writable only by the current user
+///
+/// The socket is bound inside a fresh 0700 directory and moved into place
+/// once its mode is 0600, so it is never reachable with the umask's
+/// permissions. Missing parent directories are created 0700.
+#[cfg(unix)]
+fn bind_unix(socket: &std::path::Path) -> anyhow::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let parent = socket
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(parent)?;
+    // A leftover socket from a crashed instance would make the move fail
+    if socket.exists() {
+        if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+            anyhow::bail!("{} is in use by another server", socket.display());
+        }
+        std::fs::remove_file(socket)?;
+    }
+
+    let staging = parent.join(format!(".trx-api-{}", std::process::id()));
+    std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+    let staged = staging.join("sock");
+    let bound = tokio::net::UnixListener::bind(&staged)
+        .map_err(anyhow::Error::from)
+        .and_then(|listener| {
+            std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::rename(&staged, socket)?;
+            Ok(listener)
+        });
+    std::fs::remove_file(&staged).ok();
+    std::fs::remove_dir(&staging).ok();
+    bound
+}
+
+#[cfg(not(unix))]
+async fn serve_unix(
+    _manager: &ServiceManager,
+    _app: Router,
+    _socket: &std::path::Path,
+) -> anyhow::Result<()> {
+    anyhow::bail!("Unix sockets are not supported on this platform")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_bind_unix_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("run/trx-api.sock");
+
+        let listener = bind_unix(&socket).unwrap();
+        let mode =
+            |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&socket), 0o600);
+        assert_eq!(mode(socket.parent().unwrap()), 0o700);
+        let entries: Vec<_> = std::fs::read_dir(dir.path().join("run"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, ["trx-api.sock"]);
+
+        // The socket still accepts connections after being moved into place
+        let client = tokio::net::UnixStream::connect(&socket);
+        let (accepted, connected) = tokio::join!(listener.accept(), client);
+        accepted.unwrap();
+        connected.unwrap();
+
+        // A live socket is refused, a stale one replaced
+        assert!(bind_unix(&socket).is_err());
+        drop(listener);
+        let listener = bind_unix(&socket).unwrap();
+        let client = tokio::net::UnixStream::connect(&socket);
+        let (accepted, connected) = tokio::join!(listener.accept(), client);
+        accepted.unwrap();
+        connected.unwrap();
+    }
+}
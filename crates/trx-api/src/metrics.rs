@@ -0,0 +1,307 @@
+//! Prometheus metrics
+//!
+//! `/metrics` serves the text exposition format. Backlog gauges are computed
+//! from the stores on each scrape; request and mutation counters are kept in
+//! memory since the daemon started.
+
+use crate::AppState;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use trx_core::{Issue, IssueGraph};
+
+/// Content type of the Prometheus text format
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Labels and value of one sample
+type Sample = (Vec<(&'static str, String)>, f64);
+
+/// A metric family: one `# HELP`/`# TYPE` header and its samples
+struct Family {
+    name: &'static str,
+    help: &'static str,
+    kind: &'static str,
+    samples: Vec<Sample>,
+}
+
+impl Family {
+    fn gauge(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            kind: "gauge",
+            samples: Vec::new(),
+        }
+    }
+
+    fn counter(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            kind: "counter",
+            samples: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, labels: Vec<(&'static str, String)>, value: f64) {
+        self.samples.push((labels, value));
+    }
+
+    fn render(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} {}", self.name, self.kind);
+        for (labels, value) in &self.samples {
+            out.push_str(self.name);
+            if !labels.is_empty() {
+                let labels: Vec<_> = labels
+                    .iter()
+                    .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+                    .collect();
+                let _ = write!(out, "{{{}}}", labels.join(","));
+            }
+            let _ = writeln!(out, " {}", value);
+        }
+    }
+}
+
+/// Escape a label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Count issues by a label value
+fn count_by(issues: &[&Issue], key: impl Fn(&Issue) -> String) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for issue in issues {
+        *counts.entry(key(issue)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Backlog gauges, one sample per repository
+struct Backlog {
+    by_status: Family,
+    by_type: Family,
+    by_priority: Family,
+    by_assignee: Family,
+    ready: Family,
+    blocked: Family,
+    cycles: Family,
+    oldest: Family,
+}
+
+impl Backlog {
+    fn new() -> Self {
+        Self {
+            by_status: Family::gauge("trx_open_issues_by_status", "Open issues by status."),
+            by_type: Family::gauge("trx_open_issues_by_type", "Open issues by type."),
+            by_priority: Family::gauge("trx_open_issues_by_priority", "Open issues by priority."),
+            by_assignee: Family::gauge(
+                "trx_open_issues_by_assignee",
+                "Open issues by assignee (empty when unassigned).",
+            ),
+            ready: Family::gauge(
+                "trx_ready_issues",
+                "Open, unclaimed issues with no open blockers.",
+            ),
+            blocked: Family::gauge("trx_blocked_issues", "Open issues with open blockers."),
+            cycles: Family::gauge(
+                "trx_dependency_cycles",
+                "Dependency cycles among open issues.",
+            ),
+            oldest: Family::gauge(
+                "trx_oldest_open_issue_age_seconds",
+                "Age of the oldest open P0/P1 issue.",
+            ),
+        }
+    }
+
+    /// Add the samples for one repository
+    fn add_repo(&mut self, repo: &str, open: &[&Issue], now: DateTime<Utc>) {
+        let labels =
+            |key: &'static str, value: String| vec![("repo", repo.to_string()), (key, value)];
+
+        for (status, n) in count_by(open, |i| i.status.to_string()) {
+            self.by_status.add(labels("status", status), n as f64);
+        }
+        for (issue_type, n) in count_by(open, |i| i.issue_type.to_string()) {
+            self.by_type.add(labels("type", issue_type), n as f64);
+        }
+        for (priority, n) in count_by(open, |i| i.priority.to_string()) {
+            self.by_priority.add(labels("priority", priority), n as f64);
+        }
+        for (assignee, n) in count_by(open, |i| i.assignee.clone().unwrap_or_default()) {
+            self.by_assignee.add(labels("assignee", assignee), n as f64);
+        }
+
+        let graph = IssueGraph::from_issues(open);
+        let ready = graph.ready_issues(open).len();
+        let blocked = graph.blocked_issues(open).len();
+        let repo_label = vec![("repo", repo.to_string())];
+        self.ready.add(repo_label.clone(), ready as f64);
+        self.blocked.add(repo_label.clone(), blocked as f64);
+        self.cycles
+            .add(repo_label, graph.find_cycles().len() as f64);
+
+        for priority in [0, 1] {
+            if let Some(oldest) = open
+                .iter()
+                .filter(|i| i.priority == priority)
+                .map(|i| i.created_at)
+                .min()
+            {
+                let age = (now - oldest).num_seconds().max(0);
+                self.oldest
+                    .add(labels("priority", priority.to_string()), age as f64);
+            }
+        }
+    }
+
+    fn render(&self, out: &mut String) {
+        for family in [
+            &self.by_status,
+            &self.by_type,
+            &self.by_priority,
+            &self.by_assignee,
+            &self.ready,
+            &self.blocked,
+            &self.cycles,
+            &self.oldest,
+        ] {
+            family.render(out);
+        }
+    }
+}
+
+/// Request counters, keyed by method, route and status
+#[derive(Default)]
+pub struct Metrics {
+    requests: Mutex<BTreeMap<(String, String, u16), u64>>,
+}
+
+impl Metrics {
+    fn record_request(&self, method: &str, route: &str, status: u16) {
+        let mut requests = self.requests.lock().unwrap();
+        *requests
+            .entry((method.to_string(), route.to_string(), status))
+            .or_insert(0) += 1;
+    }
+
+    fn render(&self, out: &mut String) {
+        let mut family = Family::counter(
+            "trx_api_requests_total",
+            "API requests handled, by method, route and status.",
+        );
+        for ((method, route, status), n) in self.requests.lock().unwrap().iter() {
+            family.add(
+                vec![
+                    ("method", method.clone()),
+                    ("route", route.clone()),
+                    ("status", status.to_string()),
+                ],
+                *n as f64,
+            );
+        }
+        family.render(out);
+    }
+}
+
+/// Middleware counting every request
+pub async fn track_requests(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+    state
+        .metrics
+        .record_request(&method, &route, response.status().as_u16());
+    response
+}
+
+/// Serve all metrics in Prometheus text format
+pub async fn metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let now = Utc::now();
+    let repos = state.repos.all();
+
+    let mut backlog = Backlog::new();
+    let mut mutations = Family::counter(
+        "trx_issue_mutations_total",
+        "Issue mutations made through the API, by event.",
+    );
+    for repo in &repos {
+        let store = repo.store.read().unwrap();
+        backlog.add_repo(&repo.name, &store.list_open(), now);
+        for (event, n) in repo.mutations() {
+            mutations.add(
+                vec![("repo", repo.name.clone()), ("event", event)],
+                n as f64,
+            );
+        }
+    }
+
+    let mut out = String::new();
+    backlog.render(&mut out);
+    state.metrics.render(&mut out);
+    mutations.render(&mut out);
+
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trx_core::{Dependency, DependencyType};
+
+    #[test]
+    fn test_backlog_gauges() {
+        let now = Utc::now();
+        let mut a = Issue::new("trx-a".into(), "A".into());
+        a.priority = 0;
+        a.assignee = Some("kim".into());
+        a.created_at = now - chrono::Duration::seconds(90);
+        let mut b = Issue::new("trx-b".into(), "Say \"hi\"".into());
+        b.dependencies.push(Dependency {
+            issue_id: "trx-b".into(),
+            depends_on_id: "trx-a".into(),
+            dep_type: DependencyType::Blocks,
+            created_at: now,
+            created_by: None,
+        });
+        // Claimed work is neither ready nor blocked
+        let mut c = Issue::new("trx-c".into(), "C".into());
+        c.claim("agent-1", now + chrono::Duration::minutes(5));
+
+        let mut backlog = Backlog::new();
+        backlog.add_repo("web \"app\"", &[&a, &b, &c], now);
+        let mut out = String::new();
+        backlog.render(&mut out);
+
+        assert!(out.contains("# TYPE trx_ready_issues gauge\n"));
+        assert!(out.contains("trx_ready_issues{repo=\"web \\\"app\\\"\"} 1\n"));
+        assert!(out.contains("trx_blocked_issues{repo=\"web \\\"app\\\"\"} 1\n"));
+        assert!(out.contains(",assignee=\"kim\"} 1\n"));
+        assert!(out.contains(",assignee=\"\"} 1\n"));
+        assert!(out.contains(",priority=\"0\"} 90\n"));
+        assert!(!out.contains(
+            "trx_oldest_open_issue_age_seconds{repo=\"web \\\"app\\\"\",priority=\"1\"}"
+        ));
+    }
+}
@@ -0,0 +1,414 @@
+//! Repository registry
+//!
+//! One trx-api daemon serves any number of repositories. Each is mounted
+//! under `/repos/{name}`; the unprefixed routes use the default repository,
+//! which is the first one registered.
+
+use crate::delivery::{self, RetryPolicy};
+use crate::{ApiResponse, AppState};
+use axum::{
+    Json,
+    extract::{FromRequestParts, Path},
+    http::{StatusCode, request::Parts},
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::task::JoinSet;
+use trx_core::webhook::{DeliveryLog, Payload};
+use trx_core::{
+    Config, Filter, Issue, UnifiedStore, WebhookConfig, WebhookEvent, Workspace, WorkspaceRepo,
+};
+
+/// A repository served by the daemon
+pub struct Repo {
+    pub name: String,
+    pub root: PathBuf,
+    pub store: RwLock<UnifiedStore>,
+    webhooks: Vec<WebhookConfig>,
+    delivery_log: Option<Arc<DeliveryLog>>,
+    deliveries: Deliveries,
+    mutations: Mutex<BTreeMap<String, u64>>,
+}
+
+/// Webhook deliveries still running, shared by every repository
+type Deliveries = Arc<Mutex<JoinSet<()>>>;
+
+impl Repo {
+    /// Open the store and webhook config for a repository root
+    fn open(
+        name: String,
+        root: PathBuf,
+        delivery_log: Option<Arc<DeliveryLog>>,
+        deliveries: Deliveries,
+    ) -> trx_core::Result<Self> {
+        let store = UnifiedStore::open_at(&root)?;
+        let config = Config::load(&store.trx_dir().join("config.toml"))?;
+        for hook in &config.webhooks {
+            if let Some(filter) = &hook.filter
+                && let Err(e) = filter.parse::<Filter>()
+            {
+                tracing::warn!("Webhook {} has an invalid filter: {}", hook.name, e);
+            }
+        }
+
+        Ok(Self {
+            name,
+            root,
+            store: RwLock::new(store),
+            webhooks: config.webhooks,
+            delivery_log,
+            deliveries,
+            mutations: Mutex::default(),
+        })
+    }
+
+    /// Mutation counts by event since the daemon started
+    pub fn mutations(&self) -> BTreeMap<String, u64> {
+        self.mutations.lock().unwrap().clone()
+    }
+
+    /// Record an issue event and fire matching webhooks in the background
+    pub fn emit(&self, event: WebhookEvent, issue: &Issue) {
+        *self
+            .mutations
+            .lock()
+            .unwrap()
+            .entry(event.to_string())
+            .or_insert(0) += 1;
+
+        for hook in &self.webhooks {
+            match hook.wants(event, issue) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::warn!("Webhook {} has an invalid filter: {}", hook.name, e);
+                    continue;
+                }
+            }
+
+            let hook = hook.clone();
+            let payload = Payload::new(event, issue.clone());
+            let log = self.delivery_log.clone();
+            let mut deliveries = self.deliveries.lock().unwrap();
+            // Reap finished deliveries so the set only holds running ones
+            while deliveries.try_join_next().is_some() {}
+            deliveries.spawn_blocking(move || {
+                let record = delivery::deliver(&hook, &payload, RetryPolicy::default());
+                if !record.success {
+                    tracing::warn!(
+                        "Webhook {} failed after {} attempts: {}",
+                        hook.name,
+                        record.attempts,
+                        record.error.as_deref().unwrap_or("unknown error")
+                    );
+                }
+                if let Some(log) = log
+                    && let Err(e) = log.append(&record)
+                {
+                    tracing::warn!("Failed to write webhook delivery log: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// Why a repository could not be registered
+#[derive(Debug, thiserror::Error)]
+pub enum RegisterError {
+    #[error("A different repository is already registered as '{0}'")]
+    NameTaken(String),
+
+    #[error(transparent)]
+    Open(#[from] trx_core::Error),
+}
+
+/// All repositories served by the daemon
+pub struct Registry {
+    repos: RwLock<BTreeMap<String, Arc<Repo>>>,
+    default: RwLock<Option<String>>,
+    delivery_log: Option<Arc<DeliveryLog>>,
+    deliveries: Deliveries,
+}
+
+impl Registry {
+    pub fn new(delivery_log: Option<Arc<DeliveryLog>>) -> Self {
+        Self {
+            repos: RwLock::new(BTreeMap::new()),
+            default: RwLock::new(None),
+            delivery_log,
+            deliveries: Deliveries::default(),
+        }
+    }
+
+    /// Register a repository root, returning the mounted repository
+    ///
+    /// Registering an already-served root is a no-op. The name defaults to
+    /// the root's directory name and must be unique.
+    pub fn register(
+        &self,
+        name: Option<String>,
+        root: &FsPath,
+    ) -> Result<Arc<Repo>, RegisterError> {
+        let root = root.canonicalize().map_err(trx_core::Error::from)?;
+        let name = name.unwrap_or_else(|| trx_core::workspace::repo_name(&root));
+
+        let mut repos = self.repos.write().unwrap();
+        if let Some(existing) = repos.values().find(|r| r.root == root) {
+            return Ok(existing.clone());
+        }
+        if repos.contains_key(&name) {
+            return Err(RegisterError::NameTaken(name));
+        }
+
+        let repo = Arc::new(Repo::open(
+            name.clone(),
+            root,
+            self.delivery_log.clone(),
+            self.deliveries.clone(),
+        )?);
+        repos.insert(name.clone(), repo.clone());
+        self.default.write().unwrap().get_or_insert(name);
+        Ok(repo)
+    }
+
+    /// Look up a repository by name
+    pub fn get(&self, name: &str) -> Option<Arc<Repo>> {
+        self.repos.read().unwrap().get(name).cloned()
+    }
+
+    /// The repository used by unprefixed routes
+    pub fn default_repo(&self) -> Option<Arc<Repo>> {
+        let default = self.default.read().unwrap();
+        default.as_deref().and_then(|name| self.get(name))
+    }
+
+    /// Name of the default repository
+    pub fn default_name(&self) -> Option<String> {
+        self.default.read().unwrap().clone()
+    }
+
+    /// All repositories, ordered by name
+    pub fn all(&self) -> Vec<Arc<Repo>> {
+        self.repos.read().unwrap().values().cloned().collect()
+    }
+
+    /// Wait for in-flight writes to every store and for webhook deliveries
+    /// to finish
+    ///
+    /// Stores persist on each write, so once every write lock has been
+    /// acquired there is nothing left to flush. Deliveries retry for a
+    /// while, so they get at most `timeout` before being abandoned.
+    pub async fn flush(&self, timeout: Duration) {
+        for repo in self.all() {
+            drop(repo.store.write().unwrap());
+        }
+
+        let mut deliveries = std::mem::take(&mut *self.deliveries.lock().unwrap());
+        let pending = async { while deliveries.join_next().await.is_some() {} };
+        if tokio::time::timeout(timeout, pending).await.is_err() {
+            tracing::warn!(
+                "Abandoning {} webhook deliveries still in progress",
+                deliveries.len()
+            );
+        }
+    }
+}
+
+/// Repositories registered through `POST /repos`
+///
+/// Kept in a workspace file in the service state dir so a restarted daemon
+/// serves them again.
+pub struct Registrations {
+    path: PathBuf,
+    workspace: Mutex<Workspace>,
+}
+
+impl Registrations {
+    /// Load the registrations file, starting empty if it is missing or invalid
+    pub fn load(path: PathBuf) -> Self {
+        let workspace = if path.exists() {
+            Workspace::load(&path).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring {}: {}", path.display(), e);
+                Workspace::default()
+            })
+        } else {
+            Workspace::default()
+        };
+        Self {
+            path,
+            workspace: Mutex::new(workspace),
+        }
+    }
+
+    /// The recorded repositories
+    pub fn repos(&self) -> Vec<WorkspaceRepo> {
+        self.workspace.lock().unwrap().repos.clone()
+    }
+
+    /// Record a repository unless its root is already recorded
+    pub fn add(&self, repo: &Repo) {
+        let mut workspace = self.workspace.lock().unwrap();
+        if workspace.repos.iter().any(|r| r.root == repo.root) {
+            return;
+        }
+        workspace.repos.push(WorkspaceRepo {
+            name: repo.name.clone(),
+            root: repo.root.clone(),
+        });
+        self.save(&workspace);
+    }
+
+    /// Drop the recorded repositories `keep` rejects
+    pub fn retain(&self, keep: impl Fn(&WorkspaceRepo) -> bool) {
+        let mut workspace = self.workspace.lock().unwrap();
+        let before = workspace.repos.len();
+        workspace.repos.retain(keep);
+        if workspace.repos.len() != before {
+            self.save(&workspace);
+        }
+    }
+
+    fn save(&self, workspace: &Workspace) {
+        if let Err(e) = workspace.save(&self.path) {
+            tracing::warn!("Failed to write {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// The repository a request targets
+///
+/// Taken from the `{repo}` segment of `/repos/{repo}/...`, falling back to
+/// the default repository on unprefixed routes.
+pub struct RepoCtx(pub Arc<Repo>);
+
+impl FromRequestParts<Arc<AppState>> for RepoCtx {
+    type Rejection = (StatusCode, Json<ApiResponse<()>>);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let params = Path::<HashMap<String, String>>::from_request_parts(parts, state)
+            .await
+            .map(|Path(params)| params)
+            .unwrap_or_default();
+
+        let repo = match params.get("repo") {
+            Some(name) => state.repos.get(name).ok_or_else(|| {
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::err(format!("Repository {} not found", name))),
+                )
+            })?,
+            None => state.repos.default_repo().ok_or_else(|| {
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::err("No repositories registered")),
+                )
+            })?,
+        };
+
+        Ok(RepoCtx(repo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trx_core::StorageVersion;
+
+    fn init_repo(root: &FsPath) {
+        UnifiedStore::init_at(root, "trx", StorageVersion::V1).unwrap();
+    }
+
+    #[test]
+    fn test_register_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo(&dir.path().join("api"));
+        init_repo(&dir.path().join("other/api"));
+        std::fs::create_dir_all(dir.path().join("plain")).unwrap();
+        let registry = Registry::new(None);
+
+        let repo = registry.register(None, &dir.path().join("api")).unwrap();
+        assert_eq!(repo.name, "api");
+        assert!(matches!(
+            registry.register(None, &dir.path().join("other/api")),
+            Err(RegisterError::NameTaken(_))
+        ));
+        assert!(matches!(
+            registry.register(None, &dir.path().join("missing")),
+            Err(RegisterError::Open(trx_core::Error::Io(_)))
+        ));
+        assert!(matches!(
+            registry.register(None, &dir.path().join("plain")),
+            Err(RegisterError::Open(trx_core::Error::NotInitialized))
+        ));
+
+        let repo = registry
+            .register(Some("other".into()), &dir.path().join("other/api"))
+            .unwrap();
+        assert_eq!(repo.name, "other");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_flush_waits_for_deliveries() {
+        async fn slow() -> axum::http::StatusCode {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            axum::http::StatusCode::NO_CONTENT
+        }
+        let app = axum::Router::new().route("/hook", axum::routing::post(slow));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("api");
+        init_repo(&root);
+        let config_path = root.join(".trx/config.toml");
+        let config = std::fs::read_to_string(&config_path).unwrap();
+        std::fs::write(
+            &config_path,
+            format!(
+                "{}[[webhooks]]\nname = \"slow\"\nurl = \"http://{}/hook\"\n",
+                config, addr
+            ),
+        )
+        .unwrap();
+
+        let log_path = dir.path().join("webhooks.jsonl");
+        let registry = Registry::new(Some(Arc::new(DeliveryLog::new(log_path.clone()))));
+        let repo = registry.register(None, &root).unwrap();
+        let issue = Issue::new("trx-abcd".into(), "Slow hook".into());
+        repo.emit(WebhookEvent::Created, &issue);
+        repo.emit(WebhookEvent::Closed, &issue);
+
+        registry.flush(Duration::from_secs(5)).await;
+        let logged = std::fs::read_to_string(&log_path).unwrap();
+        assert_eq!(logged.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_registrations_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo(&dir.path().join("api"));
+        let path = dir.path().join("trx-api.repos.yaml");
+        let registry = Registry::new(None);
+        let repo = registry
+            .register(Some("backend".into()), &dir.path().join("api"))
+            .unwrap();
+
+        let registrations = Registrations::load(path.clone());
+        registrations.add(&repo);
+        registrations.add(&repo);
+
+        let reloaded = Registrations::load(path.clone()).repos();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].name, "backend");
+        assert_eq!(reloaded[0].root, repo.root);
+
+        registrations.retain(|_| false);
+        assert!(Registrations::load(path).repos().is_empty());
+    }
+}
@@ -0,0 +1,501 @@
+// trx web UI
+//
+// A dependency-free single page app over the trx-api JSON routes. Views are
+// selected by the URL hash: #/ (list), #/issues/ID, #/issues/ID/edit,
+// #/new and #/graph.
+
+"use strict";
+
+const app = document.getElementById("app");
+const repoSelect = document.getElementById("repo");
+
+const STATUSES = ["open", "in_progress", "blocked", "closed"];
+const TYPES = ["bug", "feature", "task", "epic", "chore"];
+const PRIORITIES = [0, 1, 2, 3, 4];
+const PAGE_SIZE = 100;
+
+let repo = localStorage.getItem("trx.repo") || "";
+
+// ---------------------------------------------------------------------------
+// Helpers
+
+function base() {
+  return repo ? `/repos/${encodeURIComponent(repo)}` : "";
+}
+
+async function api(path, options = {}) {
+  const init = { ...options, headers: { Accept: "application/json" } };
+  if (options.body !== undefined) {
+    init.headers["Content-Type"] = "application/json";
+    init.body = JSON.stringify(options.body);
+  }
+  const response = await fetch(base() + path, init);
+  const json = await response.json().catch(() => ({}));
+  if (!response.ok || json.success === false) {
+    throw new Error(json.error || `HTTP ${response.status}`);
+  }
+  return json;
+}
+
+function escapeHtml(text) {
+  return String(text ?? "")
+    .replace(/&/g, "&amp;")
+    .replace(/</g, "&lt;")
+    .replace(/>/g, "&gt;")
+    .replace(/"/g, "&quot;")
+    .replace(/'/g, "&#39;");
+}
+
+function h(strings, ...values) {
+  return strings.reduce((out, s, i) => {
+    const value = i > 0 ? values[i - 1] : "";
+    const text = value && value.__html !== undefined ? value.__html : escapeHtml(value);
+    return out + text + s;
+  });
+}
+
+function raw(html) {
+  return { __html: html };
+}
+
+function badge(text, cls) {
+  return raw(`<span class="badge ${escapeHtml(cls || text)}">${escapeHtml(text)}</span>`);
+}
+
+function options(values, selected, blank) {
+  const items = blank !== undefined ? [`<option value="">${escapeHtml(blank)}</option>`] : [];
+  for (const v of values) {
+    const sel = String(v) === String(selected ?? "") ? " selected" : "";
+    items.push(`<option value="${escapeHtml(v)}"${sel}>${escapeHtml(v)}</option>`);
+  }
+  return raw(items.join(""));
+}
+
+function showError(err) {
+  app.innerHTML = h`<p class="error">${err.message || err}</p>`;
+}
+
+function ago(timestamp) {
+  const seconds = (Date.now() - new Date(timestamp).getTime()) / 1000;
+  if (seconds < 60) return "just now";
+  if (seconds < 3600) return `${Math.floor(seconds / 60)}m ago`;
+  if (seconds < 86400) return `${Math.floor(seconds / 3600)}h ago`;
+  return `${Math.floor(seconds / 86400)}d ago`;
+}
+
+// ---------------------------------------------------------------------------
+// Markdown (a safe subset: headings, lists, quotes, code, emphasis, links)
+
+function inline(text) {
+  return escapeHtml(text)
+    .replace(/`([^`]+)`/g, "<code>$1</code>")
+    .replace(/\*\*([^*]+)\*\*/g, "<strong>$1</strong>")
+    .replace(/(^|[^*])\*([^*]+)\*/g, "$1<em>$2</em>")
+    .replace(/\[([^\]]+)\]\((https?:\/\/[^)\s]+)\)/g, '<a href="$2" rel="noopener">$1</a>');
+}
+
+function markdown(source) {
+  const lines = String(source || "").replace(/\r\n/g, "\n").split("\n");
+  const out = [];
+  let paragraph = [];
+  let list = null;
+
+  const flush = () => {
+    if (paragraph.length) out.push(`<p>${inline(paragraph.join(" "))}</p>`);
+    if (list) out.push(`<${list.tag}>${list.items.map((i) => `<li>${inline(i)}</li>`).join("")}</${list.tag}>`);
+    paragraph = [];
+    list = null;
+  };
+
+  for (let i = 0; i < lines.length; i++) {
+    const line = lines[i];
+    let m;
+    if (line.startsWith("```")) {
+      flush();
+      const code = [];
+      for (i++; i < lines.length && !lines[i].startsWith("```"); i++) code.push(lines[i]);
+      out.push(`<pre><code>${escapeHtml(code.join("\n"))}</code></pre>`);
+    } else if ((m = line.match(/^(#{1,6})\s+(.*)$/))) {
+      flush();
+      const level = m[1].length;
+      out.push(`<h${level}>${inline(m[2])}</h${level}>`);
+    } else if ((m = line.match(/^\s*([-*]|\d+\.)\s+(.*)$/))) {
+      const tag = /\d/.test(m[1]) ? "ol" : "ul";
+      if (paragraph.length || (list && list.tag !== tag)) flush();
+      list = list || { tag, items: [] };
+      list.items.push(m[2]);
+    } else if ((m = line.match(/^>\s?(.*)$/))) {
+      flush();
+      out.push(`<blockquote>${inline(m[1])}</blockquote>`);
+    } else if (line.trim() === "") {
+      flush();
+    } else {
+      if (list) flush();
+      paragraph.push(line.trim());
+    }
+  }
+  flush();
+  return out.join("\n");
+}
+
+// ---------------------------------------------------------------------------
+// Issue list
+
+async function renderList() {
+  const params = new URLSearchParams(location.hash.split("?")[1] || "");
+  app.innerHTML = h`
+    <form class="filters" id="filters">
+      <select name="status">${options(STATUSES, params.get("status"), "Any status")}</select>
+      <select name="issue_type">${options(TYPES, params.get("issue_type"), "Any type")}</select>
+      <select name="priority">${options(PRIORITIES, params.get("priority"), "Any priority")}</select>
+      <input name="q" placeholder="Search titles" value="${params.get("q") || ""}">
+      <button type="submit">Filter</button>
+    </form>
+    <table>
+      <thead><tr><th>ID</th><th>Title</th><th>Status</th><th>Type</th><th>P</th><th>Assignee</th><th>Updated</th></tr></thead>
+      <tbody id="rows"></tbody>
+    </table>
+    <p id="more"></p>`;
+
+  document.getElementById("filters").addEventListener("submit", (e) => {
+    e.preventDefault();
+    const form = new URLSearchParams(new FormData(e.target));
+    for (const [k, v] of [...form]) if (!v) form.delete(k);
+    location.hash = `#/?${form}`;
+  });
+
+  const query = new URLSearchParams({
+    sort: "priority,updated_at:desc",
+    limit: PAGE_SIZE,
+    fields: "id,title,status,priority,issue_type,assignee,updated_at",
+  });
+  for (const key of ["status", "issue_type", "priority"]) {
+    if (params.get(key)) query.set(key, params.get(key));
+  }
+  const search = (params.get("q") || "").toLowerCase();
+
+  const rows = document.getElementById("rows");
+  const more = document.getElementById("more");
+  let shown = 0;
+
+  const load = async (cursor) => {
+    if (cursor) query.set("cursor", cursor);
+    const page = await api(`/issues?${query}`);
+    const issues = page.data.filter((i) => !search || i.title.toLowerCase().includes(search));
+    shown += issues.length;
+    rows.insertAdjacentHTML(
+      "beforeend",
+      issues
+        .map(
+          (i) => h`<tr>
+            <td class="id"><a href="#/issues/${i.id}">${i.id}</a></td>
+            <td><a href="#/issues/${i.id}">${i.title}</a></td>
+            <td>${badge(i.status)}</td>
+            <td>${i.issue_type}</td>
+            <td>${badge(`P${i.priority}`, `p${i.priority}`)}</td>
+            <td>${i.assignee || ""}</td>
+            <td>${ago(i.updated_at)}</td>
+          </tr>`
+        )
+        .join("")
+    );
+    more.innerHTML = "";
+    if (page.next_cursor) {
+      const button = document.createElement("button");
+      button.textContent = "Load more";
+      button.onclick = () => load(page.next_cursor).catch(showError);
+      more.appendChild(button);
+    } else if (shown === 0) {
+      more.innerHTML = '<p class="empty">No issues match these filters.</p>';
+    }
+  };
+  await load();
+}
+
+// ---------------------------------------------------------------------------
+// Issue detail
+
+async function renderIssue(id) {
+  const [{ data: issue }, blockers] = await Promise.all([
+    api(`/issues/${encodeURIComponent(id)}`),
+    api(`/issues/${encodeURIComponent(id)}/blockers?fields=id,title,status`),
+  ]);
+  const open = issue.status !== "closed";
+  const deps = issue.dependencies || [];
+
+  app.innerHTML = h`
+    <h1>${issue.title}</h1>
+    <div class="meta">
+      <span class="id">${issue.id}</span>
+      ${badge(issue.status)} ${badge(`P${issue.priority}`, `p${issue.priority}`)}
+      <span>${issue.issue_type}</span>
+      ${issue.assignee ? raw(h`<span>@${issue.assignee}</span>`) : ""}
+      ${(issue.labels || []).length ? raw(issue.labels.map((l) => h`${badge(l, "label")}`).join(" ")) : ""}
+      <span>updated ${ago(issue.updated_at)}</span>
+    </div>
+    <div class="actions">
+      <a href="#/issues/${issue.id}/edit"><button>Edit</button></a>
+      ${open ? raw('<button id="close">Close</button>') : raw('<button id="reopen">Reopen</button>')}
+      <button id="delete" class="danger">Delete</button>
+    </div>
+    <div class="markdown">${raw(markdown(issue.description) || '<p class="empty">No description.</p>')}</div>
+    ${issue.notes ? raw(h`<h3>Notes</h3><div class="markdown">${raw(markdown(issue.notes))}</div>`) : ""}
+    ${issue.close_reason ? raw(h`<p class="meta">Closed: ${issue.close_reason}</p>`) : ""}
+
+    <h3>Dependencies</h3>
+    <table><tbody>
+      ${raw(
+        deps
+          .map(
+            (d) => h`<tr>
+              <td class="id"><a href="#/issues/${d.depends_on_id}">${d.depends_on_id}</a></td>
+              <td>${d.type}</td>
+              <td><button class="danger" data-remove="${d.depends_on_id}">Remove</button></td>
+            </tr>`
+          )
+          .join("") || '<tr><td class="empty">None</td></tr>'
+      )}
+    </tbody></table>
+    <form class="filters" id="add-dep">
+      <input name="depends_on" placeholder="Issue ID" required>
+      <select name="dep_type">${options(["blocks", "parent-child", "related"], "blocks")}</select>
+      <button type="submit">Add dependency</button>
+    </form>
+
+    <h3>Blocked by</h3>
+    ${raw(
+      blockers.data.length
+        ? `<ul>${blockers.data.map((b) => h`<li><a href="#/issues/${b.id}">${b.id}</a> ${b.title} ${badge(b.status)}</li>`).join("")}</ul>`
+        : '<p class="empty">Nothing is blocking this issue.</p>'
+    )}
+    <p id="error" class="error"></p>`;
+
+  const act = (fn) => async () => {
+    try {
+      await fn();
+      renderIssue(id);
+    } catch (err) {
+      document.getElementById("error").textContent = err.message;
+    }
+  };
+
+  const close = document.getElementById("close");
+  if (close) {
+    close.onclick = act(async () => {
+      const reason = prompt("Close reason (optional)");
+      if (reason === null) return;
+      await api(`/issues/${encodeURIComponent(id)}/close`, { method: "POST", body: { reason: reason || null } });
+    });
+  }
+  const reopen = document.getElementById("reopen");
+  if (reopen) {
+    reopen.onclick = act(() => api(`/issues/${encodeURIComponent(id)}`, { method: "PATCH", body: { status: "open" } }));
+  }
+  document.getElementById("delete").onclick = async () => {
+    if (!confirm(`Delete ${id}?`)) return;
+    try {
+      await api(`/issues/${encodeURIComponent(id)}`, { method: "DELETE" });
+      location.hash = "#/";
+    } catch (err) {
+      document.getElementById("error").textContent = err.message;
+    }
+  };
+  for (const button of app.querySelectorAll("[data-remove]")) {
+    button.onclick = act(() =>
+      api(`/issues/${encodeURIComponent(id)}/dependencies/${encodeURIComponent(button.dataset.remove)}`, {
+        method: "DELETE",
+      })
+    );
+  }
+  document.getElementById("add-dep").addEventListener("submit", (e) => {
+    e.preventDefault();
+    const form = new FormData(e.target);
+    act(() =>
+      api(`/issues/${encodeURIComponent(id)}/dependencies`, {
+        method: "POST",
+        body: { depends_on: form.get("depends_on").trim(), dep_type: form.get("dep_type") },
+      })
+    )();
+  });
+}
+
+// ---------------------------------------------------------------------------
+// Create / edit form
+
+async function renderForm(id) {
+  const issue = id
+    ? (await api(`/issues/${encodeURIComponent(id)}`)).data
+    : { title: "", description: "", issue_type: "task", priority: 2, labels: [] };
+
+  app.innerHTML = h`
+    <h1>${id ? `Edit ${id}` : "New issue"}</h1>
+    <form class="issue" id="issue-form">
+      <label>Title <input name="title" required value="${issue.title}"></label>
+      <div class="row">
+        <label>Type <select name="issue_type">${options(TYPES, issue.issue_type)}</select></label>
+        <label>Priority <select name="priority">${options(PRIORITIES, issue.priority)}</select></label>
+        ${id ? raw(h`<label>Status <select name="status">${options(STATUSES, issue.status)}</select></label>`) : raw("<span></span>")}
+      </div>
+      <div class="row">
+        <label>Assignee <input name="assignee" value="${issue.assignee || ""}"></label>
+        <label>Labels <input name="labels" placeholder="comma separated" value="${(issue.labels || []).join(", ")}"></label>
+      </div>
+      <label>Description (markdown) <textarea name="description">${issue.description || ""}</textarea></label>
+      ${id ? raw(h`<label>Notes <textarea name="notes">${issue.notes || ""}</textarea></label>`) : ""}
+      <div class="actions">
+        <button type="submit" class="primary">${id ? "Save" : "Create"}</button>
+        <a href="${id ? `#/issues/${id}` : "#/"}"><button type="button">Cancel</button></a>
+      </div>
+      <p id="error" class="error"></p>
+    </form>`;
+
+  document.getElementById("issue-form").addEventListener("submit", async (e) => {
+    e.preventDefault();
+    const form = new FormData(e.target);
+    const body = {
+      title: form.get("title").trim(),
+      description: form.get("description"),
+      issue_type: form.get("issue_type"),
+      priority: Number(form.get("priority")),
+      assignee: form.get("assignee").trim() || null,
+      labels: form
+        .get("labels")
+        .split(",")
+        .map((l) => l.trim())
+        .filter(Boolean),
+    };
+    if (id) {
+      body.status = form.get("status");
+      body.notes = form.get("notes");
+    }
+    try {
+      const result = id
+        ? await api(`/issues/${encodeURIComponent(id)}`, { method: "PATCH", body })
+        : await api("/issues", { method: "POST", body });
+      location.hash = `#/issues/${result.data.id}`;
+    } catch (err) {
+      document.getElementById("error").textContent = err.message;
+    }
+  });
+}
+
+// ---------------------------------------------------------------------------
+// Dependency graph
+//
+// Issues are laid out in columns by dependency depth: an issue sits one
+// column to the right of the deepest issue it depends on.
+
+async function renderGraph() {
+  const params = new URLSearchParams(location.hash.split("?")[1] || "");
+  const includeClosed = params.get("include_closed") === "true";
+  const [{ data: graph }, ready] = await Promise.all([
+    api(`/graph?include_closed=${includeClosed}`),
+    api("/issues/ready?fields=id"),
+  ]);
+  const readyIds = new Set(ready.data.map((i) => i.id));
+
+  const nodes = new Map(graph.nodes.map((n) => [n.id, { ...n, depth: 0 }]));
+  const edges = graph.edges.filter((e) => nodes.has(e.issue_id) && nodes.has(e.depends_on_id));
+
+  // Longest-path depth; bounded so cycles cannot loop forever
+  for (let round = 0; round < nodes.size; round++) {
+    let changed = false;
+    for (const e of edges) {
+      const from = nodes.get(e.issue_id);
+      const to = nodes.get(e.depends_on_id);
+      if (from.depth < to.depth + 1) {
+        from.depth = to.depth + 1;
+        changed = true;
+      }
+    }
+    if (!changed) break;
+  }
+
+  const W = 200, H = 36, GAP_X = 60, GAP_Y = 14, PAD = 20;
+  const columns = new Map();
+  for (const node of [...nodes.values()].sort((a, b) => a.priority - b.priority || a.id.localeCompare(b.id))) {
+    const column = columns.get(node.depth) || [];
+    node.x = PAD + node.depth * (W + GAP_X);
+    node.y = PAD + column.length * (H + GAP_Y);
+    column.push(node);
+    columns.set(node.depth, column);
+  }
+  const width = PAD * 2 + columns.size * (W + GAP_X);
+  const height = PAD * 2 + Math.max(1, ...[...columns.values()].map((c) => c.length)) * (H + GAP_Y);
+
+  const edgeSvg = edges
+    .map((e) => {
+      const from = nodes.get(e.depends_on_id);
+      const to = nodes.get(e.issue_id);
+      const x1 = from.x + W, y1 = from.y + H / 2, x2 = to.x, y2 = to.y + H / 2;
+      const mid = (x1 + x2) / 2;
+      return `<path class="edge ${escapeHtml(e.type)}" marker-end="url(#arrow)" d="M${x1},${y1} C${mid},${y1} ${mid},${y2} ${x2},${y2}"/>`;
+    })
+    .join("");
+  const nodeSvg = [...nodes.values()]
+    .map((n) => {
+      const label = n.title.length > 26 ? n.title.slice(0, 25) + "…" : n.title;
+      const cls = n.status === "closed" || readyIds.has(n.id) ? "node" : "node blocked";
+      return h`<a href="#/issues/${n.id}"><g class="${cls}">
+        <title>${n.id}: ${n.title} (${n.status}, P${n.priority})</title>
+        <rect x="${n.x}" y="${n.y}" width="${W}" height="${H}"></rect>
+        <text x="${n.x + 8}" y="${n.y + 15}">${n.id}</text>
+        <text x="${n.x + 8}" y="${n.y + 29}">${label}</text>
+      </g></a>`;
+    })
+    .join("");
+
+  app.innerHTML = h`
+    <div class="filters">
+      <label><input type="checkbox" id="closed" ${raw(includeClosed ? "checked" : "")}> Include closed issues</label>
+      <span class="meta">Arrows point from a dependency to the issue waiting on it. Red outlines are blocked.</span>
+    </div>
+    ${raw(
+      nodes.size
+        ? `<div class="graph"><svg width="${width}" height="${height}">
+            <defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="6" markerHeight="6" orient="auto-start-reverse">
+              <path d="M 0 0 L 10 5 L 0 10 z" fill="#656d76"/></marker></defs>
+            ${edgeSvg}${nodeSvg}</svg></div>`
+        : '<p class="empty">No issues to show.</p>'
+    )}`;
+  document.getElementById("closed").onchange = (e) => {
+    location.hash = e.target.checked ? "#/graph?include_closed=true" : "#/graph";
+  };
+}
+
+// ---------------------------------------------------------------------------
+// Routing
+
+async function route() {
+  const path = location.hash.replace(/^#/, "").split("?")[0] || "/";
+  let m;
+  try {
+    if (path === "/") await renderList();
+    else if (path === "/new") await renderForm();
+    else if (path === "/graph") await renderGraph();
+    else if ((m = path.match(/^\/issues\/([^/]+)\/edit$/))) await renderForm(decodeURIComponent(m[1]));
+    else if ((m = path.match(/^\/issues\/([^/]+)$/))) await renderIssue(decodeURIComponent(m[1]));
+    else app.innerHTML = '<p class="empty">Not found.</p>';
+  } catch (err) {
+    showError(err);
+  }
+}
+
+async function loadRepos() {
+  const response = await fetch("/repos").then((r) => r.json());
+  const repos = response.data || [];
+  if (!repos.some((r) => r.name === repo)) {
+    repo = (repos.find((r) => r.default) || repos[0] || {}).name || "";
+  }
+  repoSelect.innerHTML = options(repos.map((r) => r.name), repo).__html;
+  repoSelect.hidden = repos.length < 2;
+  repoSelect.onchange = () => {
+    repo = repoSelect.value;
+    localStorage.setItem("trx.repo", repo);
+    location.hash = "#/";
+    route();
+  };
+}
+
+window.addEventListener("hashchange", route);
+loadRepos().catch(() => {}).then(route);
@@ -3,8 +3,8 @@
 use anyhow::{Result, bail};
 use colored::Colorize;
 use trx_core::{
-    DependencyType, Issue, IssueGraph, IssueType, Status, StorageVersion, Store, UnifiedStore,
-    generate_id, id::generate_child_id, migrate_v1_to_v2, rollback_v2_to_v1,
+    BatchOp, DependencyType, Issue, IssueGraph, IssueType, Status, StorageVersion, Store,
+    UnifiedStore, generate_id, id::generate_child_id, migrate_v1_to_v2, rollback_v2_to_v1,
 };
 
 pub fn init(prefix: &str) -> Result<()> {
@@ -147,6 +147,25 @@ pub fn show(id: &str, json: bool) -> Result<()> {
                 println!("  {} {} {}", dep.issue_id, dep.dep_type, dep.depends_on_id);
             }
         }
+
+        if !issue.comments.is_empty() {
+            println!();
+            println!("{}", "Comments:".bold());
+            for comment in &issue.comments {
+                println!(
+                    "  {} {}",
+                    comment
+                        .created_at
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                        .dimmed(),
+                    comment.author.as_deref().unwrap_or("unknown").cyan()
+                );
+                for line in comment.text.lines() {
+                    println!("    {}", line);
+                }
+            }
+        }
     }
 
     Ok(())
@@ -217,7 +236,7 @@ pub fn ready(json: bool) -> Result<()> {
     let mut ready = graph.ready_issues(&open_issues);
 
     // Sort by priority
-    ready.sort_by(|a, b| a.priority.cmp(&b.priority));
+    ready.sort_by_key(|a| a.priority);
 
     if json {
         println!("{}", serde_json::to_string(&ready)?);
@@ -239,6 +258,61 @@ pub fn ready(json: bool) -> Result<()> {
     Ok(())
 }
 
+pub fn claims(json: bool) -> Result<()> {
+    let store = UnifiedStore::open()?;
+    let now = chrono::Utc::now();
+    let mut claimed: Vec<_> = store
+        .list_open()
+        .into_iter()
+        .filter(|i| i.claimed_by(now).is_some())
+        .collect();
+    claimed.sort_by_key(|i| i.lease_expires_at);
+
+    if json {
+        println!("{}", serde_json::to_string(&claimed)?);
+    } else if claimed.is_empty() {
+        println!("No active claims");
+    } else {
+        println!("{}", "Active claims:".bold());
+        for issue in claimed {
+            let remaining = issue.lease_expires_at.unwrap_or(now) - now;
+            println!(
+                "{} {} - {} (lease {}m{:02}s left)",
+                issue.id.cyan(),
+                issue.assignee.as_deref().unwrap_or_default().yellow(),
+                issue.title,
+                remaining.num_minutes(),
+                remaining.num_seconds() % 60
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn claim_break(id: &str, json: bool) -> Result<()> {
+    let mut store = UnifiedStore::open()?;
+    let issue = store
+        .get_mut(id)
+        .ok_or_else(|| anyhow::anyhow!("Issue not found: {}", id))?;
+    if issue.lease_expires_at.is_none() {
+        anyhow::bail!("Issue {} is not claimed", id);
+    }
+
+    let agent = issue.assignee.clone().unwrap_or_default();
+    issue.release();
+    let issue = issue.clone();
+    store.update(issue.clone())?;
+
+    if json {
+        println!("{}", serde_json::to_string(&issue)?);
+    } else {
+        println!("{} Broke {}'s claim on {}", "✓".green(), agent, id);
+    }
+
+    Ok(())
+}
+
 pub fn dep_add(id: &str, blocks: &str, json: bool) -> Result<()> {
     let mut store = UnifiedStore::open()?;
     let issue = store
@@ -290,6 +364,50 @@ pub fn dep_tree(id: &str, _json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Apply operations in the same format as the API's `POST /issues/batch`:
+/// a JSON array of operations, or an object with an `operations` array
+pub fn batch(file: Option<&str>, json: bool) -> Result<()> {
+    use std::io::Read;
+
+    let mut input = String::new();
+    match file {
+        Some(path) if path != "-" => input = std::fs::read_to_string(path)?,
+        _ => {
+            std::io::stdin().read_to_string(&mut input)?;
+        }
+    }
+    let mut value: serde_json::Value = serde_json::from_str(&input)?;
+    if let Some(operations) = value.get_mut("operations") {
+        value = operations.take();
+    }
+    let ops: Vec<BatchOp> = serde_json::from_value(value)?;
+    if ops.is_empty() {
+        bail!("Batch contains no operations");
+    }
+
+    let mut store = UnifiedStore::open()?;
+    let outcome = store.apply_batch(&ops)?;
+
+    if json {
+        println!("{}", serde_json::to_string(&outcome)?);
+    } else {
+        for result in &outcome.results {
+            match (&result.id, &result.error) {
+                (_, Some(error)) => {
+                    println!("{} [{}] {}: {}", "✗".red(), result.index, result.op, error)
+                }
+                (Some(id), None) => println!("{} {} {}", "✓".green(), result.op, id),
+                (None, None) => println!("{} {}", "✓".green(), result.op),
+            }
+        }
+    }
+    if !outcome.applied {
+        bail!("Batch rejected; no changes were applied");
+    }
+
+    Ok(())
+}
+
 pub fn sync(message: Option<String>) -> Result<()> {
     let mut store = UnifiedStore::open()?;
     let trx_dir = store.trx_dir();
@@ -305,30 +423,9 @@ pub fn sync(message: Option<String>) -> Result<()> {
 
     let msg = message.unwrap_or_else(|| "trx: sync issues".to_string());
 
-    // Git add .trx/
-    let output = std::process::Command::new("git")
-        .args(["add", &trx_dir.to_string_lossy()])
-        .output()?;
-
-    if !output.status.success() {
-        bail!(
-            "git add failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        );
-    }
-
-    // Git commit
-    let output = std::process::Command::new("git")
-        .args(["commit", "-m", &msg])
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if stderr.contains("nothing to commit") {
-            println!("Nothing to sync");
-            return Ok(());
-        }
-        bail!("git commit failed: {}", stderr);
+    if !trx_core::sync::commit(&trx_dir, &msg)? {
+        println!("Nothing to sync");
+        return Ok(());
     }
 
     println!("{} Synced .trx/", "✓".green());
@@ -692,6 +789,40 @@ pub fn schema() -> Result<()> {
                         "default": 80
                     }
                 }
+            },
+            "webhooks": {
+                "type": "array",
+                "description": "Outgoing webhooks sent by trx-api on issue events",
+                "items": {
+                    "type": "object",
+                    "required": ["name", "url"],
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "Webhook name, used in logs and 'trx webhooks test'"
+                        },
+                        "url": {
+                            "type": "string",
+                            "description": "URL to POST payloads to"
+                        },
+                        "events": {
+                            "type": "array",
+                            "items": {
+                                "type": "string",
+                                "enum": ["created", "updated", "closed", "deleted"]
+                            },
+                            "description": "Events to deliver (all events if omitted)"
+                        },
+                        "filter": {
+                            "type": "string",
+                            "description": "Only deliver events for matching issues, e.g. 'type:bug priority:<=1'"
+                        },
+                        "secret": {
+                            "type": "string",
+                            "description": "Secret for the HMAC-SHA256 X-Trx-Signature header"
+                        }
+                    }
+                }
             }
         }
     });
@@ -734,6 +865,22 @@ pub fn config_show(json: bool) -> Result<()> {
         println!("date_format = \"{}\"", config.display.date_format);
         println!("show_count = {}", config.display.show_count);
         println!("max_title_length = {}", config.display.max_title_length);
+        for hook in &config.webhooks {
+            println!();
+            println!("[[webhooks]]");
+            println!("name = \"{}\"", hook.name);
+            println!("url = \"{}\"", hook.url);
+            if !hook.events.is_empty() {
+                let events: Vec<_> = hook.events.iter().map(|e| format!("\"{}\"", e)).collect();
+                println!("events = [{}]", events.join(", "));
+            }
+            if let Some(ref filter) = hook.filter {
+                println!("filter = \"{}\"", filter);
+            }
+            if hook.secret.is_some() {
+                println!("secret = \"********\"");
+            }
+        }
     }
 
     Ok(())
@@ -876,6 +1023,61 @@ pub fn config_set(key: &str, value: &str) -> Result<()> {
     Ok(())
 }
 
+// ============================================================================
+// Webhook commands
+// ============================================================================
+
+/// Send a sample payload to a configured webhook
+///
+/// Delivery lives in trx-api, so this runs `trx-api webhook-test` from the
+/// current directory and reports the delivery record it prints.
+pub fn webhooks_test(name: &str, event: &str, json: bool) -> Result<()> {
+    use trx_core::webhook::DeliveryRecord;
+
+    let event: trx_core::WebhookEvent = event.parse()?;
+    let store = UnifiedStore::open()?;
+    let config = trx_core::Config::load(&store.trx_dir().join("config.toml"))?;
+    let hook = config
+        .webhooks
+        .iter()
+        .find(|h| h.name == name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown webhook: {}", name))?;
+
+    let api = trx_core::ServiceManager::new()?.binary_path()?;
+    let output = std::process::Command::new(api)
+        .args(["webhook-test", name, "--event", &event.to_string()])
+        .stderr(std::process::Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        bail!("trx-api webhook-test failed");
+    }
+    let record: DeliveryRecord = serde_json::from_slice(&output.stdout)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&record)?);
+    } else if record.success {
+        println!(
+            "{} Delivered {} to {} (HTTP {})",
+            "✓".green(),
+            event,
+            hook.url,
+            record.status.unwrap_or_default()
+        );
+    } else {
+        println!(
+            "{} Delivery to {} failed: {}",
+            "✗".red(),
+            hook.url,
+            record.error.as_deref().unwrap_or("unknown error")
+        );
+    }
+
+    if !record.success {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Service commands
 // ============================================================================
@@ -887,29 +1089,99 @@ pub trait ServiceCommand {
     fn is_restart(&self) -> bool;
     fn is_status(&self) -> bool;
     fn is_enable(&self) -> bool;
+    /// Whether to listen on the default Unix socket
+    fn socket(&self) -> bool;
+    /// Name to serve the current repository under
+    fn name(&self) -> Option<&str>;
+    /// Line count and follow flag for `service logs`
+    fn logs(&self) -> Option<(usize, bool)>;
+}
+
+/// Print lines appended to a log file until interrupted
+fn follow_log(path: &std::path::Path) -> Result<()> {
+    use std::io::{BufRead, BufReader, Seek, SeekFrom};
+
+    let mut offset = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let Ok(file) = std::fs::File::open(path) else {
+            continue;
+        };
+        let len = file.metadata()?.len();
+        if len < offset {
+            // Log was truncated or replaced
+            offset = 0;
+        }
+        if len == offset {
+            continue;
+        }
+
+        let mut reader = BufReader::new(file);
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            if !line.ends_with('\n') {
+                break;
+            }
+            print!("{}", line);
+            offset += line.len() as u64;
+            line.clear();
+        }
+    }
 }
 
-pub fn service<T: ServiceCommand>(cmd: T) -> Result<()> {
-    use trx_core::{ServiceManager, ServiceStatus};
+/// Print where a freshly started service is listening
+fn print_endpoint(port: Option<u16>, socket: Option<&std::path::Path>) {
+    if let Some(s) = socket {
+        println!("  Listening on: {}", s.display());
+    } else if let Some(p) = port {
+        println!("  Listening on: 127.0.0.1:{}", p);
+    }
+}
 
-    let manager = ServiceManager::new()
+pub fn service<T: ServiceCommand>(cmd: T, mcp: bool) -> Result<()> {
+    use trx_core::{Service, ServiceManager, ServiceStatus};
+
+    let service = if mcp { Service::Mcp } else { Service::Api };
+    let name = service.binary();
+    let manager = ServiceManager::for_service(service)
         .map_err(|e| anyhow::anyhow!("Failed to initialize service manager: {}", e))?;
+    let socket = cmd.socket().then(|| manager.default_socket());
+    // trx-mcp serves the repository it is started in
+    let workdir = || -> Result<Option<std::path::PathBuf>> {
+        Ok(match service {
+            Service::Api => None,
+            Service::Mcp => Some(trx_core::UnifiedStore::find_root()?),
+        })
+    };
 
     if cmd.is_start() {
-        println!("Starting trx-api service...");
+        // A running trx-api serves many repositories; add this one to it
+        if service == Service::Api && manager.is_running() {
+            let root = trx_core::UnifiedStore::find_root()?;
+            let repo = manager
+                .register_repo(&root, cmd.name())
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            println!(
+                "{} Registered {} with running trx-api",
+                "✓".green(),
+                repo.cyan()
+            );
+            return Ok(());
+        }
+
+        println!("Starting {} service...", name);
         manager
-            .start(false, None)
+            .start(false, workdir()?.as_ref(), socket.as_deref(), cmd.name())
             .map_err(|e| anyhow::anyhow!("{}", e))?;
 
         // Wait and check status
         std::thread::sleep(std::time::Duration::from_secs(1));
 
         match manager.status() {
-            ServiceStatus::Running { pid, port } => {
+            ServiceStatus::Running { pid, port, socket } => {
                 println!("{} Service started (PID: {})", "✓".green(), pid);
-                if let Some(p) = port {
-                    println!("  Listening on: 127.0.0.1:{}", p);
-                }
+                print_endpoint(port, socket.as_deref());
             }
             _ => {
                 println!("{} Service failed to start", "✗".red());
@@ -917,13 +1189,13 @@ pub fn service<T: ServiceCommand>(cmd: T) -> Result<()> {
             }
         }
     } else if cmd.is_run() {
-        println!("Running trx-api in foreground...");
+        println!("Running {} in foreground...", name);
         println!("Press Ctrl+C to stop");
         manager
-            .start(true, None)
+            .start(true, workdir()?.as_ref(), socket.as_deref(), cmd.name())
             .map_err(|e| anyhow::anyhow!("{}", e))?;
     } else if cmd.is_stop() {
-        println!("Stopping trx-api service...");
+        println!("Stopping {} service...", name);
         match manager.stop() {
             Ok(()) => println!("{} Service stopped", "✓".green()),
             Err(e) => {
@@ -932,33 +1204,44 @@ pub fn service<T: ServiceCommand>(cmd: T) -> Result<()> {
             }
         }
     } else if cmd.is_restart() {
-        println!("Restarting trx-api service...");
+        println!("Restarting {} service...", name);
         manager
-            .restart(None)
+            .restart(workdir()?.as_ref(), socket.as_deref())
             .map_err(|e| anyhow::anyhow!("{}", e))?;
 
         std::thread::sleep(std::time::Duration::from_secs(1));
 
         match manager.status() {
-            ServiceStatus::Running { pid, port } => {
+            ServiceStatus::Running { pid, port, socket } => {
                 println!("{} Service restarted (PID: {})", "✓".green(), pid);
-                if let Some(p) = port {
-                    println!("  Listening on: 127.0.0.1:{}", p);
-                }
+                print_endpoint(port, socket.as_deref());
             }
             _ => {
                 println!("{} Service failed to restart", "✗".red());
                 std::process::exit(1);
             }
         }
+    } else if let Some((lines, follow)) = cmd.logs() {
+        for line in manager
+            .log_tail(lines)
+            .map_err(|e| anyhow::anyhow!("Failed to read log: {}", e))?
+        {
+            println!("{}", line);
+        }
+        if follow {
+            follow_log(&manager.log_file())?;
+        }
     } else if cmd.is_status() {
         match manager.status() {
-            ServiceStatus::Running { pid, port } => {
+            ServiceStatus::Running { pid, port, socket } => {
                 println!("Service is {}", "running".green());
                 println!("  PID: {}", pid);
                 if let Some(p) = port {
                     println!("  Port: {}", p);
                 }
+                if let Some(s) = socket {
+                    println!("  Socket: {}", s.display());
+                }
             }
             ServiceStatus::Stopped => {
                 println!("Service is {}", "stopped".yellow());
@@ -107,12 +107,24 @@ enum Commands {
     /// Show ready (unblocked) issues
     Ready,
 
+    /// List or break agent claims
+    Claims {
+        #[command(subcommand)]
+        command: Option<ClaimCommands>,
+    },
+
     /// Manage dependencies
     Dep {
         #[command(subcommand)]
         command: DepCommands,
     },
 
+    /// Apply a JSON list of operations atomically: all or none
+    Batch {
+        /// File with the operations; reads stdin if omitted or "-"
+        file: Option<String>,
+    },
+
     /// Git add and commit .trx/
     Sync {
         /// Commit message
@@ -163,9 +175,32 @@ enum Commands {
 
     /// Manage trx-api service
     Service {
+        /// Manage trx-mcp's HTTP server instead of trx-api
+        #[arg(long, global = true)]
+        mcp: bool,
+
         #[command(subcommand)]
         command: ServiceCommands,
     },
+
+    /// Manage outgoing webhooks
+    Webhooks {
+        #[command(subcommand)]
+        command: WebhookCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum WebhookCommands {
+    /// Send a sample payload to a configured webhook
+    Test {
+        /// Webhook name from config
+        name: String,
+
+        /// Event to simulate (created, updated, closed, deleted)
+        #[arg(short, long, default_value = "created")]
+        event: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -192,25 +227,68 @@ enum ConfigCommands {
 
 #[derive(Subcommand)]
 enum ServiceCommands {
-    /// Start the API service in background
-    Start,
+    /// Start the API service in background, or add this repository to the
+    /// running one (registrations survive restarts)
+    Start {
+        /// Listen on a Unix socket instead of a TCP port
+        #[arg(long)]
+        socket: bool,
+
+        /// Name to serve this repository under (defaults to its directory name)
+        #[arg(long)]
+        name: Option<String>,
+    },
 
     /// Run the API service in foreground (for debugging)
-    Run,
+    Run {
+        /// Listen on a Unix socket instead of a TCP port
+        #[arg(long)]
+        socket: bool,
+
+        /// Name to serve this repository under (defaults to its directory name)
+        #[arg(long)]
+        name: Option<String>,
+    },
 
     /// Stop the API service
     Stop,
 
     /// Restart the API service
-    Restart,
+    Restart {
+        /// Listen on a Unix socket instead of a TCP port
+        #[arg(long)]
+        socket: bool,
+    },
 
     /// Show service status
     Status,
 
+    /// Show the service log
+    Logs {
+        /// Number of lines to show
+        #[arg(short = 'n', long, default_value = "50")]
+        lines: usize,
+
+        /// Keep printing new lines as they are written
+        #[arg(short, long)]
+        follow: bool,
+    },
+
     /// Show instructions for enabling auto-start
     Enable,
 }
 
+#[derive(Subcommand)]
+enum ClaimCommands {
+    /// List active claims
+    List,
+    /// Release a claim held by an agent
+    Break {
+        /// Issue ID
+        id: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum DepCommands {
     /// Add a dependency
@@ -267,11 +345,16 @@ fn main() -> Result<()> {
         } => commands::update(&id, status, priority, title, description, cli.json),
         Commands::Close { id, reason } => commands::close(&id, reason, cli.json),
         Commands::Ready => commands::ready(cli.json),
+        Commands::Claims { command } => match command {
+            Some(ClaimCommands::List) | None => commands::claims(cli.json),
+            Some(ClaimCommands::Break { id }) => commands::claim_break(&id, cli.json),
+        },
         Commands::Dep { command } => match command {
             DepCommands::Add { id, blocks } => commands::dep_add(&id, &blocks, cli.json),
             DepCommands::Rm { id, blocks } => commands::dep_rm(&id, &blocks, cli.json),
             DepCommands::Tree { id } => commands::dep_tree(&id, cli.json),
         },
+        Commands::Batch { file } => commands::batch(file.as_deref(), cli.json),
         Commands::Sync { message } => commands::sync(message),
         Commands::Migrate {
             dry_run,
@@ -289,22 +372,27 @@ fn main() -> Result<()> {
             Some(ConfigCommands::Set { key, value }) => commands::config_set(&key, &value),
             None => commands::config_show(cli.json),
         },
-        Commands::Service { command } => commands::service(command),
+        Commands::Service { mcp, command } => commands::service(command, mcp),
+        Commands::Webhooks { command } => match command {
+            WebhookCommands::Test { name, event } => {
+                commands::webhooks_test(&name, &event, cli.json)
+            }
+        },
     }
 }
 
 impl commands::ServiceCommand for ServiceCommands {
     fn is_start(&self) -> bool {
-        matches!(self, ServiceCommands::Start)
+        matches!(self, ServiceCommands::Start { .. })
     }
     fn is_run(&self) -> bool {
-        matches!(self, ServiceCommands::Run)
+        matches!(self, ServiceCommands::Run { .. })
     }
     fn is_stop(&self) -> bool {
         matches!(self, ServiceCommands::Stop)
     }
     fn is_restart(&self) -> bool {
-        matches!(self, ServiceCommands::Restart)
+        matches!(self, ServiceCommands::Restart { .. })
     }
     fn is_status(&self) -> bool {
         matches!(self, ServiceCommands::Status)
@@ -312,4 +400,26 @@ impl commands::ServiceCommand for ServiceCommands {
     fn is_enable(&self) -> bool {
         matches!(self, ServiceCommands::Enable)
     }
+    fn socket(&self) -> bool {
+        matches!(
+            self,
+            ServiceCommands::Start { socket: true, .. }
+                | ServiceCommands::Run { socket: true, .. }
+                | ServiceCommands::Restart { socket: true }
+        )
+    }
+    fn name(&self) -> Option<&str> {
+        match self {
+            ServiceCommands::Start { name, .. } | ServiceCommands::Run { name, .. } => {
+                name.as_deref()
+            }
+            _ => None,
+        }
+    }
+    fn logs(&self) -> Option<(usize, bool)> {
+        match self {
+            ServiceCommands::Logs { lines, follow } => Some((*lines, *follow)),
+            _ => None,
+        }
+    }
 }
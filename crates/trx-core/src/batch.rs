@@ -0,0 +1,432 @@
+//! Batch operations for trx
+//!
+//! A batch is validated in full against a staged copy of the issues before
+//! anything is written, so either every operation is applied or none are.
+//! Operations may refer to issues created earlier in the same batch as
+//! `$N`, where N is the index of the create operation.
+
+use crate::issue::check_priority;
+use crate::{DependencyType, Error, Issue, IssueType, Result, Status, generate_id};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A single operation in a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOp {
+    Create {
+        title: String,
+        description: Option<String>,
+        priority: Option<u8>,
+        issue_type: Option<String>,
+        labels: Option<Vec<String>>,
+        parent_id: Option<String>,
+        assignee: Option<String>,
+    },
+    Update {
+        id: String,
+        title: Option<String>,
+        description: Option<String>,
+        status: Option<String>,
+        priority: Option<u8>,
+        issue_type: Option<String>,
+        labels: Option<Vec<String>>,
+        assignee: Option<String>,
+        notes: Option<String>,
+    },
+    Close {
+        id: String,
+        reason: Option<String>,
+    },
+    Delete {
+        id: String,
+        by: Option<String>,
+        reason: Option<String>,
+    },
+    AddDep {
+        id: String,
+        depends_on: String,
+        dep_type: Option<String>,
+    },
+    RemoveDep {
+        id: String,
+        depends_on: String,
+    },
+}
+
+impl BatchOp {
+    /// Operation name as used in the `op` tag
+    pub fn name(&self) -> &'static str {
+        match self {
+            BatchOp::Create { .. } => "create",
+            BatchOp::Update { .. } => "update",
+            BatchOp::Close { .. } => "close",
+            BatchOp::Delete { .. } => "delete",
+            BatchOp::AddDep { .. } => "add_dep",
+            BatchOp::RemoveDep { .. } => "remove_dep",
+        }
+    }
+}
+
+/// Result of a single batch operation
+#[derive(Debug, Clone, Serialize)]
+pub struct OpResult {
+    pub index: usize,
+    pub op: &'static str,
+    pub success: bool,
+    /// ID of the issue the operation touched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of a whole batch
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchOutcome {
+    /// Whether the batch was written; false if any operation failed
+    pub applied: bool,
+    pub results: Vec<OpResult>,
+}
+
+impl BatchOutcome {
+    /// IDs of the issues touched by successful operations, without repeats
+    pub fn touched_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = Vec::new();
+        for id in self.results.iter().filter_map(|r| r.id.as_deref()) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+}
+
+/// A validated batch, ready for a store to persist
+pub(crate) struct StagedBatch {
+    pub outcome: BatchOutcome,
+    /// Final state of every issue the batch changed
+    pub changed: Vec<Issue>,
+}
+
+/// Validate and apply a batch against a copy of `issues`
+///
+/// Nothing is returned in `changed` unless every operation succeeded.
+pub(crate) fn stage(issues: &HashMap<String, Issue>, prefix: &str, ops: &[BatchOp]) -> StagedBatch {
+    let mut staged = issues.clone();
+    let mut created: Vec<Option<String>> = Vec::with_capacity(ops.len());
+    let mut changed: Vec<String> = Vec::new();
+    let mut results = Vec::with_capacity(ops.len());
+
+    for (index, op) in ops.iter().enumerate() {
+        let result = apply_op(&mut staged, prefix, op, &created);
+        created.push(match (op, &result) {
+            (BatchOp::Create { .. }, Ok(id)) => Some(id.clone()),
+            _ => None,
+        });
+
+        results.push(match result {
+            Ok(id) => {
+                if !changed.contains(&id) {
+                    changed.push(id.clone());
+                }
+                OpResult {
+                    index,
+                    op: op.name(),
+                    success: true,
+                    id: Some(id),
+                    error: None,
+                }
+            }
+            Err(e) => OpResult {
+                index,
+                op: op.name(),
+                success: false,
+                id: None,
+                error: Some(e.to_string()),
+            },
+        });
+    }
+
+    let applied = results.iter().all(|r| r.success);
+    let changed = if applied {
+        changed.iter().filter_map(|id| staged.remove(id)).collect()
+    } else {
+        Vec::new()
+    };
+
+    StagedBatch {
+        outcome: BatchOutcome { applied, results },
+        changed,
+    }
+}
+
+/// Resolve a `$N` reference to an issue created earlier in the batch
+fn resolve(id: &str, created: &[Option<String>]) -> Result<String> {
+    let Some(index) = id.strip_prefix('$') else {
+        return Ok(id.to_string());
+    };
+    index
+        .parse::<usize>()
+        .ok()
+        .and_then(|i| created.get(i).cloned().flatten())
+        .ok_or_else(|| {
+            Error::InvalidOperation(format!(
+                "{} does not refer to an earlier create operation",
+                id
+            ))
+        })
+}
+
+fn next_child_num(issues: &HashMap<String, Issue>, parent_id: &str) -> u32 {
+    let prefix = format!("{}.", parent_id);
+    issues
+        .keys()
+        .filter_map(|id| id.strip_prefix(&prefix))
+        .filter(|suffix| !suffix.contains('.'))
+        .filter_map(|suffix| suffix.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
+/// Apply one operation to the staged issues, returning the touched ID
+fn apply_op(
+    issues: &mut HashMap<String, Issue>,
+    prefix: &str,
+    op: &BatchOp,
+    created: &[Option<String>],
+) -> Result<String> {
+    match op {
+        BatchOp::Create {
+            title,
+            description,
+            priority,
+            issue_type,
+            labels,
+            parent_id,
+            assignee,
+        } => {
+            if title.trim().is_empty() {
+                return Err(Error::InvalidOperation("title must not be empty".into()));
+            }
+            let parent_id = parent_id
+                .as_deref()
+                .map(|p| resolve(p, created))
+                .transpose()?;
+
+            let id = match &parent_id {
+                Some(parent) => {
+                    if !issues.contains_key(parent) {
+                        return Err(Error::NotFound(parent.clone()));
+                    }
+                    format!("{}.{}", parent, next_child_num(issues, parent))
+                }
+                None => loop {
+                    let id = generate_id(prefix);
+                    if !issues.contains_key(&id) {
+                        break id;
+                    }
+                },
+            };
+
+            let mut issue = Issue::new(id.clone(), title.clone());
+            issue.description = description.clone();
+            if let Some(priority) = priority {
+                issue.priority = check_priority(*priority)?;
+            }
+            if let Some(t) = issue_type {
+                issue.issue_type = t.parse::<IssueType>()?;
+            }
+            if let Some(labels) = labels {
+                issue.labels = labels.clone();
+            }
+            issue.assignee = assignee.clone();
+            if let Some(parent) = parent_id {
+                issue.add_dependency(parent, DependencyType::ParentChild);
+            }
+
+            issues.insert(id.clone(), issue);
+            Ok(id)
+        }
+        BatchOp::Update {
+            id,
+            title,
+            description,
+            status,
+            priority,
+            issue_type,
+            labels,
+            assignee,
+            notes,
+        } => {
+            let id = resolve(id, created)?;
+            let status = status.as_deref().map(str::parse::<Status>).transpose()?;
+            let issue_type = issue_type
+                .as_deref()
+                .map(str::parse::<IssueType>)
+                .transpose()?;
+            let priority = priority.map(check_priority).transpose()?;
+
+            let issue = issues
+                .get_mut(&id)
+                .ok_or_else(|| Error::NotFound(id.clone()))?;
+            if let Some(title) = title {
+                issue.title = title.clone();
+            }
+            if let Some(desc) = description {
+                issue.description = Some(desc.clone());
+            }
+            if let Some(status) = status {
+                issue.set_status(status);
+            }
+            if let Some(priority) = priority {
+                issue.priority = priority;
+            }
+            if let Some(t) = issue_type {
+                issue.issue_type = t;
+            }
+            if let Some(labels) = labels {
+                issue.labels = labels.clone();
+            }
+            if let Some(assignee) = assignee {
+                issue.assignee = Some(assignee.clone());
+            }
+            if let Some(notes) = notes {
+                issue.notes = Some(notes.clone());
+            }
+            issue.updated_at = chrono::Utc::now();
+            Ok(id)
+        }
+        BatchOp::Close { id, reason } => {
+            let id = resolve(id, created)?;
+            let issue = issues
+                .get_mut(&id)
+                .ok_or_else(|| Error::NotFound(id.clone()))?;
+            issue.close(reason.clone());
+            Ok(id)
+        }
+        BatchOp::Delete { id, by, reason } => {
+            let id = resolve(id, created)?;
+            let issue = issues
+                .get_mut(&id)
+                .ok_or_else(|| Error::NotFound(id.clone()))?;
+            issue.delete(by.clone(), reason.clone());
+            Ok(id)
+        }
+        BatchOp::AddDep {
+            id,
+            depends_on,
+            dep_type,
+        } => {
+            let id = resolve(id, created)?;
+            let depends_on = resolve(depends_on, created)?;
+            let dep_type = dep_type
+                .as_deref()
+                .map(str::parse::<DependencyType>)
+                .transpose()?
+                .unwrap_or_default();
+            if id == depends_on {
+                return Err(Error::InvalidOperation(format!(
+                    "{} cannot depend on itself",
+                    id
+                )));
+            }
+            if !issues.contains_key(&depends_on) {
+                return Err(Error::NotFound(depends_on));
+            }
+            let issue = issues
+                .get_mut(&id)
+                .ok_or_else(|| Error::NotFound(id.clone()))?;
+            issue.add_dependency(depends_on, dep_type);
+            Ok(id)
+        }
+        BatchOp::RemoveDep { id, depends_on } => {
+            let id = resolve(id, created)?;
+            let depends_on = resolve(depends_on, created)?;
+            let issue = issues
+                .get_mut(&id)
+                .ok_or_else(|| Error::NotFound(id.clone()))?;
+            issue.remove_dependency(&depends_on);
+            Ok(id)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn existing() -> HashMap<String, Issue> {
+        let issue = Issue::new("trx-a".into(), "Existing".into());
+        HashMap::from([(issue.id.clone(), issue)])
+    }
+
+    #[test]
+    fn test_stage_with_references() {
+        let ops: Vec<BatchOp> = serde_json::from_str(
+            r#"[
+                {"op": "create", "title": "New", "priority": 1},
+                {"op": "create", "title": "Child", "parent_id": "$0"},
+                {"op": "add_dep", "id": "trx-a", "depends_on": "$0"},
+                {"op": "update", "id": "trx-a", "status": "in_progress"}
+            ]"#,
+        )
+        .unwrap();
+
+        let staged = stage(&existing(), "trx", &ops);
+        assert!(staged.outcome.applied);
+        assert_eq!(staged.changed.len(), 3);
+
+        let new_id = staged.outcome.results[0].id.clone().unwrap();
+        assert_eq!(
+            staged.outcome.results[1].id.as_deref(),
+            Some(format!("{}.1", new_id).as_str())
+        );
+        let a = staged.changed.iter().find(|i| i.id == "trx-a").unwrap();
+        assert_eq!(a.status, Status::InProgress);
+        assert_eq!(a.blocking_ids(), vec![new_id.as_str()]);
+    }
+
+    #[test]
+    fn test_stage_status_updates_close_and_reopen() {
+        let ops: Vec<BatchOp> =
+            serde_json::from_str(r#"[{"op": "update", "id": "trx-a", "status": "closed"}]"#)
+                .unwrap();
+        let staged = stage(&existing(), "trx", &ops);
+        let closed = staged.changed[0].clone();
+        assert_eq!(closed.status, Status::Closed);
+        assert!(closed.closed_at.is_some());
+
+        let mut issues = existing();
+        let mut a = closed;
+        a.close_reason = Some("done".into());
+        issues.insert(a.id.clone(), a);
+        let ops: Vec<BatchOp> =
+            serde_json::from_str(r#"[{"op": "update", "id": "trx-a", "status": "open"}]"#).unwrap();
+        let reopened = &stage(&issues, "trx", &ops).changed[0];
+        assert_eq!(reopened.status, Status::Open);
+        assert_eq!(reopened.closed_at, None);
+        assert_eq!(reopened.close_reason, None);
+    }
+
+    #[test]
+    fn test_stage_rejects_whole_batch() {
+        let ops: Vec<BatchOp> = serde_json::from_str(
+            r#"[
+                {"op": "close", "id": "trx-a"},
+                {"op": "update", "id": "trx-a", "status": "bogus"},
+                {"op": "delete", "id": "trx-missing"},
+                {"op": "add_dep", "id": "trx-a", "depends_on": "$0"}
+            ]"#,
+        )
+        .unwrap();
+
+        let staged = stage(&existing(), "trx", &ops);
+        assert!(!staged.outcome.applied);
+        assert!(staged.changed.is_empty());
+
+        let ok: Vec<_> = staged.outcome.results.iter().map(|r| r.success).collect();
+        assert_eq!(ok, [true, false, false, false]);
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! Stored in .trx/config.toml
 
+use crate::webhook::WebhookConfig;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 /// Storage version for migration support
@@ -62,6 +64,14 @@ pub struct Config {
     /// Display settings
     #[serde(default)]
     pub display: DisplayConfig,
+
+    /// Outgoing webhooks fired by trx-api on issue events
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+
+    /// trx-tui settings
+    #[serde(default, skip_serializing_if = "TuiConfig::is_empty")]
+    pub tui: TuiConfig,
 }
 
 impl Default for Config {
@@ -77,6 +87,8 @@ impl Default for Config {
             editor: None,
             git: GitConfig::default(),
             display: DisplayConfig::default(),
+            webhooks: Vec::new(),
+            tui: TuiConfig::default(),
         }
     }
 }
@@ -120,6 +132,60 @@ impl Default for DisplayConfig {
     }
 }
 
+/// trx-tui configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct TuiConfig {
+    /// Key bindings, from a key such as "x" or "ctrl-k" to a command such
+    /// as "close" or "label add backend"; checked by trx-tui at startup
+    pub keys: BTreeMap<String, String>,
+
+    /// Colours
+    #[serde(skip_serializing_if = "ThemeConfig::is_empty")]
+    pub theme: ThemeConfig,
+}
+
+impl TuiConfig {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.theme.is_empty()
+    }
+}
+
+/// trx-tui colours: a built-in theme plus named colours for single
+/// elements, e.g. `open = "green"` under `[tui.theme.status]`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Built-in theme to start from: "dark" (default), "light" or "mono"
+    pub base: Option<String>,
+
+    /// Colours by status name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub status: BTreeMap<String, String>,
+
+    /// Colours by priority, "0" to "4"
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub priority: BTreeMap<String, String>,
+
+    /// Colours by issue type
+    #[serde(rename = "type", skip_serializing_if = "BTreeMap::is_empty")]
+    pub issue_type: BTreeMap<String, String>,
+
+    /// Colours of interface elements such as "border" or "selection"
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub ui: BTreeMap<String, String>,
+}
+
+impl ThemeConfig {
+    pub fn is_empty(&self) -> bool {
+        self.base.is_none()
+            && self.status.is_empty()
+            && self.priority.is_empty()
+            && self.issue_type.is_empty()
+            && self.ui.is_empty()
+    }
+}
+
 impl Config {
     /// Load config from a TOML file
     pub fn load(path: &Path) -> crate::Result<Self> {
@@ -143,7 +209,7 @@ impl Config {
 
     /// Generate a default config file with comments
     pub fn default_with_comments() -> String {
-        r#"# trx configuration
+        r##"# trx configuration
 # See schema at: https://raw.githubusercontent.com/byteowlz/schemas/main/trx/trx.config.schema.json
 
 # Storage format version (v1 = JSONL, v2 = CRDT)
@@ -191,7 +257,38 @@ show_count = true
 
 # Maximum title length before truncation
 max_title_length = 80
-"#
+
+# Outgoing webhooks, sent by trx-api when issues change
+# [[webhooks]]
+# name = "ci"
+# url = "https://example.com/trx-hook"
+# events = ["created", "updated", "closed", "deleted"]  # all events if omitted
+# filter = "type:bug priority:<=1"                       # optional issue filter
+# secret = "change-me"                                   # signs X-Trx-Signature
+
+# trx-tui key bindings: key = command, as typed in the ':' palette
+# Bound keys replace their default; "none" unbinds a key
+# [tui.keys]
+# "x" = "close"
+# "ctrl-l" = "label add backend"
+# "L" = "view triage"
+
+# trx-tui colours: start from a built-in theme and override single elements
+# Colours are names ("red", "lightblue", "darkgray"), "#rrggbb" or 0-255
+# The mono theme is used whenever NO_COLOR is set
+# [tui.theme]
+# base = "dark"                 # dark, light or mono
+# [tui.theme.status]
+# in_progress = "lightblue"
+# [tui.theme.priority]
+# "0" = "#ff5555"
+# [tui.theme.type]
+# bug = "red"
+# [tui.theme.ui]
+# border = "darkgray"           # also: overlay_border, overlay, accent, highlight,
+#                               # muted, selection, status_bar, danger, success,
+#                               # label, repo, link, code
+"##
         .to_string()
     }
 }
@@ -2,12 +2,13 @@
 //!
 //! Each issue is stored as a separate .automerge file for conflict-free merging.
 
+use crate::batch::{self, BatchOp, BatchOutcome};
 use crate::{Config, Error, Issue, Result, StorageVersion};
 use automerge::{AutoCommit, ObjType, ReadDoc, transaction::Transactable};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 const TRX_DIR: &str = ".trx";
 const CRDT_DIR: &str = "crdt";
@@ -23,9 +24,16 @@ pub struct CrdtStore {
 impl CrdtStore {
     /// Find and open the CRDT store for the current directory
     pub fn open() -> Result<Self> {
-        let root = Self::find_root()?;
+        Self::open_at(&Self::find_root()?)
+    }
+
+    /// Open the CRDT store in a specific repository root
+    pub fn open_at(root: &Path) -> Result<Self> {
+        if !root.join(TRX_DIR).exists() {
+            return Err(Error::NotInitialized);
+        }
         let mut store = Self {
-            root,
+            root: root.to_path_buf(),
             issues: HashMap::new(),
         };
         store.load()?;
@@ -34,7 +42,12 @@ impl CrdtStore {
 
     /// Initialize a new CRDT store (v2)
     pub fn init(prefix: &str) -> Result<Self> {
-        let root = std::env::current_dir()?;
+        Self::init_at(&std::env::current_dir()?, prefix)
+    }
+
+    /// Initialize a new CRDT store (v2) in a specific repository root
+    pub fn init_at(root: &Path, prefix: &str) -> Result<Self> {
+        let root = root.to_path_buf();
         let trx_dir = root.join(TRX_DIR);
         let crdt_dir = trx_dir.join(CRDT_DIR);
 
@@ -45,9 +58,11 @@ impl CrdtStore {
         fs::create_dir_all(&crdt_dir)?;
 
         // Create config with v2 storage
-        let mut config = Config::default();
-        config.storage_version = StorageVersion::V2;
-        config.prefix = prefix.to_string();
+        let config = Config {
+            storage_version: StorageVersion::V2,
+            prefix: prefix.to_string(),
+            ..Config::default()
+        };
         config.save(&trx_dir.join(CONFIG_FILE))?;
 
         // Create empty ISSUES.md
@@ -188,6 +203,9 @@ impl CrdtStore {
         if let Some(assignee) = Self::get_str(doc, "assignee") {
             issue.assignee = Some(assignee);
         }
+        if let Some(expires) = Self::get_datetime(doc, "lease_expires_at") {
+            issue.lease_expires_at = Some(expires);
+        }
         if let Some(reason) = Self::get_str(doc, "close_reason") {
             issue.close_reason = Some(reason);
         }
@@ -199,10 +217,10 @@ impl CrdtStore {
         if let Ok(Some((_, labels_id))) = doc.get(automerge::ROOT, "labels") {
             let len = doc.length(&labels_id);
             for i in 0..len {
-                if let Ok(Some((v, _))) = doc.get(&labels_id, i) {
-                    if let Some(s) = v.to_str() {
-                        issue.labels.push(s.to_string());
-                    }
+                if let Ok(Some((v, _))) = doc.get(&labels_id, i)
+                    && let Some(s) = v.to_str()
+                {
+                    issue.labels.push(s.to_string());
                 }
             }
         }
@@ -228,12 +246,7 @@ impl CrdtStore {
 
                     if let (Some(issue_id), Some(depends_on_id)) = (issue_id, depends_on_id) {
                         let dep_type = dep_type_str
-                            .and_then(|t| match t.as_str() {
-                                "blocks" => Some(crate::DependencyType::Blocks),
-                                "parent_child" => Some(crate::DependencyType::ParentChild),
-                                "related" => Some(crate::DependencyType::Related),
-                                _ => None,
-                            })
+                            .and_then(|t| t.parse().ok())
                             .unwrap_or_default();
 
                         let created_at = created_at_str
@@ -253,6 +266,34 @@ impl CrdtStore {
             }
         }
 
+        // Load comments
+        if let Ok(Some((_, comments_id))) = doc.get(automerge::ROOT, "comments") {
+            let len = doc.length(&comments_id);
+            for i in 0..len {
+                if let Ok(Some((_, comment_obj))) = doc.get(&comments_id, i) {
+                    let get_field = |key: &str| -> Option<String> {
+                        doc.get(&comment_obj, key)
+                            .ok()
+                            .flatten()
+                            .and_then(|(v, _)| v.to_str().map(|s| s.to_string()))
+                    };
+
+                    if let Some(text) = get_field("text") {
+                        let created_at = get_field("created_at")
+                            .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                            .map(|dt| dt.with_timezone(&chrono::Utc))
+                            .unwrap_or_else(chrono::Utc::now);
+
+                        issue.comments.push(crate::Comment {
+                            author: get_field("author"),
+                            text,
+                            created_at,
+                        });
+                    }
+                }
+            }
+        }
+
         Ok(issue)
     }
 
@@ -305,6 +346,14 @@ impl CrdtStore {
             doc.put(automerge::ROOT, "assignee", assignee.as_str())
                 .map_err(|e| Error::Other(format!("Failed to set assignee: {}", e)))?;
         }
+        if let Some(ref expires) = issue.lease_expires_at {
+            doc.put(
+                automerge::ROOT,
+                "lease_expires_at",
+                expires.to_rfc3339().as_str(),
+            )
+            .map_err(|e| Error::Other(format!("Failed to set lease_expires_at: {}", e)))?;
+        }
         if let Some(ref reason) = issue.close_reason {
             doc.put(automerge::ROOT, "close_reason", reason.as_str())
                 .map_err(|e| Error::Other(format!("Failed to set close_reason: {}", e)))?;
@@ -352,6 +401,34 @@ impl CrdtStore {
             }
         }
 
+        // Comments
+        if !issue.comments.is_empty() {
+            let comments_id = doc
+                .put_object(automerge::ROOT, "comments", ObjType::List)
+                .map_err(|e| Error::Other(format!("Failed to create comments: {}", e)))?;
+
+            for (i, comment) in issue.comments.iter().enumerate() {
+                let comment_obj = doc
+                    .insert_object(&comments_id, i, ObjType::Map)
+                    .map_err(|e| Error::Other(format!("Failed to create comment: {}", e)))?;
+
+                doc.put(&comment_obj, "text", comment.text.as_str())
+                    .map_err(|e| Error::Other(format!("Failed to set comment text: {}", e)))?;
+                doc.put(
+                    &comment_obj,
+                    "created_at",
+                    comment.created_at.to_rfc3339().as_str(),
+                )
+                .map_err(|e| Error::Other(format!("Failed to set comment created_at: {}", e)))?;
+                if let Some(ref author) = comment.author {
+                    doc.put(&comment_obj, "author", author.as_str())
+                        .map_err(|e| {
+                            Error::Other(format!("Failed to set comment author: {}", e))
+                        })?;
+                }
+            }
+        }
+
         Ok(doc)
     }
 
@@ -369,69 +446,8 @@ impl CrdtStore {
 
     /// Regenerate ISSUES.md from current state
     pub fn regenerate_issues_md(&self) -> Result<()> {
-        let mut content = String::from("# Issues\n\n");
-
-        // Collect and sort issues
-        let mut open: Vec<_> = self
-            .issues
-            .values()
-            .filter(|i| i.status.is_open())
-            .collect();
-        let mut closed: Vec<_> = self
-            .issues
-            .values()
-            .filter(|i| i.status.is_closed() && i.status != crate::Status::Tombstone)
-            .collect();
-
-        // Sort by priority, then by created_at
-        open.sort_by(|a, b| {
-            a.priority
-                .cmp(&b.priority)
-                .then_with(|| b.created_at.cmp(&a.created_at))
-        });
-        closed.sort_by(|a, b| b.closed_at.cmp(&a.closed_at));
-
-        // Open issues
-        if !open.is_empty() {
-            content.push_str("## Open\n\n");
-            for issue in &open {
-                content.push_str(&format!(
-                    "### [{}] {} (P{}, {})\n",
-                    issue.id, issue.title, issue.priority, issue.issue_type
-                ));
-                if let Some(ref desc) = issue.description {
-                    // Truncate long descriptions
-                    let desc_preview: String = desc.lines().take(5).collect::<Vec<_>>().join("\n");
-                    content.push_str(&desc_preview);
-                    if desc.lines().count() > 5 {
-                        content.push_str("\n...\n");
-                    }
-                    content.push('\n');
-                }
-                content.push('\n');
-            }
-        }
-
-        // Closed issues
-        if !closed.is_empty() {
-            content.push_str("## Closed\n\n");
-            for issue in &closed {
-                let closed_date = issue
-                    .closed_at
-                    .map(|dt| dt.format("%Y-%m-%d").to_string())
-                    .unwrap_or_default();
-                content.push_str(&format!(
-                    "- [{}] {} (closed {})\n",
-                    issue.id, issue.title, closed_date
-                ));
-            }
-        }
-
-        if open.is_empty() && closed.is_empty() {
-            content.push_str("No issues yet.\n");
-        }
-
-        fs::write(self.issues_md_path(), content)?;
+        let issues: Vec<_> = self.issues.values().collect();
+        fs::write(self.issues_md_path(), crate::markdown::issues_md(&issues))?;
         Ok(())
     }
 
@@ -484,6 +500,50 @@ impl CrdtStore {
         Ok(())
     }
 
+    /// Validate and apply a batch of operations
+    ///
+    /// Changed issues are written to temporary files first and renamed into
+    /// place once all of them are serialized; ISSUES.md is regenerated once.
+    pub fn apply_batch(&mut self, ops: &[BatchOp]) -> Result<BatchOutcome> {
+        let staged = batch::stage(&self.issues, &self.prefix()?, ops);
+        if !staged.outcome.applied {
+            return Ok(staged.outcome);
+        }
+
+        self.write_issues(staged.changed)?;
+        Ok(staged.outcome)
+    }
+
+    /// Update several existing issues with a single write
+    pub fn update_many(&mut self, issues: Vec<Issue>) -> Result<()> {
+        if let Some(missing) = issues.iter().find(|i| !self.issues.contains_key(&i.id)) {
+            return Err(Error::NotFound(missing.id.clone()));
+        }
+        self.write_issues(issues)
+    }
+
+    /// Write issues to temporary files, then rename them all into place and
+    /// regenerate ISSUES.md once
+    fn write_issues(&mut self, issues: Vec<Issue>) -> Result<()> {
+        fs::create_dir_all(self.crdt_dir())?;
+        let mut pending = Vec::with_capacity(issues.len());
+        for issue in &issues {
+            let bytes = self.issue_to_doc(issue)?.save();
+            let path = self.issue_path(&issue.id);
+            let tmp_path = path.with_extension("automerge.tmp");
+            fs::write(&tmp_path, bytes)?;
+            pending.push((tmp_path, path));
+        }
+        for (tmp_path, path) in pending {
+            fs::rename(tmp_path, path)?;
+        }
+
+        for issue in issues {
+            self.issues.insert(issue.id.clone(), issue);
+        }
+        self.regenerate_issues_md()
+    }
+
     /// List all issues (excluding tombstones by default)
     pub fn list(&self, include_tombstones: bool) -> Vec<&Issue> {
         self.issues
@@ -617,6 +677,7 @@ impl CrdtStore {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::DependencyType;
 
     #[test]
     fn test_issue_roundtrip() {
@@ -639,4 +700,27 @@ mod tests {
         assert_eq!(issue.priority, roundtrip.priority);
         assert_eq!(issue.labels, roundtrip.labels);
     }
+
+    #[test]
+    fn test_dependency_and_comment_roundtrip() {
+        let store = CrdtStore {
+            root: PathBuf::from("/tmp"),
+            issues: HashMap::new(),
+        };
+
+        let mut issue = Issue::new("test-123".to_string(), "Test Issue".to_string());
+        issue.add_dependency("test-1".to_string(), DependencyType::ParentChild);
+        issue.add_comment(Some("alice".to_string()), "Looks good".to_string());
+
+        let doc = store.issue_to_doc(&issue).unwrap();
+        let roundtrip = store.doc_to_issue(&doc).unwrap();
+
+        assert_eq!(
+            roundtrip.dependencies[0].dep_type,
+            DependencyType::ParentChild
+        );
+        assert_eq!(roundtrip.comments.len(), 1);
+        assert_eq!(roundtrip.comments[0].author.as_deref(), Some("alice"));
+        assert_eq!(roundtrip.comments[0].text, "Looks good");
+    }
 }
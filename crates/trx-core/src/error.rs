@@ -34,6 +34,15 @@ pub enum Error {
     #[error("Invalid issue type: {0}")]
     InvalidType(String),
 
+    #[error("Invalid dependency type: {0}")]
+    InvalidDependencyType(String),
+
+    #[error("Invalid operation: {0}")]
+    InvalidOperation(String),
+
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
     #[error("Service error: {0}")]
     Service(String),
 
@@ -6,12 +6,22 @@ use crate::{DependencyType, Issue};
 use petgraph::algo::{is_cyclic_directed, toposort};
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::visit::EdgeRef;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Issue dependency graph
 pub struct IssueGraph {
     graph: DiGraph<String, DependencyType>,
     node_map: HashMap<String, NodeIndex>,
+    titles: HashMap<String, String>,
+}
+
+/// A dependency edge: `issue_id` depends on `depends_on_id`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct GraphEdge {
+    pub issue_id: String,
+    pub depends_on_id: String,
+    #[serde(rename = "type")]
+    pub dep_type: DependencyType,
 }
 
 impl IssueGraph {
@@ -19,11 +29,13 @@ impl IssueGraph {
     pub fn from_issues(issues: &[&Issue]) -> Self {
         let mut graph = DiGraph::new();
         let mut node_map = HashMap::new();
+        let mut titles = HashMap::new();
 
         // Add all issues as nodes
         for issue in issues {
             let idx = graph.add_node(issue.id.clone());
             node_map.insert(issue.id.clone(), idx);
+            titles.insert(issue.id.clone(), issue.title.clone());
         }
 
         // Add dependency edges
@@ -37,7 +49,28 @@ impl IssueGraph {
             }
         }
 
-        Self { graph, node_map }
+        Self {
+            graph,
+            node_map,
+            titles,
+        }
+    }
+
+    /// All issue IDs in the graph
+    pub fn node_ids(&self) -> Vec<String> {
+        self.graph.node_weights().cloned().collect()
+    }
+
+    /// All dependency edges between issues in the graph
+    pub fn edges(&self) -> Vec<GraphEdge> {
+        self.graph
+            .edge_references()
+            .map(|e| GraphEdge {
+                issue_id: self.graph[e.source()].clone(),
+                depends_on_id: self.graph[e.target()].clone(),
+                dep_type: *e.weight(),
+            })
+            .collect()
     }
 
     /// Check if the graph has cycles
@@ -69,27 +102,35 @@ impl IssueGraph {
         })
     }
 
-    /// Get issues that are ready (no open blocking dependencies)
+    /// Get issues that are ready (no open blocking dependencies and not
+    /// claimed by an agent)
     pub fn ready_issues<'a>(&self, issues: &'a [&'a Issue]) -> Vec<&'a Issue> {
-        let open_ids: std::collections::HashSet<_> = issues
-            .iter()
-            .filter(|i| i.status.is_open())
-            .map(|i| i.id.as_str())
-            .collect();
+        let now = chrono::Utc::now();
+        let open_ids = open_ids(issues);
 
         issues
             .iter()
             .filter(|issue| {
                 issue.status.is_open()
-                    && !issue.dependencies.iter().any(|dep| {
-                        dep.dep_type == DependencyType::Blocks
-                            && open_ids.contains(dep.depends_on_id.as_str())
-                    })
+                    && issue.claimed_by(now).is_none()
+                    && !has_open_blocker(issue, &open_ids)
             })
             .copied()
             .collect()
     }
 
+    /// Get open issues waiting on an open `blocks` dependency, whether or
+    /// not they are claimed
+    pub fn blocked_issues<'a>(&self, issues: &'a [&'a Issue]) -> Vec<&'a Issue> {
+        let open_ids = open_ids(issues);
+
+        issues
+            .iter()
+            .filter(|issue| issue.status.is_open() && has_open_blocker(issue, &open_ids))
+            .copied()
+            .collect()
+    }
+
     /// Get issues blocked by a given issue
     pub fn blocked_by(&self, issue_id: &str) -> Vec<String> {
         if let Some(&idx) = self.node_map.get(issue_id) {
@@ -114,6 +155,165 @@ impl IssueGraph {
         }
     }
 
+    /// All issues that block a given issue, directly or through other blockers
+    ///
+    /// Only `blocks` edges are followed. Nearest blockers come first.
+    pub fn transitive_blockers(&self, issue_id: &str) -> Vec<String> {
+        let Some(&start) = self.node_map.get(issue_id) else {
+            return Vec::new();
+        };
+
+        let mut seen = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        let mut blockers = Vec::new();
+
+        while let Some(node) = queue.pop_front() {
+            for edge in self.graph.edges(node) {
+                if *edge.weight() == DependencyType::Blocks && seen.insert(edge.target()) {
+                    blockers.push(self.graph[edge.target()].clone());
+                    queue.push_back(edge.target());
+                }
+            }
+        }
+
+        blockers
+    }
+
+    /// Whether making `issue_id` wait on `depends_on_id` through a `blocks`
+    /// edge would close a cycle
+    pub fn would_create_cycle(&self, issue_id: &str, depends_on_id: &str) -> bool {
+        issue_id == depends_on_id
+            || self
+                .transitive_blockers(depends_on_id)
+                .iter()
+                .any(|id| id == issue_id)
+    }
+
+    /// Number of issues each issue directly blocks
+    pub fn blocks_count(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for edge in self.graph.edge_references() {
+            if *edge.weight() == DependencyType::Blocks {
+                *counts.entry(self.graph[edge.target()].clone()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Longest chain of `blocks` dependencies
+    ///
+    /// Returned in working order: the first issue has no open blockers and
+    /// the last one is the final dependent. Returns `None` if the blocking
+    /// edges contain a cycle.
+    pub fn critical_path(&self) -> Option<Vec<String>> {
+        let n = self.graph.node_count();
+        let mut remaining: Vec<usize> = vec![0; n];
+        for edge in self.graph.edge_references() {
+            if *edge.weight() == DependencyType::Blocks {
+                remaining[edge.source().index()] += 1;
+            }
+        }
+
+        // Kahn's algorithm from the leaf blockers towards their dependents,
+        // tracking the longest chain that reaches each node.
+        let mut queue: VecDeque<NodeIndex> = self
+            .graph
+            .node_indices()
+            .filter(|idx| remaining[idx.index()] == 0)
+            .collect();
+        let mut depth = vec![1usize; n];
+        let mut prev: Vec<Option<NodeIndex>> = vec![None; n];
+        let mut visited = 0;
+
+        while let Some(node) = queue.pop_front() {
+            visited += 1;
+            for edge in self
+                .graph
+                .edges_directed(node, petgraph::Direction::Incoming)
+            {
+                if *edge.weight() != DependencyType::Blocks {
+                    continue;
+                }
+                let dependent = edge.source();
+                if depth[node.index()] + 1 > depth[dependent.index()] {
+                    depth[dependent.index()] = depth[node.index()] + 1;
+                    prev[dependent.index()] = Some(node);
+                }
+                remaining[dependent.index()] -= 1;
+                if remaining[dependent.index()] == 0 {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+
+        if visited < n {
+            return None;
+        }
+
+        let Some(mut node) = self
+            .graph
+            .node_indices()
+            .max_by_key(|idx| depth[idx.index()])
+        else {
+            return Some(Vec::new());
+        };
+        let mut path = vec![self.graph[node].clone()];
+        while let Some(p) = prev[node.index()] {
+            path.push(self.graph[p].clone());
+            node = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Render the graph in Graphviz DOT format
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph issues {\n  rankdir=LR;\n  node [shape=box];\n");
+        for idx in self.graph.node_indices() {
+            let id = &self.graph[idx];
+            let title = self.titles.get(id).map(String::as_str).unwrap_or("");
+            out.push_str(&format!(
+                "  \"{}\" [label=\"{}\\n{}\"];\n",
+                id,
+                id,
+                dot_escape(title)
+            ));
+        }
+        for edge in self.edges() {
+            out.push_str(&format!(
+                "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                edge.issue_id, edge.depends_on_id, edge.dep_type
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render the graph as a Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        let node_name = |idx: NodeIndex| format!("n{}", idx.index());
+        let mut out = String::from("graph LR\n");
+        for idx in self.graph.node_indices() {
+            let id = &self.graph[idx];
+            let title = self.titles.get(id).map(String::as_str).unwrap_or("");
+            out.push_str(&format!(
+                "  {}[\"{}: {}\"]\n",
+                node_name(idx),
+                id,
+                mermaid_escape(title)
+            ));
+        }
+        for edge in self.graph.edge_references() {
+            out.push_str(&format!(
+                "  {} -->|{}| {}\n",
+                node_name(edge.source()),
+                edge.weight(),
+                node_name(edge.target())
+            ));
+        }
+        out
+    }
+
     /// Calculate PageRank-style scores for prioritization
     pub fn pagerank(&self, damping: f64, iterations: usize) -> HashMap<String, f64> {
         let n = self.graph.node_count();
@@ -157,6 +357,46 @@ impl IssueGraph {
     }
 }
 
+/// Escape text for a quoted DOT string: backslashes first so the escapes
+/// added for quotes and line breaks are not doubled
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+/// Escape text for a quoted Mermaid label: `#` first so the entity codes
+/// added for other characters are not escaped again, and line breaks last
+/// so their `<br>` survives
+fn mermaid_escape(text: &str) -> String {
+    text.replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('[', "#91;")
+        .replace(']', "#93;")
+        .replace('|', "#124;")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+/// IDs of the open issues in a listing
+fn open_ids<'a>(issues: &[&'a Issue]) -> HashSet<&'a str> {
+    issues
+        .iter()
+        .filter(|i| i.status.is_open())
+        .map(|i| i.id.as_str())
+        .collect()
+}
+
+/// Whether an issue has a `blocks` dependency on one of `open_ids`
+fn has_open_blocker(issue: &Issue, open_ids: &HashSet<&str>) -> bool {
+    issue.dependencies.iter().any(|dep| {
+        dep.dep_type == DependencyType::Blocks && open_ids.contains(dep.depends_on_id.as_str())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,7 +404,7 @@ mod tests {
 
     #[test]
     fn test_ready_issues() {
-        let mut issue1 = Issue::new("trx-001".into(), "First".into());
+        let issue1 = Issue::new("trx-001".into(), "First".into());
         let mut issue2 = Issue::new("trx-002".into(), "Second".into());
 
         // issue2 blocks issue1
@@ -176,5 +416,89 @@ mod tests {
         let ready = graph.ready_issues(&issues);
         assert_eq!(ready.len(), 1);
         assert_eq!(ready[0].id, "trx-001");
+
+        let blocked = graph.blocked_issues(&issues);
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].id, "trx-002");
+    }
+
+    #[test]
+    fn test_ready_issues_skip_claims() {
+        let now = chrono::Utc::now();
+        let mut claimed = Issue::new("trx-001".into(), "Claimed".into());
+        let mut expired = Issue::new("trx-002".into(), "Expired".into());
+        claimed.claim("agent-1", now + chrono::Duration::minutes(5));
+        expired.claim("agent-2", now - chrono::Duration::minutes(5));
+        assert_eq!(claimed.claimed_by(now), Some("agent-1"));
+        assert_eq!(expired.claimed_by(now), None);
+
+        let issues: Vec<&Issue> = vec![&claimed, &expired];
+        let graph = IssueGraph::from_issues(&issues);
+        let ready = graph.ready_issues(&issues);
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].id, "trx-002");
+        // Claimed work is taken, not blocked
+        assert!(graph.blocked_issues(&issues).is_empty());
+
+        claimed.release();
+        assert_eq!(claimed.status, crate::Status::Open);
+        assert_eq!(claimed.assignee, None);
+    }
+
+    #[test]
+    fn test_critical_path_and_transitive_blockers() {
+        let a = Issue::new("trx-a".into(), "A".into());
+        let mut b = Issue::new("trx-b".into(), "B".into());
+        let mut c = Issue::new("trx-c".into(), "C".into());
+        let mut d = Issue::new("trx-d".into(), "D".into());
+
+        // a blocks b, b blocks c; d is only related to a
+        b.add_dependency("trx-a".into(), DependencyType::Blocks);
+        c.add_dependency("trx-b".into(), DependencyType::Blocks);
+        d.add_dependency("trx-a".into(), DependencyType::Related);
+
+        let issues: Vec<&Issue> = vec![&a, &b, &c, &d];
+        let graph = IssueGraph::from_issues(&issues);
+
+        assert_eq!(
+            graph.critical_path(),
+            Some(vec!["trx-a".into(), "trx-b".into(), "trx-c".into()])
+        );
+        assert_eq!(graph.transitive_blockers("trx-c"), vec!["trx-b", "trx-a"]);
+        assert!(graph.transitive_blockers("trx-d").is_empty());
+        assert_eq!(graph.blocks_count().get("trx-a"), Some(&1));
+        assert!(graph.would_create_cycle("trx-a", "trx-c"));
+        assert!(graph.would_create_cycle("trx-a", "trx-a"));
+        assert!(!graph.would_create_cycle("trx-c", "trx-a"));
+        assert!(!graph.would_create_cycle("trx-a", "trx-d"));
+    }
+
+    #[test]
+    fn test_critical_path_with_cycle() {
+        let mut a = Issue::new("trx-a".into(), "A".into());
+        let mut b = Issue::new("trx-b".into(), "B".into());
+        a.add_dependency("trx-b".into(), DependencyType::Blocks);
+        b.add_dependency("trx-a".into(), DependencyType::Blocks);
+
+        let issues: Vec<&Issue> = vec![&a, &b];
+        assert_eq!(IssueGraph::from_issues(&issues).critical_path(), None);
+    }
+
+    #[test]
+    fn test_to_dot_escapes_titles() {
+        let a = Issue::new("trx-a".into(), "C:\\tmp \"quoted\"\nsecond line".into());
+        let dot = IssueGraph::from_issues(&[&a]).to_dot();
+        assert!(dot.contains(r#"label="trx-a\nC:\\tmp \"quoted\"\nsecond line"];"#));
+        assert_eq!(dot.lines().count(), 5);
+    }
+
+    #[test]
+    fn test_to_mermaid_escapes_titles() {
+        let a = Issue::new("trx-a".into(), "C:\\tmp \"quoted\"\nsecond line".into());
+        let b = Issue::new("trx-b".into(), "a]b|c #1 <d>".into());
+        let mermaid = IssueGraph::from_issues(&[&a, &b]).to_mermaid();
+        assert!(mermaid.contains(r#"["trx-a: C:\tmp #quot;quoted#quot;<br>second line"]"#));
+        assert!(mermaid.contains(r#"["trx-b: a#93;b#124;c #35;1 #lt;d#gt;"]"#));
+        assert_eq!(mermaid.lines().count(), 3);
     }
 }
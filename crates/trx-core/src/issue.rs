@@ -108,6 +108,19 @@ pub enum DependencyType {
     Related,
 }
 
+impl std::str::FromStr for DependencyType {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "blocks" => Ok(DependencyType::Blocks),
+            "parent_child" | "parent-child" => Ok(DependencyType::ParentChild),
+            "related" => Ok(DependencyType::Related),
+            _ => Err(crate::Error::InvalidDependencyType(s.to_string())),
+        }
+    }
+}
+
 impl std::fmt::Display for DependencyType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -135,6 +148,18 @@ pub struct Dependency {
     pub created_by: Option<String>,
 }
 
+/// Comment left on an issue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comment {
+    /// Who wrote the comment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Comment body
+    pub text: String,
+    /// When the comment was added
+    pub created_at: DateTime<Utc>,
+}
+
 /// Core issue structure
 ///
 /// Designed to be minimal but beads-viewer compatible.
@@ -194,6 +219,14 @@ pub struct Issue {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub assignee: Option<String>,
 
+    /// When the assignee's claim lapses, if the issue is claimed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lease_expires_at: Option<DateTime<Utc>>,
+
+    /// Comments, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<Comment>,
+
     /// Notes (additional context)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
@@ -212,6 +245,17 @@ pub struct Issue {
     pub delete_reason: Option<String>,
 }
 
+/// Check that a priority is in range, 0 (critical) to 4 (backlog)
+pub fn check_priority(priority: u8) -> crate::Result<u8> {
+    if priority > 4 {
+        return Err(crate::Error::InvalidOperation(format!(
+            "priority must be 0-4, got {}",
+            priority
+        )));
+    }
+    Ok(priority)
+}
+
 impl Issue {
     /// Create a new issue with minimal required fields
     pub fn new(id: String, title: String) -> Self {
@@ -232,6 +276,8 @@ impl Issue {
             created_by: None,
             close_reason: None,
             assignee: None,
+            lease_expires_at: None,
+            comments: Vec::new(),
             notes: None,
             original_type: None,
             deleted_by: None,
@@ -274,6 +320,61 @@ impl Issue {
         self.closed_at = Some(Utc::now());
         self.updated_at = Utc::now();
         self.close_reason = reason;
+        self.lease_expires_at = None;
+    }
+
+    /// Change status, closing through `close` and dropping the close time
+    /// and reason when the issue reopens
+    pub fn set_status(&mut self, status: Status) {
+        if status == Status::Closed {
+            if self.status != Status::Closed {
+                self.close(None);
+            }
+            return;
+        }
+        if status.is_open() {
+            self.closed_at = None;
+            self.close_reason = None;
+        }
+        self.status = status;
+        self.updated_at = Utc::now();
+    }
+
+    /// Agent holding an unexpired claim on this issue
+    pub fn claimed_by(&self, now: DateTime<Utc>) -> Option<&str> {
+        match self.lease_expires_at {
+            Some(expires) if expires > now => self.assignee.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Claim for an agent until the lease expires, marking it in progress
+    pub fn claim(&mut self, agent: &str, expires: DateTime<Utc>) {
+        self.assignee = Some(agent.to_string());
+        self.lease_expires_at = Some(expires);
+        self.status = Status::InProgress;
+        self.updated_at = Utc::now();
+    }
+
+    /// Drop the claim and return the issue to the open pool
+    pub fn release(&mut self) {
+        self.assignee = None;
+        self.lease_expires_at = None;
+        if self.status == Status::InProgress {
+            self.status = Status::Open;
+        }
+        self.updated_at = Utc::now();
+    }
+
+    /// Append a comment
+    pub fn add_comment(&mut self, author: Option<String>, text: String) {
+        let now = Utc::now();
+        self.comments.push(Comment {
+            author,
+            text,
+            created_at: now,
+        });
+        self.updated_at = now;
     }
 
     /// Mark as deleted (tombstone)
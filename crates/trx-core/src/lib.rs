@@ -3,25 +3,37 @@
 //! Provides the data model, storage, and graph operations for a minimal
 //! git-backed issue tracker. Supports both JSONL (v1) and CRDT (v2) storage.
 
+pub mod batch;
 pub mod config;
 pub mod crdt_store;
 pub mod error;
 pub mod graph;
 pub mod id;
 pub mod issue;
+pub mod markdown;
+pub mod query;
 pub mod service;
 pub mod store;
+pub mod sync;
 pub mod unified_store;
+pub mod webhook;
+pub mod workspace;
 
-pub use config::{Config, StorageVersion};
+pub use batch::{BatchOp, BatchOutcome, OpResult};
+pub use config::{Config, StorageVersion, ThemeConfig, TuiConfig};
 pub use crdt_store::CrdtStore;
 pub use error::Error;
 pub use graph::IssueGraph;
 pub use id::generate_id;
-pub use issue::{Dependency, DependencyType, Issue, IssueType, Status};
-pub use service::{ServiceManager, ServiceStatus};
+pub use issue::{Comment, Dependency, DependencyType, Issue, IssueType, Status, check_priority};
+pub use query::{Filter, Page, SortSpec};
+pub use service::{DEFAULT_MCP_PORT, DEFAULT_PORT, Service, ServiceManager, ServiceStatus};
 pub use store::Store;
-pub use unified_store::{MigrationResult, UnifiedStore, migrate_v1_to_v2, rollback_v2_to_v1};
+pub use unified_store::{
+    MigrationResult, StoreLock, UnifiedStore, migrate_v1_to_v2, rollback_v2_to_v1,
+};
+pub use webhook::{WebhookConfig, WebhookEvent};
+pub use workspace::{Workspace, WorkspaceRepo};
 
 /// Result type for trx operations
 pub type Result<T> = std::result::Result<T, Error>;
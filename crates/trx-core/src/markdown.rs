@@ -0,0 +1,140 @@
+//! Markdown rendering of issues
+//!
+//! Used for the generated ISSUES.md and anywhere a human-readable view of
+//! an issue is needed.
+
+use crate::{Issue, Status};
+
+/// Render the ISSUES.md summary: open issues by priority, then closed ones
+pub fn issues_md(issues: &[&Issue]) -> String {
+    let mut content = String::from("# Issues\n\n");
+
+    // Collect and sort issues
+    let mut open: Vec<_> = issues.iter().filter(|i| i.status.is_open()).collect();
+    let mut closed: Vec<_> = issues
+        .iter()
+        .filter(|i| i.status.is_closed() && i.status != Status::Tombstone)
+        .collect();
+
+    // Sort by priority, then by created_at
+    open.sort_by(|a, b| {
+        a.priority
+            .cmp(&b.priority)
+            .then_with(|| b.created_at.cmp(&a.created_at))
+    });
+    closed.sort_by_key(|i| std::cmp::Reverse(i.closed_at));
+
+    // Open issues
+    if !open.is_empty() {
+        content.push_str("## Open\n\n");
+        for issue in &open {
+            content.push_str(&format!(
+                "### [{}] {} (P{}, {})\n",
+                issue.id, issue.title, issue.priority, issue.issue_type
+            ));
+            if let Some(ref desc) = issue.description {
+                // Truncate long descriptions
+                let desc_preview: String = desc.lines().take(5).collect::<Vec<_>>().join("\n");
+                content.push_str(&desc_preview);
+                if desc.lines().count() > 5 {
+                    content.push_str("\n...\n");
+                }
+                content.push('\n');
+            }
+            content.push('\n');
+        }
+    }
+
+    // Closed issues
+    if !closed.is_empty() {
+        content.push_str("## Closed\n\n");
+        for issue in &closed {
+            let closed_date = issue
+                .closed_at
+                .map(|dt| dt.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            content.push_str(&format!(
+                "- [{}] {} (closed {})\n",
+                issue.id, issue.title, closed_date
+            ));
+        }
+    }
+
+    if open.is_empty() && closed.is_empty() {
+        content.push_str("No issues yet.\n");
+    }
+
+    content
+}
+
+/// Render a single issue with its metadata, description and dependencies
+pub fn issue_md(issue: &Issue) -> String {
+    let mut content = format!("# [{}] {}\n\n", issue.id, issue.title);
+
+    content.push_str(&format!("- Status: {}\n", issue.status));
+    content.push_str(&format!("- Priority: P{}\n", issue.priority));
+    content.push_str(&format!("- Type: {}\n", issue.issue_type));
+    if let Some(assignee) = &issue.assignee {
+        content.push_str(&format!("- Assignee: {}\n", assignee));
+    }
+    if !issue.labels.is_empty() {
+        content.push_str(&format!("- Labels: {}\n", issue.labels.join(", ")));
+    }
+    content.push_str(&format!(
+        "- Created: {}\n",
+        issue.created_at.format("%Y-%m-%d %H:%M UTC")
+    ));
+    content.push_str(&format!(
+        "- Updated: {}\n",
+        issue.updated_at.format("%Y-%m-%d %H:%M UTC")
+    ));
+    if let Some(closed_at) = issue.closed_at {
+        content.push_str(&format!(
+            "- Closed: {}{}\n",
+            closed_at.format("%Y-%m-%d %H:%M UTC"),
+            issue
+                .close_reason
+                .as_ref()
+                .map(|r| format!(" ({})", r))
+                .unwrap_or_default()
+        ));
+    }
+
+    if let Some(desc) = &issue.description {
+        content.push_str(&format!("\n{}\n", desc.trim_end()));
+    }
+    if let Some(notes) = &issue.notes {
+        content.push_str(&format!("\n## Notes\n\n{}\n", notes.trim_end()));
+    }
+    if !issue.dependencies.is_empty() {
+        content.push_str("\n## Dependencies\n\n");
+        for dep in &issue.dependencies {
+            content.push_str(&format!("- {} ({})\n", dep.depends_on_id, dep.dep_type));
+        }
+    }
+
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_md() {
+        let mut issue = Issue::new("trx-a".into(), "Fix login".into());
+        issue.description = Some("Steps to reproduce".into());
+        issue.labels = vec!["auth".into()];
+        issue.add_dependency("trx-b".into(), crate::DependencyType::Blocks);
+
+        let md = issue_md(&issue);
+        assert!(md.starts_with("# [trx-a] Fix login\n"));
+        assert!(md.contains("- Labels: auth\n"));
+        assert!(md.contains("\nSteps to reproduce\n"));
+        assert!(md.contains("- trx-b (blocks)\n"));
+
+        let summary = issues_md(&[&issue]);
+        assert!(summary.contains("### [trx-a] Fix login (P2, task)\n"));
+        assert_eq!(issues_md(&[]), "# Issues\n\nNo issues yet.\n");
+    }
+}
@@ -0,0 +1,462 @@
+//! Filtering, sorting, pagination and field selection for issue listings
+//!
+//! Shared by the API and MCP servers so every list endpoint accepts the
+//! same `sort`, `limit`, `cursor` and `fields` options.
+
+use crate::{Error, Issue, IssueType, Result, Status};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// A parsed filter expression, e.g. `status:open type:bug priority:<=1 login`
+///
+/// Terms are separated by whitespace and must all match. `key:value` terms
+/// filter on `status`, `type`, `label`, `assignee` or `priority`; the first
+/// three accept comma-separated alternatives. `priority` takes an optional
+/// comparison (`<`, `<=`, `>`, `>=`). Any other word must appear in the
+/// title or description, ignoring case.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    terms: Vec<FilterTerm>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FilterTerm {
+    Status(Vec<Status>),
+    Type(Vec<IssueType>),
+    Label(Vec<String>),
+    Assignee(String),
+    Priority(Ordering, bool, u8),
+    Text(String),
+}
+
+impl std::str::FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut terms = Vec::new();
+        for word in s.split_whitespace() {
+            let term = match word.split_once(':') {
+                Some(("status", v)) => {
+                    FilterTerm::Status(v.split(',').map(str::parse).collect::<Result<_>>()?)
+                }
+                Some(("type", v)) => {
+                    FilterTerm::Type(v.split(',').map(str::parse).collect::<Result<_>>()?)
+                }
+                Some(("label", v)) => FilterTerm::Label(v.split(',').map(String::from).collect()),
+                Some(("assignee", v)) => FilterTerm::Assignee(v.to_string()),
+                Some(("priority", v)) => parse_priority_term(v)?,
+                _ => FilterTerm::Text(word.to_lowercase()),
+            };
+            terms.push(term);
+        }
+        Ok(Self { terms })
+    }
+}
+
+fn parse_priority_term(v: &str) -> Result<FilterTerm> {
+    let (ord, or_equal, n) = if let Some(n) = v.strip_prefix("<=") {
+        (Ordering::Less, true, n)
+    } else if let Some(n) = v.strip_prefix(">=") {
+        (Ordering::Greater, true, n)
+    } else if let Some(n) = v.strip_prefix('<') {
+        (Ordering::Less, false, n)
+    } else if let Some(n) = v.strip_prefix('>') {
+        (Ordering::Greater, false, n)
+    } else {
+        (Ordering::Equal, true, v)
+    };
+    let n = n
+        .parse()
+        .map_err(|_| Error::InvalidQuery(format!("invalid priority '{}'", v)))?;
+    Ok(FilterTerm::Priority(ord, or_equal, n))
+}
+
+impl Filter {
+    /// Whether the filter has no terms and so matches everything
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Check whether an issue matches every term
+    pub fn matches(&self, issue: &Issue) -> bool {
+        self.terms.iter().all(|term| match term {
+            FilterTerm::Status(statuses) => statuses.contains(&issue.status),
+            FilterTerm::Type(types) => types.contains(&issue.issue_type),
+            FilterTerm::Label(labels) => labels.iter().any(|l| issue.labels.contains(l)),
+            FilterTerm::Assignee(assignee) => issue.assignee.as_deref() == Some(assignee),
+            FilterTerm::Priority(ord, or_equal, n) => {
+                let cmp = issue.priority.cmp(n);
+                cmp == *ord || (*or_equal && cmp == Ordering::Equal)
+            }
+            FilterTerm::Text(text) => {
+                issue.title.to_lowercase().contains(text)
+                    || issue
+                        .description
+                        .as_deref()
+                        .is_some_and(|d| d.to_lowercase().contains(text))
+            }
+        })
+    }
+}
+
+/// Field an issue listing can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Id,
+    Title,
+    Status,
+    Priority,
+    IssueType,
+    CreatedAt,
+    UpdatedAt,
+    ClosedAt,
+}
+
+impl std::str::FromStr for SortField {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "id" => Ok(SortField::Id),
+            "title" => Ok(SortField::Title),
+            "status" => Ok(SortField::Status),
+            "priority" => Ok(SortField::Priority),
+            "type" | "issue_type" => Ok(SortField::IssueType),
+            "created" | "created_at" => Ok(SortField::CreatedAt),
+            "updated" | "updated_at" => Ok(SortField::UpdatedAt),
+            "closed" | "closed_at" => Ok(SortField::ClosedAt),
+            _ => Err(Error::InvalidQuery(format!("unknown sort field '{}'", s))),
+        }
+    }
+}
+
+/// A single sort key with direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl SortKey {
+    fn compare(&self, a: &Issue, b: &Issue) -> Ordering {
+        let ord = match self.field {
+            SortField::Id => a.id.cmp(&b.id),
+            SortField::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            SortField::Status => a.status.to_string().cmp(&b.status.to_string()),
+            SortField::Priority => a.priority.cmp(&b.priority),
+            SortField::IssueType => a.issue_type.to_string().cmp(&b.issue_type.to_string()),
+            SortField::CreatedAt => a.created_at.cmp(&b.created_at),
+            SortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+            SortField::ClosedAt => a.closed_at.cmp(&b.closed_at),
+        };
+        if self.descending { ord.reverse() } else { ord }
+    }
+}
+
+/// Ordered list of sort keys, e.g. `updated_at:desc,priority`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortSpec {
+    keys: Vec<SortKey>,
+}
+
+impl Default for SortSpec {
+    /// Priority first, newest first within a priority
+    fn default() -> Self {
+        Self {
+            keys: vec![
+                SortKey {
+                    field: SortField::Priority,
+                    descending: false,
+                },
+                SortKey {
+                    field: SortField::CreatedAt,
+                    descending: true,
+                },
+            ],
+        }
+    }
+}
+
+impl std::str::FromStr for SortSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut keys = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (field, dir) = part.split_once(':').unwrap_or((part, "asc"));
+            let descending = match dir.to_lowercase().as_str() {
+                "asc" => false,
+                "desc" => true,
+                _ => {
+                    return Err(Error::InvalidQuery(format!(
+                        "unknown sort direction '{}'",
+                        dir
+                    )));
+                }
+            };
+            keys.push(SortKey {
+                field: field.parse()?,
+                descending,
+            });
+        }
+        if keys.is_empty() {
+            return Ok(Self::default());
+        }
+        Ok(Self { keys })
+    }
+}
+
+impl SortSpec {
+    /// Compare two issues, falling back to ID so the order is total
+    pub fn compare(&self, a: &Issue, b: &Issue) -> Ordering {
+        self.keys
+            .iter()
+            .map(|k| k.compare(a, b))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| a.id.cmp(&b.id))
+    }
+
+    /// Sort issues in place
+    pub fn sort(&self, issues: &mut [&Issue]) {
+        issues.sort_by(|a, b| self.compare(a, b));
+    }
+}
+
+/// One page of a sorted listing
+#[derive(Debug)]
+pub struct Page<'a> {
+    pub items: Vec<&'a Issue>,
+    /// Number of issues matching the filters, across all pages
+    pub total: usize,
+    /// Cursor for the next page, if there is one
+    pub next_cursor: Option<String>,
+}
+
+/// Sort issues and return the page following `cursor`
+///
+/// The cursor is the ID of the last issue on the previous page. Pages are
+/// keyset-based: the next page starts after the position that issue occupies
+/// in the current ordering, so inserts, deletes and edits to other issues
+/// between requests do not shift or repeat items. The cursor issue's own
+/// position is looked up again, though, so if an edit moves it (say its
+/// priority changes under `sort=priority`) the next page follows it to the
+/// new position and may skip or repeat issues. `cursor_issue` is the
+/// looked-up cursor issue, which may since have been filtered out of
+/// `issues`. A `limit` of zero is rejected, since it could never advance.
+pub fn paginate<'a>(
+    issues: Vec<&'a Issue>,
+    sort: &SortSpec,
+    cursor_issue: Option<&Issue>,
+    limit: Option<usize>,
+) -> Result<Page<'a>> {
+    let issues = issues.into_iter().map(|i| ((), i)).collect();
+    let page = paginate_keyed(issues, sort, cursor_issue.map(|i| (&(), i)), limit)?;
+    Ok(Page {
+        items: page.items.into_iter().map(|(_, i)| i).collect(),
+        total: page.total,
+        next_cursor: page.next_cursor.map(|(_, id)| id),
+    })
+}
+
+/// One page of a listing drawn from several sources
+#[derive(Debug)]
+pub struct KeyedPage<'a, K> {
+    pub items: Vec<(K, &'a Issue)>,
+    /// Number of issues matching the filters, across all pages
+    pub total: usize,
+    /// Source key and ID of the last item, if there is a next page
+    pub next_cursor: Option<(K, String)>,
+}
+
+/// Like [`paginate`], for issues tagged with the source they came from
+///
+/// The key (a repository name, say) breaks ties between issues that sort
+/// equally, so issues sharing an ID across sources keep a stable order, and
+/// it is returned with the next cursor so that cursor stays unambiguous.
+pub fn paginate_keyed<'a, K: Ord + Clone>(
+    mut issues: Vec<(K, &'a Issue)>,
+    sort: &SortSpec,
+    cursor: Option<(&K, &Issue)>,
+    limit: Option<usize>,
+) -> Result<KeyedPage<'a, K>> {
+    if limit == Some(0) {
+        return Err(Error::InvalidQuery("limit must be at least 1".into()));
+    }
+    let total = issues.len();
+    let compare =
+        |(ka, a): (&K, &Issue), (kb, b): (&K, &Issue)| sort.compare(a, b).then_with(|| ka.cmp(kb));
+    issues.sort_by(|(ka, a), (kb, b)| compare((ka, a), (kb, b)));
+
+    let start = match cursor {
+        Some(after) => issues.partition_point(|(k, i)| compare((k, i), after).is_le()),
+        None => 0,
+    };
+
+    let end = match limit {
+        Some(limit) => (start + limit).min(issues.len()),
+        None => issues.len(),
+    };
+
+    let next_cursor = if end < issues.len() && end > start {
+        let (key, issue) = &issues[end - 1];
+        Some((key.clone(), issue.id.clone()))
+    } else {
+        None
+    };
+
+    Ok(KeyedPage {
+        items: issues.drain(start..end).collect(),
+        total,
+        next_cursor,
+    })
+}
+
+/// Parse a comma-separated field list, e.g. `id,title,status`
+pub fn parse_fields(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Serialize an issue, keeping only the requested top-level fields
+///
+/// An empty field list returns the full issue.
+pub fn select_fields(issue: &Issue, fields: &[String]) -> Result<Value> {
+    let value = serde_json::to_value(issue)?;
+    if fields.is_empty() {
+        return Ok(value);
+    }
+    match value {
+        Value::Object(map) => Ok(Value::Object(
+            map.into_iter()
+                .filter(|(k, _)| fields.iter().any(|f| f == k))
+                .collect(),
+        )),
+        other => Ok(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn issue(id: &str, priority: u8, age_days: i64) -> Issue {
+        let mut issue = Issue::new(id.into(), id.into());
+        issue.priority = priority;
+        issue.updated_at = chrono::Utc::now() - Duration::days(age_days);
+        issue
+    }
+
+    #[test]
+    fn test_parse_sort_spec() {
+        let spec: SortSpec = "updated_at:desc,priority".parse().unwrap();
+        assert_eq!(
+            spec.keys,
+            vec![
+                SortKey {
+                    field: SortField::UpdatedAt,
+                    descending: true
+                },
+                SortKey {
+                    field: SortField::Priority,
+                    descending: false
+                },
+            ]
+        );
+        assert!("bogus".parse::<SortSpec>().is_err());
+        assert!("priority:sideways".parse::<SortSpec>().is_err());
+    }
+
+    #[test]
+    fn test_paginate_with_cursor() {
+        let issues = [
+            issue("trx-a", 1, 3),
+            issue("trx-b", 0, 1),
+            issue("trx-c", 2, 2),
+        ];
+        let refs: Vec<&Issue> = issues.iter().collect();
+        let spec: SortSpec = "updated_at:desc".parse().unwrap();
+
+        let first = paginate(refs.clone(), &spec, None, Some(2)).unwrap();
+        assert_eq!(first.total, 3);
+        let ids: Vec<_> = first.items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, ["trx-b", "trx-c"]);
+        assert_eq!(first.next_cursor.as_deref(), Some("trx-c"));
+
+        let cursor = issues.iter().find(|i| i.id == "trx-c");
+        let second = paginate(refs.clone(), &spec, cursor, Some(2)).unwrap();
+        let ids: Vec<_> = second.items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, ["trx-a"]);
+        assert_eq!(second.next_cursor, None);
+
+        assert!(paginate(refs, &spec, None, Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_paginate_keyed_across_sources() {
+        // The same ID in two repositories, sorting equally
+        let issues = [
+            issue("trx-a", 1, 1),
+            issue("trx-a", 1, 1),
+            issue("trx-b", 0, 2),
+        ];
+        let tagged = vec![
+            ("web", &issues[0]),
+            ("api", &issues[1]),
+            ("api", &issues[2]),
+        ];
+        let spec: SortSpec = "priority".parse().unwrap();
+
+        let first = paginate_keyed(tagged.clone(), &spec, None, Some(2)).unwrap();
+        let keys: Vec<_> = first
+            .items
+            .iter()
+            .map(|(k, i)| (*k, i.id.as_str()))
+            .collect();
+        assert_eq!(keys, [("api", "trx-b"), ("api", "trx-a")]);
+        assert_eq!(first.next_cursor, Some(("api", "trx-a".to_string())));
+
+        let second = paginate_keyed(tagged, &spec, Some((&"api", &issues[1])), Some(2)).unwrap();
+        let keys: Vec<_> = second
+            .items
+            .iter()
+            .map(|(k, i)| (*k, i.id.as_str()))
+            .collect();
+        assert_eq!(keys, [("web", "trx-a")]);
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut bug = issue("trx-a", 1, 0);
+        bug.issue_type = IssueType::Bug;
+        bug.labels = vec!["backend".into()];
+        bug.title = "Login fails".into();
+        let task = issue("trx-b", 3, 0);
+
+        let filter: Filter = "type:bug,feature priority:<=1 login".parse().unwrap();
+        assert!(filter.matches(&bug));
+        assert!(!filter.matches(&task));
+
+        let filter: Filter = "status:open label:backend".parse().unwrap();
+        assert!(filter.matches(&bug));
+        assert!(!filter.matches(&task));
+
+        assert!("".parse::<Filter>().unwrap().matches(&task));
+        assert!("status:bogus".parse::<Filter>().is_err());
+        assert!("priority:<high".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn test_select_fields() {
+        let issue = issue("trx-a", 1, 0);
+        let value = select_fields(&issue, &parse_fields("id, status")).unwrap();
+        let obj = value.as_object().unwrap();
+        assert_eq!(obj.len(), 2);
+        assert_eq!(obj["id"], "trx-a");
+        assert_eq!(obj["status"], "open");
+    }
+}
@@ -1,41 +1,126 @@
-//! Service management for trx-api
+//! Service management for trx-api and trx-mcp
 //!
-//! Provides start/stop/status functionality for the trx-api daemon.
+//! Provides start/stop/status functionality for the trx-api daemon and the
+//! HTTP mode of trx-mcp.
 
 use crate::Result;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use sysinfo::{Pid, System};
 
-/// Service manager for trx-api
+/// Port trx-api listens on unless told otherwise
+pub const DEFAULT_PORT: u16 = 3847;
+
+/// Port trx-mcp's HTTP transport listens on unless told otherwise
+pub const DEFAULT_MCP_PORT: u16 = 3848;
+
+/// A background service trx can manage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    /// The REST API, `trx-api`
+    Api,
+    /// The MCP server over HTTP, `trx-mcp --http`
+    Mcp,
+}
+
+impl Service {
+    /// Binary name, also used to name the state files
+    pub fn binary(&self) -> &'static str {
+        match self {
+            Service::Api => "trx-api",
+            Service::Mcp => "trx-mcp",
+        }
+    }
+}
+
+/// Service manager for trx-api or trx-mcp
 pub struct ServiceManager {
     state_dir: PathBuf,
+    service: Service,
 }
 
 /// Service status
 #[derive(Debug, Clone)]
 pub enum ServiceStatus {
-    Running { pid: u32, port: Option<u16> },
+    Running {
+        pid: u32,
+        port: Option<u16>,
+        socket: Option<PathBuf>,
+    },
     Stopped,
     Dead, // PID file exists but process not running
 }
 
 impl ServiceManager {
-    /// Create a new service manager
+    /// Create a service manager for trx-api
     pub fn new() -> Result<Self> {
+        Self::for_service(Service::Api)
+    }
+
+    /// Create a service manager for the given service
+    pub fn for_service(service: Service) -> Result<Self> {
         let state_dir = get_state_dir()?;
         std::fs::create_dir_all(&state_dir)?;
-        Ok(Self { state_dir })
+        Ok(Self { state_dir, service })
+    }
+
+    /// The service being managed
+    pub fn service(&self) -> Service {
+        self.service
+    }
+
+    fn state_file(&self, extension: &str) -> PathBuf {
+        self.state_dir
+            .join(format!("{}.{}", self.service.binary(), extension))
+    }
+
+    /// Directory holding the service state files
+    pub fn state_dir(&self) -> &Path {
+        &self.state_dir
+    }
+
+    /// Path to the webhook delivery log
+    pub fn webhook_log_file(&self) -> PathBuf {
+        self.state_dir.join("webhooks.jsonl")
+    }
+
+    /// Path to the list of repositories registered with the running
+    /// service, which it registers again when restarted
+    pub fn repos_file(&self) -> PathBuf {
+        self.state_file("repos.yaml")
     }
 
     /// Path to the PID file
     pub fn pid_file(&self) -> PathBuf {
-        self.state_dir.join("trx-api.pid")
+        self.state_file("pid")
     }
 
     /// Path to the port file
     pub fn port_file(&self) -> PathBuf {
-        self.state_dir.join("trx-api.port")
+        self.state_file("port")
+    }
+
+    /// Path to the file recording the Unix socket the service listens on
+    pub fn socket_file(&self) -> PathBuf {
+        self.state_file("socket")
+    }
+
+    /// Default Unix socket path
+    ///
+    /// Lives in `$XDG_RUNTIME_DIR/trx`, falling back to the state dir.
+    pub fn default_socket(&self) -> PathBuf {
+        std::env::var("XDG_RUNTIME_DIR")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|dir| PathBuf::from(dir).join("trx"))
+            .unwrap_or_else(|| self.state_dir.clone())
+            .join(format!("{}.sock", self.service.binary()))
+    }
+
+    /// Path to the log file written by the background service
+    pub fn log_file(&self) -> PathBuf {
+        self.state_file("log")
     }
 
     /// Check if the service is running
@@ -65,28 +150,55 @@ impl ServiceManager {
             .map_err(|e| crate::Error::Service(format!("Invalid port: {e}")))
     }
 
+    /// Read the socket path from the socket file
+    pub fn read_socket(&self) -> Result<PathBuf> {
+        let content = std::fs::read_to_string(self.socket_file())?;
+        Ok(PathBuf::from(content.trim()))
+    }
+
     /// Start the service
     ///
     /// If `foreground` is true, runs in foreground (blocking).
-    /// Otherwise, spawns as a background daemon.
-    pub fn start(&self, foreground: bool, workdir: Option<&PathBuf>) -> Result<()> {
+    /// Otherwise, spawns as a background daemon. With `socket`, trx-api
+    /// listens on a Unix socket instead of a TCP port; trx-mcp always serves
+    /// HTTP over TCP. `name` names the repository trx-api serves first.
+    pub fn start(
+        &self,
+        foreground: bool,
+        workdir: Option<&PathBuf>,
+        socket: Option<&Path>,
+        name: Option<&str>,
+    ) -> Result<()> {
         if self.is_running() {
             return Err(crate::Error::Service("Service already running".into()));
         }
 
-        let exe = std::env::current_exe()?;
-        let service_exe = exe
-            .parent()
-            .ok_or_else(|| crate::Error::Service("Cannot find service binary".into()))?
-            .join("trx-api");
-
-        if !service_exe.exists() {
-            return Err(crate::Error::Service(
-                "trx-api binary not found. Please install it first.".into(),
-            ));
+        let mut cmd = Command::new(self.binary_path()?);
+        match (self.service, name) {
+            (Service::Api, Some(name)) => {
+                cmd.arg("--name").arg(name);
+            }
+            (Service::Mcp, Some(_)) => {
+                return Err(crate::Error::Service(
+                    "trx-mcp serves a single repository".into(),
+                ));
+            }
+            (_, None) => {}
+        }
+        match (self.service, socket) {
+            (Service::Api, Some(socket)) => {
+                cmd.arg("--socket").arg(socket);
+            }
+            (Service::Api, None) => {}
+            (Service::Mcp, Some(_)) => {
+                return Err(crate::Error::Service(
+                    "trx-mcp does not support Unix sockets".into(),
+                ));
+            }
+            (Service::Mcp, None) => {
+                cmd.arg("--http");
+            }
         }
-
-        let mut cmd = Command::new(&service_exe);
 
         // Pass workdir if specified
         if let Some(dir) = workdir {
@@ -99,23 +211,25 @@ impl ServiceManager {
                 return Err(crate::Error::Service("Service failed to start".into()));
             }
         } else {
-            // Start in background
+            // Start in background, logging to the state dir
+            let log = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.log_file())?;
+            cmd.stdin(Stdio::null())
+                .stdout(log.try_clone()?)
+                .stderr(log);
+
             #[cfg(unix)]
             {
                 use std::os::unix::process::CommandExt;
-                cmd.stdin(Stdio::null())
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .process_group(0) // Create new process group
+                cmd.process_group(0) // Create new process group
                     .spawn()?;
             }
 
             #[cfg(not(unix))]
             {
-                cmd.stdin(Stdio::null())
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .spawn()?;
+                cmd.spawn()?;
             }
 
             // Wait for service to start
@@ -136,6 +250,24 @@ impl ServiceManager {
         Ok(())
     }
 
+    /// Path to the service binary, installed next to the running executable
+    pub fn binary_path(&self) -> Result<PathBuf> {
+        let binary = self.service.binary();
+        let exe = std::env::current_exe()?;
+        let service_exe = exe
+            .parent()
+            .ok_or_else(|| crate::Error::Service("Cannot find service binary".into()))?
+            .join(binary);
+
+        if !service_exe.exists() {
+            return Err(crate::Error::Service(format!(
+                "{} binary not found. Please install it first.",
+                binary
+            )));
+        }
+        Ok(service_exe)
+    }
+
     /// Stop the service
     pub fn stop(&self) -> Result<()> {
         let pid = self.read_pid()?;
@@ -170,17 +302,21 @@ impl ServiceManager {
         // Cleanup files
         std::fs::remove_file(self.pid_file()).ok();
         std::fs::remove_file(self.port_file()).ok();
+        std::fs::remove_file(self.socket_file()).ok();
 
         Ok(())
     }
 
     /// Restart the service
-    pub fn restart(&self, workdir: Option<&PathBuf>) -> Result<()> {
+    ///
+    /// Keeps listening on the same Unix socket if the running service used one.
+    pub fn restart(&self, workdir: Option<&PathBuf>, socket: Option<&Path>) -> Result<()> {
+        let previous = self.read_socket().ok();
         if self.is_running() {
             self.stop()?;
             std::thread::sleep(std::time::Duration::from_millis(200));
         }
-        self.start(false, workdir)
+        self.start(false, workdir, socket.or(previous.as_deref()), None)
     }
 
     /// Get the service status
@@ -188,7 +324,8 @@ impl ServiceManager {
         if let Ok(pid) = self.read_pid() {
             if process_exists(pid) {
                 let port = self.read_port().ok();
-                ServiceStatus::Running { pid, port }
+                let socket = self.read_socket().ok();
+                ServiceStatus::Running { pid, port, socket }
             } else {
                 ServiceStatus::Dead
             }
@@ -197,23 +334,142 @@ impl ServiceManager {
         }
     }
 
-    /// Write PID file (called by trx-api on startup)
+    /// Write PID file (called by the service on startup)
     pub fn write_pid(&self, pid: u32) -> Result<()> {
         std::fs::write(self.pid_file(), pid.to_string())?;
         Ok(())
     }
 
-    /// Write port file (called by trx-api on startup)
+    /// Write port file (called by the service on startup)
     pub fn write_port(&self, port: u16) -> Result<()> {
         std::fs::write(self.port_file(), port.to_string())?;
         Ok(())
     }
 
-    /// Cleanup PID and port files (called by trx-api on shutdown)
+    /// Write socket file (called by the service on startup)
+    pub fn write_socket(&self, socket: &Path) -> Result<()> {
+        std::fs::write(self.socket_file(), socket.to_string_lossy().as_bytes())?;
+        Ok(())
+    }
+
+    /// Register a repository with the running service
+    ///
+    /// `name` defaults to the root's directory name. Returns the name the
+    /// service mounted the repository under.
+    pub fn register_repo(&self, root: &Path, name: Option<&str>) -> Result<String> {
+        if self.service != Service::Api {
+            return Err(crate::Error::Service(format!(
+                "{} serves a single repository",
+                self.service.binary()
+            )));
+        }
+        let body = serde_json::json!({ "path": root, "name": name }).to_string();
+        let (status, response) = match self.read_socket() {
+            Ok(socket) => post_unix(&socket, "/repos", &body)?,
+            Err(_) => {
+                let port = self.read_port().unwrap_or(DEFAULT_PORT);
+                post_tcp(port, "/repos", &body)?
+            }
+        };
+
+        let value: serde_json::Value = serde_json::from_str(&response).map_err(|_| {
+            crate::Error::Service(format!(
+                "Unexpected response from trx-api (HTTP {})",
+                status
+            ))
+        })?;
+        if !(200..300).contains(&status) {
+            return Err(crate::Error::Service(
+                value["error"]
+                    .as_str()
+                    .unwrap_or("Failed to register repository")
+                    .to_string(),
+            ));
+        }
+        value["data"]["name"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| crate::Error::Service("Unexpected response from trx-api".into()))
+    }
+
+    /// Cleanup PID, port and socket files (called by the service on shutdown)
+    ///
+    /// Files belonging to another instance are left alone.
     pub fn cleanup(&self) {
+        if self.read_pid().is_ok_and(|pid| pid != std::process::id()) {
+            return;
+        }
         std::fs::remove_file(self.pid_file()).ok();
         std::fs::remove_file(self.port_file()).ok();
+        std::fs::remove_file(self.socket_file()).ok();
     }
+
+    /// Read the last `lines` lines of the service log
+    pub fn log_tail(&self, lines: usize) -> Result<Vec<String>> {
+        let content = match std::fs::read_to_string(self.log_file()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let all: Vec<&str> = content.lines().collect();
+        let start = all.len().saturating_sub(lines);
+        Ok(all[start..].iter().map(|l| l.to_string()).collect())
+    }
+}
+
+/// POST a JSON body to trx-api over TCP, returning status and body
+fn post_tcp(port: u16, path: &str, body: &str) -> Result<(u16, String)> {
+    let stream = std::net::TcpStream::connect(("127.0.0.1", port)).map_err(|e| {
+        crate::Error::Service(format!("Failed to reach trx-api on port {}: {}", port, e))
+    })?;
+    post(stream, path, body)
+}
+
+/// POST a JSON body to trx-api over a Unix socket, returning status and body
+#[cfg(unix)]
+fn post_unix(socket: &Path, path: &str, body: &str) -> Result<(u16, String)> {
+    let stream = std::os::unix::net::UnixStream::connect(socket).map_err(|e| {
+        crate::Error::Service(format!(
+            "Failed to reach trx-api at {}: {}",
+            socket.display(),
+            e
+        ))
+    })?;
+    post(stream, path, body)
+}
+
+/// Send a one-shot HTTP/1.1 POST over a connected stream
+///
+/// trx-api answers with a `Content-Length` body and closes the connection,
+/// so reading to the end yields the whole response.
+fn post(mut stream: impl Read + Write, path: &str, body: &str) -> Result<(u16, String)> {
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        body.len(),
+        body
+    )?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| crate::Error::Service("Malformed response from trx-api".into()))?;
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| crate::Error::Service("Malformed response from trx-api".into()))?;
+    Ok((status, body.to_string()))
+}
+
+#[cfg(not(unix))]
+fn post_unix(_socket: &Path, _path: &str, _body: &str) -> Result<(u16, String)> {
+    Err(crate::Error::Service(
+        "Unix sockets are not supported on this platform".into(),
+    ))
 }
 
 fn process_exists(pid: u32) -> bool {
@@ -232,3 +488,43 @@ fn get_state_dir() -> Result<PathBuf> {
 
     Ok(base.join("trx"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_tail_and_cleanup() {
+        let state_dir = std::env::temp_dir().join(format!("trx-svc-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&state_dir).unwrap();
+        let manager = ServiceManager {
+            state_dir: state_dir.clone(),
+            service: Service::Api,
+        };
+
+        assert!(manager.log_tail(10).unwrap().is_empty());
+        std::fs::write(manager.log_file(), "one\ntwo\nthree\n").unwrap();
+        assert_eq!(manager.log_tail(2).unwrap(), ["two", "three"]);
+        assert_eq!(manager.log_tail(10).unwrap().len(), 3);
+
+        // Another instance's files survive cleanup; our own are removed
+        manager.write_pid(u32::MAX).unwrap();
+        manager.cleanup();
+        assert!(manager.pid_file().exists());
+        manager.write_pid(std::process::id()).unwrap();
+        manager.write_port(DEFAULT_PORT).unwrap();
+        manager.cleanup();
+        assert!(!manager.pid_file().exists());
+        assert!(!manager.port_file().exists());
+
+        // Each service keeps its own state files
+        let mcp = ServiceManager {
+            state_dir: state_dir.clone(),
+            service: Service::Mcp,
+        };
+        assert_eq!(mcp.pid_file(), state_dir.join("trx-mcp.pid"));
+        assert!(mcp.register_repo(&state_dir, None).is_err());
+
+        std::fs::remove_dir_all(state_dir).ok();
+    }
+}
@@ -2,11 +2,12 @@
 //!
 //! No SQLite, no daemon - just files.
 
+use crate::batch::{self, BatchOp, BatchOutcome};
 use crate::{Error, Issue, Result};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 const TRX_DIR: &str = ".trx";
 const ISSUES_FILE: &str = "issues.jsonl";
@@ -21,9 +22,16 @@ pub struct Store {
 impl Store {
     /// Find and open the store for the current directory
     pub fn open() -> Result<Self> {
-        let root = Self::find_root()?;
+        Self::open_at(&Self::find_root()?)
+    }
+
+    /// Open the store in a specific repository root
+    pub fn open_at(root: &Path) -> Result<Self> {
+        if !root.join(TRX_DIR).exists() {
+            return Err(Error::NotInitialized);
+        }
         let mut store = Self {
-            root,
+            root: root.to_path_buf(),
             issues: HashMap::new(),
         };
         store.load()?;
@@ -32,7 +40,12 @@ impl Store {
 
     /// Initialize a new store in the current directory
     pub fn init(prefix: &str) -> Result<Self> {
-        let root = std::env::current_dir()?;
+        Self::init_at(&std::env::current_dir()?, prefix)
+    }
+
+    /// Initialize a new store in a specific repository root
+    pub fn init_at(root: &Path, prefix: &str) -> Result<Self> {
+        let root = root.to_path_buf();
         let trx_dir = root.join(TRX_DIR);
 
         if trx_dir.exists() {
@@ -105,9 +118,13 @@ prefix = "{}"
     }
 
     /// Save all issues to JSONL
+    ///
+    /// Writes to a temporary file and renames it into place, so readers never
+    /// see a partially written file.
     pub fn save(&self) -> Result<()> {
         let path = self.issues_path();
-        let file = File::create(&path)?;
+        let tmp_path = path.with_extension("jsonl.tmp");
+        let file = File::create(&tmp_path)?;
         let mut writer = BufWriter::new(file);
 
         for issue in self.issues.values() {
@@ -116,6 +133,8 @@ prefix = "{}"
         }
 
         writer.flush()?;
+        writer.get_ref().sync_all()?;
+        fs::rename(&tmp_path, &path)?;
         Ok(())
     }
 
@@ -147,6 +166,17 @@ prefix = "{}"
         self.save()
     }
 
+    /// Update several existing issues with a single save
+    pub fn update_many(&mut self, issues: Vec<Issue>) -> Result<()> {
+        if let Some(missing) = issues.iter().find(|i| !self.issues.contains_key(&i.id)) {
+            return Err(Error::NotFound(missing.id.clone()));
+        }
+        for issue in issues {
+            self.issues.insert(issue.id.clone(), issue);
+        }
+        self.save()
+    }
+
     /// Delete an issue (tombstone)
     pub fn delete(&mut self, id: &str, by: Option<String>, reason: Option<String>) -> Result<()> {
         let issue = self
@@ -157,6 +187,21 @@ prefix = "{}"
         self.save()
     }
 
+    /// Validate and apply a batch of operations with a single save
+    ///
+    /// If any operation fails, nothing is written and the outcome reports
+    /// which operations were rejected.
+    pub fn apply_batch(&mut self, ops: &[BatchOp]) -> Result<BatchOutcome> {
+        let staged = batch::stage(&self.issues, &self.prefix()?, ops);
+        if staged.outcome.applied {
+            for issue in staged.changed {
+                self.issues.insert(issue.id.clone(), issue);
+            }
+            self.save()?;
+        }
+        Ok(staged.outcome)
+    }
+
     /// List all issues (excluding tombstones by default)
     pub fn list(&self, include_tombstones: bool) -> Vec<&Issue> {
         self.issues
@@ -0,0 +1,107 @@
+//! Git sync for the .trx directory
+//!
+//! Shared by `trx sync` and the servers' auto-sync after mutations.
+
+use crate::{Error, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Fill in a sync message template
+///
+/// Supported variables: `{action}`, `{id}`, `{title}`.
+pub fn render_message(template: &str, action: &str, id: &str, title: &str) -> String {
+    template
+        .replace("{action}", action)
+        .replace("{id}", id)
+        .replace("{title}", title)
+}
+
+/// Stage and commit the .trx directory
+///
+/// Only `.trx/` is committed: anything else the user has staged stays in
+/// the index. Returns `false` if there was nothing to commit.
+pub fn commit(trx_dir: &Path, message: &str) -> Result<bool> {
+    let repo = trx_dir.parent().unwrap_or(trx_dir);
+    let pathspec = trx_dir.to_string_lossy();
+
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args(["add", "--", &pathspec])
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::Other(format!(
+            "git add failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let status = Command::new("git")
+        .current_dir(repo)
+        .args(["diff", "--cached", "--quiet", "--", &pathspec])
+        .status()?;
+    if status.success() {
+        return Ok(false);
+    }
+
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args(["commit", "-m", message, "--", &pathspec])
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::Other(format!(
+            "git commit failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_message() {
+        assert_eq!(
+            render_message(
+                "trx: {action} {id} ({title})",
+                "close",
+                "trx-ab12",
+                "Fix it"
+            ),
+            "trx: close trx-ab12 (Fix it)"
+        );
+    }
+
+    #[test]
+    fn test_commit_leaves_other_staged_files() {
+        let repo = std::env::temp_dir().join(format!("trx-sync-{}", uuid::Uuid::new_v4()));
+        let trx_dir = repo.join(".trx");
+        std::fs::create_dir_all(&trx_dir).unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .current_dir(&repo)
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "trx"]);
+        git(&["config", "user.email", "trx@example.com"]);
+        std::fs::write(trx_dir.join("issues.jsonl"), "{}\n").unwrap();
+        std::fs::write(repo.join("notes.txt"), "draft\n").unwrap();
+        git(&["add", "notes.txt"]);
+        assert!(commit(&trx_dir, "trx: sync").unwrap());
+        assert_eq!(
+            git(&["show", "--name-only", "--format="]),
+            ".trx/issues.jsonl\n"
+        );
+        assert_eq!(git(&["diff", "--cached", "--name-only"]), "notes.txt\n");
+
+        assert!(!commit(&trx_dir, "trx: sync").unwrap());
+        std::fs::remove_dir_all(&repo).unwrap();
+    }
+}
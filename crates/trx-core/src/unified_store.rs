@@ -2,11 +2,21 @@
 //!
 //! Provides a common interface for both JSONL (v1) and CRDT (v2) storage.
 
-use crate::{Config, CrdtStore, Error, Issue, Result, StorageVersion, Store};
-use std::path::PathBuf;
+use crate::{
+    BatchOp, BatchOutcome, Config, CrdtStore, Error, Issue, Result, StorageVersion, Store,
+};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
 
 const TRX_DIR: &str = ".trx";
 const CONFIG_FILE: &str = "config.toml";
+const LOCK_FILE: &str = ".lock";
+
+/// Exclusive lock on a store, released when dropped
+#[derive(Debug)]
+pub struct StoreLock {
+    _file: File,
+}
 
 /// Unified store that wraps both JSONL and CRDT backends
 pub enum UnifiedStore {
@@ -17,26 +27,25 @@ pub enum UnifiedStore {
 impl UnifiedStore {
     /// Open the store, auto-detecting the storage version
     pub fn open() -> Result<Self> {
-        let root = Self::find_root()?;
-        let config_path = root.join(TRX_DIR).join(CONFIG_FILE);
-        let config = Config::load(&config_path)?;
+        Self::open_at(&Self::find_root()?)
+    }
 
-        match config.storage_version {
-            StorageVersion::V1 => Ok(UnifiedStore::V1(Store::open()?)),
-            StorageVersion::V2 => Ok(UnifiedStore::V2(CrdtStore::open()?)),
+    /// Open the store in a specific repository root
+    pub fn open_at(root: &Path) -> Result<Self> {
+        let trx_dir = root.join(TRX_DIR);
+        if !trx_dir.exists() {
+            return Err(Error::NotInitialized);
         }
-    }
+        let config = Config::load(&trx_dir.join(CONFIG_FILE))?;
 
-    /// Initialize a new store with the specified version
-    pub fn init(prefix: &str, version: StorageVersion) -> Result<Self> {
-        match version {
-            StorageVersion::V1 => Ok(UnifiedStore::V1(Store::init(prefix)?)),
-            StorageVersion::V2 => Ok(UnifiedStore::V2(CrdtStore::init(prefix)?)),
+        match config.storage_version {
+            StorageVersion::V1 => Ok(UnifiedStore::V1(Store::open_at(root)?)),
+            StorageVersion::V2 => Ok(UnifiedStore::V2(CrdtStore::open_at(root)?)),
         }
     }
 
-    /// Find the repository root
-    fn find_root() -> Result<PathBuf> {
+    /// Find the root of the repository containing the current directory
+    pub fn find_root() -> Result<PathBuf> {
         let mut current = std::env::current_dir()?;
         loop {
             if current.join(TRX_DIR).exists() {
@@ -48,6 +57,69 @@ impl UnifiedStore {
         }
     }
 
+    /// Take the exclusive lock on the store in `root`, waiting until other
+    /// processes release it
+    ///
+    /// The lock is advisory: it serializes read-check-write sequences such
+    /// as claims between processes that take it, which in-process locks
+    /// cannot do when each agent runs its own server. The lock file is
+    /// listed in `.trx/.gitignore` so it is never committed.
+    pub fn lock(root: &Path) -> Result<StoreLock> {
+        let trx_dir = root.join(TRX_DIR);
+        if !trx_dir.exists() {
+            return Err(Error::NotInitialized);
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(trx_dir.join(LOCK_FILE))?;
+        file.lock()?;
+
+        // Checked under the lock so concurrent first locks add one entry
+        let gitignore = trx_dir.join(".gitignore");
+        let ignored = std::fs::read_to_string(&gitignore).unwrap_or_default();
+        if !ignored.lines().any(|line| line.trim() == LOCK_FILE) {
+            let mut ignore_file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&gitignore)?;
+            let separator = if ignored.is_empty() || ignored.ends_with('\n') {
+                ""
+            } else {
+                "\n"
+            };
+            std::io::Write::write_all(
+                &mut ignore_file,
+                format!("{}{}\n", separator, LOCK_FILE).as_bytes(),
+            )?;
+        }
+        Ok(StoreLock { _file: file })
+    }
+
+    /// Initialize a new store with the specified version
+    pub fn init(prefix: &str, version: StorageVersion) -> Result<Self> {
+        Self::init_at(&std::env::current_dir()?, prefix, version)
+    }
+
+    /// Initialize a new store with the specified version in a specific
+    /// repository root
+    pub fn init_at(root: &Path, prefix: &str, version: StorageVersion) -> Result<Self> {
+        match version {
+            StorageVersion::V1 => {
+                let store = Store::init_at(root, prefix)?;
+                // Without an explicit version the config would open as V2
+                let config_path = root.join(TRX_DIR).join(CONFIG_FILE);
+                let mut config = Config::load(&config_path)?;
+                config.storage_version = StorageVersion::V1;
+                config.save(&config_path)?;
+                Ok(UnifiedStore::V1(store))
+            }
+            StorageVersion::V2 => Ok(UnifiedStore::V2(CrdtStore::init_at(root, prefix)?)),
+        }
+    }
+
     /// Get the storage version
     pub fn version(&self) -> StorageVersion {
         match self {
@@ -96,6 +168,14 @@ impl UnifiedStore {
         }
     }
 
+    /// Update several existing issues with a single save
+    pub fn update_many(&mut self, issues: Vec<Issue>) -> Result<()> {
+        match self {
+            UnifiedStore::V1(s) => s.update_many(issues),
+            UnifiedStore::V2(s) => s.update_many(issues),
+        }
+    }
+
     /// Delete an issue (tombstone)
     pub fn delete(&mut self, id: &str, by: Option<String>, reason: Option<String>) -> Result<()> {
         match self {
@@ -104,6 +184,14 @@ impl UnifiedStore {
         }
     }
 
+    /// Validate and apply a batch of operations atomically
+    pub fn apply_batch(&mut self, ops: &[BatchOp]) -> Result<BatchOutcome> {
+        match self {
+            UnifiedStore::V1(s) => s.apply_batch(ops),
+            UnifiedStore::V2(s) => s.apply_batch(ops),
+        }
+    }
+
     /// List all issues
     pub fn list(&self, include_tombstones: bool) -> Vec<&Issue> {
         match self {
@@ -144,6 +232,11 @@ impl UnifiedStore {
         }
     }
 
+    /// Render the ISSUES.md summary for the current state
+    pub fn issues_md(&self) -> String {
+        crate::markdown::issues_md(&self.list(false))
+    }
+
     /// Regenerate ISSUES.md (v2 only, no-op for v1)
     pub fn regenerate_issues_md(&self) -> Result<()> {
         match self {
@@ -0,0 +1,226 @@
+//! Outgoing webhooks for issue events
+//!
+//! Webhooks are configured as `[[webhooks]]` in config.toml. This module
+//! holds the configuration, the JSON payload and the delivery log; sending,
+//! signing and retries live in trx-api so the CLI and TUI do not link an
+//! HTTP client.
+
+use crate::{Error, Filter, Issue, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Issue event that can trigger a webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEvent {
+    Created,
+    Updated,
+    Closed,
+    Deleted,
+}
+
+impl std::fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookEvent::Created => write!(f, "created"),
+            WebhookEvent::Updated => write!(f, "updated"),
+            WebhookEvent::Closed => write!(f, "closed"),
+            WebhookEvent::Deleted => write!(f, "deleted"),
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEvent {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "created" => Ok(WebhookEvent::Created),
+            "updated" => Ok(WebhookEvent::Updated),
+            "closed" => Ok(WebhookEvent::Closed),
+            "deleted" => Ok(WebhookEvent::Deleted),
+            _ => Err(Error::Other(format!("Invalid webhook event: {}", s))),
+        }
+    }
+}
+
+/// A configured webhook endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Name used in logs and `trx webhooks test`
+    pub name: String,
+
+    /// URL to POST payloads to
+    pub url: String,
+
+    /// Events to deliver (all events if empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<WebhookEvent>,
+
+    /// Only deliver events for issues matching this filter
+    /// (e.g. "type:bug priority:<=1")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+
+    /// Secret for the HMAC-SHA256 signature header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl WebhookConfig {
+    /// Whether this webhook wants an event for the given issue
+    pub fn wants(&self, event: WebhookEvent, issue: &Issue) -> Result<bool> {
+        if !self.events.is_empty() && !self.events.contains(&event) {
+            return Ok(false);
+        }
+        match &self.filter {
+            Some(filter) => Ok(filter.parse::<Filter>()?.matches(issue)),
+            None => Ok(true),
+        }
+    }
+}
+
+/// JSON body sent to webhook endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct Payload {
+    pub delivery_id: String,
+    pub event: WebhookEvent,
+    pub timestamp: DateTime<Utc>,
+    pub issue: Issue,
+}
+
+impl Payload {
+    pub fn new(event: WebhookEvent, issue: Issue) -> Self {
+        Self {
+            delivery_id: uuid::Uuid::new_v4().to_string(),
+            event,
+            timestamp: Utc::now(),
+            issue,
+        }
+    }
+}
+
+/// Record of one delivery, as written to the delivery log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryRecord {
+    pub delivery_id: String,
+    pub webhook: String,
+    pub url: String,
+    pub event: WebhookEvent,
+    pub issue_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub attempts: u32,
+    pub success: bool,
+    /// HTTP status of the last attempt, if a response was received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Append-only JSONL log of webhook deliveries
+///
+/// Deliveries finish on different threads, so appends are serialized and
+/// each record is written with a single call to keep lines whole.
+pub struct DeliveryLog {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl DeliveryLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Path to the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a delivery record
+    pub fn append(&self, record: &DeliveryRecord) -> Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wants() {
+        let hook: WebhookConfig = toml::from_str(
+            r#"
+            name = "bugs"
+            url = "http://localhost/hook"
+            events = ["created", "closed"]
+            filter = "type:bug"
+            "#,
+        )
+        .unwrap();
+
+        let mut issue = Issue::new("trx-a".into(), "A".into());
+        assert!(!hook.wants(WebhookEvent::Created, &issue).unwrap());
+        issue.issue_type = crate::IssueType::Bug;
+        assert!(hook.wants(WebhookEvent::Created, &issue).unwrap());
+        assert!(!hook.wants(WebhookEvent::Updated, &issue).unwrap());
+    }
+
+    #[test]
+    fn test_concurrent_appends_keep_lines_whole() {
+        let dir = std::env::temp_dir().join(format!("trx-hooks-{}", uuid::Uuid::new_v4()));
+        let log = std::sync::Arc::new(DeliveryLog::new(dir.join("webhooks.jsonl")));
+        let record = DeliveryRecord {
+            delivery_id: "d".repeat(4000),
+            webhook: "hook".into(),
+            url: "http://localhost/hook".into(),
+            event: WebhookEvent::Created,
+            issue_id: "trx-a".into(),
+            timestamp: Utc::now(),
+            attempts: 1,
+            success: true,
+            status: Some(200),
+            error: None,
+        };
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let (log, record) = (log.clone(), record.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        log.append(&record).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let content = std::fs::read_to_string(log.path()).unwrap();
+        assert_eq!(content.lines().count(), 160);
+        for line in content.lines() {
+            serde_json::from_str::<DeliveryRecord>(line).unwrap();
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
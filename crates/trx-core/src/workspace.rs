@@ -0,0 +1,154 @@
+//! Multi-repository workspaces
+//!
+//! A workspace file (beads-compatible `workspace.yaml`) lists repository
+//! roots, either as plain paths or with an explicit name:
+//!
+//! ```yaml
+//! repos:
+//!   - ../api
+//!   - path: ../web
+//!     name: frontend
+//! ```
+//!
+//! Relative paths are resolved against the directory holding the file.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A repository entry in a workspace file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RepoEntry {
+    Path(PathBuf),
+    Named {
+        path: PathBuf,
+        #[serde(default)]
+        name: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WorkspaceFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default)]
+    repos: Vec<RepoEntry>,
+}
+
+/// A repository in a workspace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkspaceRepo {
+    pub name: String,
+    pub root: PathBuf,
+}
+
+impl WorkspaceRepo {
+    /// Create a repo entry named after its directory
+    pub fn from_root(root: PathBuf) -> Self {
+        let name = repo_name(&root);
+        Self { name, root }
+    }
+}
+
+/// A set of repositories served or viewed together
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Workspace {
+    pub name: Option<String>,
+    pub repos: Vec<WorkspaceRepo>,
+}
+
+impl Workspace {
+    /// Load a workspace file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let file: WorkspaceFile = serde_yaml::from_str(&content)
+            .map_err(|e| Error::Other(format!("Invalid workspace file: {}", e)))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+
+        let mut workspace = Self {
+            name: file.name,
+            repos: Vec::new(),
+        };
+        for entry in file.repos {
+            let (path, name) = match entry {
+                RepoEntry::Path(path) => (path, None),
+                RepoEntry::Named { path, name } => (path, name),
+            };
+            let root = base.join(path);
+            let root = root.canonicalize().unwrap_or(root);
+            let name = name.unwrap_or_else(|| repo_name(&root));
+            workspace.add(WorkspaceRepo { name, root })?;
+        }
+        Ok(workspace)
+    }
+
+    /// Write the workspace file, recording every repository by absolute
+    /// path and name
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = WorkspaceFile {
+            name: self.name.clone(),
+            repos: self
+                .repos
+                .iter()
+                .map(|r| RepoEntry::Named {
+                    path: r.root.clone(),
+                    name: Some(r.name.clone()),
+                })
+                .collect(),
+        };
+        let content = serde_yaml::to_string(&file)
+            .map_err(|e| Error::Other(format!("Failed to write workspace file: {}", e)))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Add a repository, rejecting duplicate names
+    pub fn add(&mut self, repo: WorkspaceRepo) -> Result<()> {
+        if self.repos.iter().any(|r| r.name == repo.name) {
+            return Err(Error::Other(format!(
+                "Duplicate repository name in workspace: {}",
+                repo.name
+            )));
+        }
+        self.repos.push(repo);
+        Ok(())
+    }
+}
+
+/// Default repository name: the last component of its root path
+pub fn repo_name(root: &Path) -> String {
+    root.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_workspace() {
+        let dir = std::env::temp_dir().join(format!("trx-ws-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("api")).unwrap();
+        let path = dir.join("workspace.yaml");
+        std::fs::write(
+            &path,
+            "repos:\n  - api\n  - path: web\n    name: frontend\n",
+        )
+        .unwrap();
+
+        let workspace = Workspace::load(&path).unwrap();
+        let names: Vec<_> = workspace.repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["api", "frontend"]);
+        assert!(workspace.repos[1].root.ends_with("web"));
+
+        workspace.save(&path).unwrap();
+        assert_eq!(Workspace::load(&path).unwrap().repos, workspace.repos);
+
+        std::fs::write(&path, "repos:\n  - api\n  - path: other/api\n").unwrap();
+        assert!(Workspace::load(&path).is_err());
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,484 @@
+//! HTTP transports
+//!
+//! Serves the router over MCP's streamable HTTP transport at `/mcp` and the
+//! older HTTP+SSE transport at `/sse` + `/messages`, so one long-running
+//! server can back several agent sessions. All sessions share the router's
+//! store and its file-watch reloads.
+//!
+//! Streamable HTTP: `POST /mcp` carries requests and returns their responses
+//! as JSON. The `initialize` response includes an `Mcp-Session-Id` header
+//! that later requests must send back. `GET /mcp` opens the session's event
+//! stream for resource notifications, and `DELETE /mcp` ends the session, as
+//! does closing that stream. Sessions that never open it expire after
+//! [`SESSION_IDLE_TIMEOUT`] without requests.
+//!
+//! HTTP+SSE: `GET /sse` opens a session whose first event names the URL to
+//! POST messages to; responses arrive on the stream.
+//!
+//! Each session claims issues as its own agent unless a call names one.
+
+use crate::TrxMcpRouter;
+use crate::server::{self, Session, Sessions};
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::{Query, Request, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use futures_util::stream::{self, Stream, StreamExt};
+use mcp_spec::protocol::JsonRpcMessage;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::Receiver;
+
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// How long a streamable HTTP session without an open event stream may go
+/// without requests before it is dropped
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How often idle sessions are looked for
+const SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+struct HttpState {
+    router: TrxMcpRouter,
+    sessions: Sessions,
+    /// Event streams of streamable HTTP sessions not yet picked up by a GET
+    streams: Arc<Mutex<HashMap<String, Receiver<JsonRpcMessage>>>>,
+    token: Option<Arc<str>>,
+}
+
+impl HttpState {
+    fn session(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<(String, Arc<Session>), (StatusCode, &'static str)> {
+        let id = headers
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .ok_or((StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header"))?;
+        let session = self.sessions.lock().unwrap().get(id).cloned();
+        let session = session.ok_or((StatusCode::NOT_FOUND, "Unknown session"))?;
+        session.touch();
+        Ok((id.to_string(), session))
+    }
+
+    fn open_session(&self) -> (String, Arc<Session>, Receiver<JsonRpcMessage>) {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (session, rx) = Session::new(crate::default_agent(format!("mcp-{}", id)));
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(id.clone(), session.clone());
+        (id, session, rx)
+    }
+
+    fn close_session(&self, id: &str) -> bool {
+        self.streams.lock().unwrap().remove(id);
+        self.sessions.lock().unwrap().remove(id).is_some()
+    }
+
+    /// Close streamable HTTP sessions idle for longer than `max_idle` whose
+    /// event stream was never opened
+    ///
+    /// Sessions with an open stream are closed when it drops instead.
+    fn expire_idle(&self, max_idle: Duration) {
+        let waiting: Vec<String> = self.streams.lock().unwrap().keys().cloned().collect();
+        let expired: Vec<String> = {
+            let sessions = self.sessions.lock().unwrap();
+            waiting
+                .into_iter()
+                .filter(|id| sessions.get(id).is_none_or(|s| s.idle() > max_idle))
+                .collect()
+        };
+        for id in expired {
+            tracing::debug!("Expiring idle session {}", id);
+            self.close_session(&id);
+        }
+    }
+}
+
+/// Drop idle sessions periodically
+async fn sweep_sessions(state: HttpState) {
+    let mut interval = tokio::time::interval(SESSION_SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        state.expire_idle(SESSION_IDLE_TIMEOUT);
+    }
+}
+
+/// Removes a session when its event stream is dropped
+struct SessionGuard {
+    state: HttpState,
+    id: String,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        self.state.close_session(&self.id);
+    }
+}
+
+/// Build the HTTP app
+///
+/// With a `token`, every request must carry `Authorization: Bearer <token>`.
+pub fn app(router: TrxMcpRouter, token: Option<String>) -> Router {
+    let state = HttpState {
+        router: router.clone(),
+        sessions: Arc::default(),
+        streams: Arc::default(),
+        token: token.map(Into::into),
+    };
+    tokio::spawn(server::watch(router, state.sessions.clone()));
+    tokio::spawn(sweep_sessions(state.clone()));
+
+    Router::new()
+        .route(
+            "/mcp",
+            post(streamable_post)
+                .get(streamable_get)
+                .delete(streamable_delete),
+        )
+        .route("/sse", get(sse_open))
+        .route("/messages", post(sse_message))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            require_token,
+        ))
+        .with_state(state)
+}
+
+/// Compare without short-circuiting on the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn require_token(State(state): State<HttpState>, request: Request, next: Next) -> Response {
+    if let Some(expected) = &state.token {
+        let authorized = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|given| tokens_match(given.trim(), expected));
+        if !authorized {
+            return (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                "Missing or invalid bearer token",
+            )
+                .into_response();
+        }
+    }
+    next.run(request).await
+}
+
+fn json_response(status: StatusCode, body: &impl serde::Serialize) -> Response {
+    match serde_json::to_vec(body) {
+        Ok(body) => (status, [(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Parse a body holding one JSON-RPC message or a batch of them
+fn parse_messages(body: &[u8]) -> Result<(Vec<JsonRpcMessage>, bool), String> {
+    let value: Value = serde_json::from_slice(body).map_err(|e| e.to_string())?;
+    let batch = value.is_array();
+    let values = match value {
+        Value::Array(values) => values,
+        value => vec![value],
+    };
+    let messages = values
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<Vec<JsonRpcMessage>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok((messages, batch))
+}
+
+async fn streamable_post(
+    State(state): State<HttpState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let (messages, batch) = match parse_messages(&body) {
+        Ok(parsed) => parsed,
+        Err(e) => return json_response(StatusCode::BAD_REQUEST, &server::parse_error(e)),
+    };
+
+    let initialize = messages
+        .iter()
+        .any(|m| matches!(m, JsonRpcMessage::Request(r) if r.method == "initialize"));
+    let (id, session) = if initialize {
+        let (id, session, rx) = state.open_session();
+        state.streams.lock().unwrap().insert(id.clone(), rx);
+        (id, session)
+    } else {
+        match state.session(&headers) {
+            Ok(found) => found,
+            Err(error) => return error.into_response(),
+        }
+    };
+
+    let mut responses = Vec::new();
+    for message in messages {
+        if let JsonRpcMessage::Request(request) = message {
+            let response = server::handle(&state.router, &session, request).await;
+            responses.push(JsonRpcMessage::Response(response));
+        }
+    }
+
+    let mut response = if responses.is_empty() {
+        StatusCode::ACCEPTED.into_response()
+    } else if batch {
+        json_response(StatusCode::OK, &responses)
+    } else {
+        json_response(StatusCode::OK, &responses[0])
+    };
+    if initialize && let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(SESSION_HEADER, value);
+    }
+    response
+}
+
+/// Turn a session's messages into SSE `message` events, closing the
+/// session when the stream is dropped
+fn events(
+    rx: Receiver<JsonRpcMessage>,
+    guard: SessionGuard,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold((rx, guard), |(mut rx, guard)| async move {
+        let message = rx.recv().await?;
+        let data = serde_json::to_string(&message).unwrap_or_default();
+        Some((
+            Ok(Event::default().event("message").data(data)),
+            (rx, guard),
+        ))
+    })
+}
+
+/// Open a session's event stream; the session ends when the stream closes
+async fn streamable_get(State(state): State<HttpState>, headers: HeaderMap) -> Response {
+    let (id, _) = match state.session(&headers) {
+        Ok(found) => found,
+        Err(error) => return error.into_response(),
+    };
+    let Some(rx) = state.streams.lock().unwrap().remove(&id) else {
+        return (
+            StatusCode::CONFLICT,
+            "An event stream is already open for this session",
+        )
+            .into_response();
+    };
+    let guard = SessionGuard {
+        state: state.clone(),
+        id,
+    };
+    Sse::new(events(rx, guard))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn streamable_delete(State(state): State<HttpState>, headers: HeaderMap) -> Response {
+    match state.session(&headers) {
+        Ok((id, _)) => {
+            state.close_session(&id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(error) => error.into_response(),
+    }
+}
+
+async fn sse_open(State(state): State<HttpState>) -> Response {
+    let (id, _, rx) = state.open_session();
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("/messages?sessionId={}", id));
+    let guard = SessionGuard {
+        state: state.clone(),
+        id,
+    };
+    let stream = stream::once(async move { Ok(endpoint) }).chain(events(rx, guard));
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+#[derive(Deserialize)]
+struct MessageQuery {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+async fn sse_message(
+    State(state): State<HttpState>,
+    Query(query): Query<MessageQuery>,
+    body: Bytes,
+) -> Response {
+    let Some(session) = state
+        .sessions
+        .lock()
+        .unwrap()
+        .get(&query.session_id)
+        .cloned()
+    else {
+        return (StatusCode::NOT_FOUND, "Unknown session").into_response();
+    };
+    session.touch();
+    let messages = match parse_messages(&body) {
+        Ok((messages, _)) => messages,
+        Err(e) => return json_response(StatusCode::BAD_REQUEST, &server::parse_error(e)),
+    };
+
+    // Responses go out on the session's event stream
+    tokio::spawn(async move {
+        for message in messages {
+            if let JsonRpcMessage::Request(request) = message {
+                let response = server::handle(&state.router, &session, request).await;
+                session.send(JsonRpcMessage::Response(response)).await;
+            }
+        }
+    });
+    StatusCode::ACCEPTED.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trx_core::UnifiedStore;
+
+    fn test_state(root: &std::path::Path) -> HttpState {
+        crate::tests::init_repo(root, 1);
+        let store = UnifiedStore::open_at(root).unwrap();
+        HttpState {
+            router: TrxMcpRouter::new(store, root.to_path_buf()),
+            sessions: Arc::default(),
+            streams: Arc::default(),
+            token: None,
+        }
+    }
+
+    async fn call(state: &HttpState, session: &Session, tool: &str) -> Value {
+        call_with(state, session, tool, serde_json::json!({ "id": "trx-000" })).await
+    }
+
+    async fn call_with(
+        state: &HttpState,
+        session: &Session,
+        tool: &str,
+        arguments: Value,
+    ) -> Value {
+        let request = serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "name": tool, "arguments": arguments }
+        }))
+        .unwrap();
+        let response = server::handle(&state.router, session, request).await;
+        serde_json::to_value(response).unwrap()
+    }
+
+    fn is_error(response: &Value) -> bool {
+        response["error"].is_object() || response["result"]["isError"] == true
+    }
+
+    #[tokio::test]
+    async fn test_sessions_claim_as_distinct_agents() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let (_, first, _first_rx) = state.open_session();
+        let (_, second, _second_rx) = state.open_session();
+
+        let claimed = call(&state, &first, "trx.issues.claim").await;
+        assert!(!is_error(&claimed), "{}", claimed);
+        assert!(is_error(&call(&state, &second, "trx.issues.claim").await));
+        assert!(is_error(&call(&state, &second, "trx.issues.release").await));
+        assert!(!is_error(
+            &call(&state, &first, "trx.issues.heartbeat").await
+        ));
+        assert!(!is_error(&call(&state, &first, "trx.issues.release").await));
+    }
+
+    #[tokio::test]
+    async fn test_sessions_comment_as_their_agents() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+        let (first_id, first, _first_rx) = state.open_session();
+        let (second_id, second, _second_rx) = state.open_session();
+
+        for session in [&first, &second] {
+            let arguments = serde_json::json!({ "id": "trx-000", "text": "Looking" });
+            let commented = call_with(&state, session, "trx.issues.comment", arguments).await;
+            assert!(!is_error(&commented), "{}", commented);
+        }
+
+        let store = UnifiedStore::open_at(dir.path()).unwrap();
+        let authors: Vec<_> = store
+            .get("trx-000")
+            .unwrap()
+            .comments
+            .iter()
+            .map(|c| c.author.clone())
+            .collect();
+        let agent = |id: &str| Some(crate::default_agent(format!("mcp-{}", id)));
+        assert_eq!(authors, [agent(&first_id), agent(&second_id)]);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_close_when_idle_or_stream_drops() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path());
+
+        // Never opened its stream
+        let (idle, _, rx) = state.open_session();
+        state.streams.lock().unwrap().insert(idle.clone(), rx);
+        // Stream open, as after GET /mcp
+        let (streaming, _, rx) = state.open_session();
+        let guard = SessionGuard {
+            state: state.clone(),
+            id: streaming.clone(),
+        };
+        let stream = events(rx, guard);
+
+        state.expire_idle(Duration::ZERO);
+        let live = |id: &str| state.sessions.lock().unwrap().contains_key(id);
+        assert!(!live(&idle));
+        assert!(live(&streaming));
+
+        drop(stream);
+        assert!(!live(&streaming));
+    }
+
+    #[test]
+    fn test_parse_messages_and_tokens() {
+        let (messages, batch) =
+            parse_messages(br#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(!batch);
+
+        let (messages, batch) = parse_messages(
+            br#"[{"jsonrpc":"2.0","id":1,"method":"ping"},{"jsonrpc":"2.0","method":"notifications/initialized"}]"#,
+        )
+        .unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(batch);
+        assert!(parse_messages(b"not json").is_err());
+
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secreT", "secret"));
+        assert!(!tokens_match("secret2", "secret"));
+    }
+}
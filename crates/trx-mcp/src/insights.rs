@@ -0,0 +1,319 @@
+//! Graph analysis for agents
+//!
+//! Backs the `next`, `insights`, `dependency tree` and `critical path`
+//! tools. Output is deliberately compact: IDs, titles and the numbers an
+//! agent needs to decide, without full issue bodies.
+
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet, VecDeque};
+use trx_core::{DependencyType, Issue, IssueGraph};
+
+/// PageRank settings shared by every tool
+const DAMPING: f64 = 0.85;
+const ITERATIONS: usize = 20;
+
+/// Round scores so they don't waste tokens
+fn round(score: f64) -> f64 {
+    (score * 10_000.0).round() / 10_000.0
+}
+
+/// Open issues waiting directly on each issue through `blocks` edges
+fn dependents<'a>(open: &[&'a Issue]) -> HashMap<&'a str, Vec<&'a str>> {
+    let ids: HashSet<&str> = open.iter().map(|i| i.id.as_str()).collect();
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for &issue in open {
+        for dep in &issue.dependencies {
+            if dep.dep_type == DependencyType::Blocks && ids.contains(dep.depends_on_id.as_str()) {
+                dependents
+                    .entry(dep.depends_on_id.as_str())
+                    .or_default()
+                    .push(issue.id.as_str());
+            }
+        }
+    }
+    for list in dependents.values_mut() {
+        list.sort();
+    }
+    dependents
+}
+
+/// Every open issue that finishing `id` would help unblock, nearest first
+fn unblocks<'a>(id: &'a str, dependents: &HashMap<&'a str, Vec<&'a str>>) -> Vec<&'a str> {
+    let mut seen = HashSet::from([id]);
+    let mut queue = VecDeque::from([id]);
+    let mut out = Vec::new();
+    while let Some(node) = queue.pop_front() {
+        for &next in dependents.get(node).into_iter().flatten() {
+            if seen.insert(next) {
+                out.push(next);
+                queue.push_back(next);
+            }
+        }
+    }
+    out
+}
+
+fn summary(issue: &Issue) -> Value {
+    json!({
+        "id": issue.id,
+        "title": issue.title,
+        "priority": issue.priority,
+        "status": issue.status,
+    })
+}
+
+/// The ready issue with the most leverage, plus a few alternatives
+///
+/// Ready issues are ranked by priority, then by how many open issues they
+/// unblock (directly or transitively), then by PageRank.
+pub fn next(open: &[&Issue]) -> Value {
+    let graph = IssueGraph::from_issues(open);
+    let pagerank = graph.pagerank(DAMPING, ITERATIONS);
+    let dependents = dependents(open);
+    let critical: HashSet<String> = graph
+        .critical_path()
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    let mut ranked: Vec<_> = graph
+        .ready_issues(open)
+        .into_iter()
+        .map(|issue| {
+            let unblocks = unblocks(&issue.id, &dependents);
+            let score = pagerank.get(&issue.id).copied().unwrap_or(0.0);
+            (issue, unblocks, score)
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        a.0.priority
+            .cmp(&b.0.priority)
+            .then_with(|| b.1.len().cmp(&a.1.len()))
+            .then_with(|| b.2.total_cmp(&a.2))
+            .then_with(|| a.0.created_at.cmp(&b.0.created_at))
+    });
+
+    let Some((issue, unblocks, score)) = ranked.first() else {
+        return json!({ "issue": null, "reason": "No ready issues" });
+    };
+
+    let mut reasons = vec![format!("highest ready priority (P{})", issue.priority)];
+    if !unblocks.is_empty() {
+        reasons.push(format!("unblocks {} open issue(s)", unblocks.len()));
+    }
+    if critical.contains(&issue.id) && critical.len() > 1 {
+        reasons.push(format!(
+            "starts the critical path ({} issues)",
+            critical.len()
+        ));
+    }
+
+    json!({
+        "issue": {
+            "id": issue.id,
+            "title": issue.title,
+            "priority": issue.priority,
+            "type": issue.issue_type,
+            "status": issue.status,
+        },
+        "reason": reasons.join("; "),
+        "unblocks": unblocks,
+        "pagerank": round(*score),
+        "alternatives": ranked
+            .iter()
+            .skip(1)
+            .take(3)
+            .map(|(i, unblocks, _)| json!({
+                "id": i.id,
+                "title": i.title,
+                "priority": i.priority,
+                "unblocks": unblocks.len(),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Backlog health: cycles, most central issues and bottlenecks
+pub fn insights(open: &[&Issue], top: usize) -> Value {
+    let graph = IssueGraph::from_issues(open);
+    let by_id: HashMap<&str, &Issue> = open.iter().map(|i| (i.id.as_str(), *i)).collect();
+    let title = |id: &str| by_id.get(id).map(|i| i.title.as_str()).unwrap_or("");
+
+    let mut pagerank: Vec<_> = graph.pagerank(DAMPING, ITERATIONS).into_iter().collect();
+    pagerank.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    pagerank.truncate(top);
+
+    let dependents = dependents(open);
+    let mut bottlenecks: Vec<_> = open
+        .iter()
+        .map(|i| {
+            let direct = dependents.get(i.id.as_str()).map_or(0, Vec::len);
+            (i, direct, unblocks(&i.id, &dependents).len())
+        })
+        .filter(|(_, direct, _)| *direct > 0)
+        .collect();
+    bottlenecks.sort_by(|a, b| {
+        b.2.cmp(&a.2)
+            .then_with(|| b.1.cmp(&a.1))
+            .then_with(|| a.0.id.cmp(&b.0.id))
+    });
+    bottlenecks.truncate(top);
+
+    json!({
+        "open": open.len(),
+        "ready": graph.ready_issues(open).len(),
+        "waiting": graph.blocked_issues(open).len(),
+        "cycles": graph.find_cycles(),
+        "critical_path": graph.critical_path(),
+        "pagerank": pagerank
+            .iter()
+            .map(|(id, score)| json!({ "id": id, "title": title(id), "score": round(*score) }))
+            .collect::<Vec<_>>(),
+        "bottlenecks": bottlenecks
+            .iter()
+            .map(|(i, direct, total)| json!({
+                "id": i.id,
+                "title": i.title,
+                "priority": i.priority,
+                "blocks": direct,
+                "unblocks": total,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Dependency tree rooted at an issue
+///
+/// `up` follows dependents (issues waiting on the root) instead of
+/// dependencies. Issues already shown elsewhere in the tree are listed with
+/// `"repeat": true` and not expanded again, so cycles terminate.
+pub fn dependency_tree(issues: &[&Issue], id: &str, up: bool, max_depth: usize) -> Option<Value> {
+    let by_id: HashMap<&str, &Issue> = issues.iter().map(|i| (i.id.as_str(), *i)).collect();
+    let root = by_id.get(id)?;
+
+    // Edges as (neighbour, dependency type) in the chosen direction
+    let mut edges: HashMap<&str, Vec<(&str, DependencyType)>> = HashMap::new();
+    for issue in issues {
+        for dep in &issue.dependencies {
+            let (from, to) = if up {
+                (dep.depends_on_id.as_str(), issue.id.as_str())
+            } else {
+                (issue.id.as_str(), dep.depends_on_id.as_str())
+            };
+            if by_id.contains_key(to) {
+                edges.entry(from).or_default().push((to, dep.dep_type));
+            }
+        }
+    }
+
+    fn build(
+        issue: &Issue,
+        dep_type: Option<DependencyType>,
+        depth: usize,
+        max_depth: usize,
+        by_id: &HashMap<&str, &Issue>,
+        edges: &HashMap<&str, Vec<(&str, DependencyType)>>,
+        seen: &mut HashSet<String>,
+    ) -> Value {
+        let mut node = summary(issue);
+        if let Some(dep_type) = dep_type {
+            node["dep"] = json!(dep_type);
+        }
+        if !seen.insert(issue.id.clone()) {
+            node["repeat"] = json!(true);
+            return node;
+        }
+
+        let next = edges.get(issue.id.as_str()).map_or(&[][..], Vec::as_slice);
+        if next.is_empty() {
+            return node;
+        }
+        if depth >= max_depth {
+            node["truncated"] = json!(next.len());
+            return node;
+        }
+        node["deps"] = next
+            .iter()
+            .map(|(id, dep_type)| {
+                build(
+                    by_id[id],
+                    Some(*dep_type),
+                    depth + 1,
+                    max_depth,
+                    by_id,
+                    edges,
+                    seen,
+                )
+            })
+            .collect();
+        node
+    }
+
+    Some(build(
+        root,
+        None,
+        0,
+        max_depth,
+        &by_id,
+        &edges,
+        &mut HashSet::new(),
+    ))
+}
+
+/// Longest chain of blocking work among open issues, in working order
+pub fn critical_path(open: &[&Issue]) -> Value {
+    let graph = IssueGraph::from_issues(open);
+    let by_id: HashMap<&str, &Issue> = open.iter().map(|i| (i.id.as_str(), *i)).collect();
+    match graph.critical_path() {
+        Some(path) => json!({
+            "length": path.len(),
+            "path": path
+                .iter()
+                .filter_map(|id| by_id.get(id.as_str()).map(|i| summary(i)))
+                .collect::<Vec<_>>(),
+        }),
+        None => json!({
+            "length": null,
+            "error": "Blocking dependencies contain a cycle",
+            "cycles": graph.find_cycles(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_and_tree() {
+        // a blocks b, b blocks c; d is independent with the same priority
+        let a = Issue::new("trx-a".into(), "A".into());
+        let mut b = Issue::new("trx-b".into(), "B".into());
+        let mut c = Issue::new("trx-c".into(), "C".into());
+        let d = Issue::new("trx-d".into(), "D".into());
+        b.add_dependency("trx-a".into(), DependencyType::Blocks);
+        c.add_dependency("trx-b".into(), DependencyType::Blocks);
+        let open: Vec<&Issue> = vec![&d, &c, &b, &a];
+
+        let next = next(&open);
+        assert_eq!(next["issue"]["id"], "trx-a");
+        assert_eq!(next["unblocks"], json!(["trx-b", "trx-c"]));
+        assert_eq!(next["alternatives"][0]["id"], "trx-d");
+
+        let insights = insights(&open, 5);
+        assert_eq!(insights["ready"], 2);
+        assert_eq!(insights["waiting"], 2);
+        assert_eq!(insights["bottlenecks"][0]["id"], "trx-a");
+        assert_eq!(
+            insights["critical_path"],
+            json!(["trx-a", "trx-b", "trx-c"])
+        );
+
+        let tree = dependency_tree(&open, "trx-c", false, 1).unwrap();
+        assert_eq!(tree["deps"][0]["id"], "trx-b");
+        assert_eq!(tree["deps"][0]["truncated"], 1);
+        let tree = dependency_tree(&open, "trx-a", true, 5).unwrap();
+        assert_eq!(tree["deps"][0]["deps"][0]["id"], "trx-c");
+        assert!(dependency_tree(&open, "trx-x", false, 5).is_none());
+    }
+}
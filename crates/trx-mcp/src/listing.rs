@@ -0,0 +1,109 @@
+//! Issue listings for tool output
+//!
+//! The list, ready and search tools share one page shape and one truncation
+//! rule, so an agent sees the same structure whichever tool it calls. Long
+//! free-text fields are cut to `MAX_TEXT_CHARS`; `trx.issues.get` returns an
+//! issue in full.
+
+use serde_json::{Value, json};
+use trx_core::Issue;
+use trx_core::query::{SortSpec, paginate, select_fields};
+
+/// Longest free-text field kept in a listing, in characters
+pub const MAX_TEXT_CHARS: usize = 500;
+
+/// Issue fields holding free text
+const TEXT_FIELDS: &[&str] = &["description", "notes", "close_reason", "delete_reason"];
+
+/// Cut a string to `MAX_TEXT_CHARS`, noting how much was dropped
+fn truncate_text(text: &mut String) {
+    let len = text.chars().count();
+    if len <= MAX_TEXT_CHARS {
+        return;
+    }
+    let end = text
+        .char_indices()
+        .nth(MAX_TEXT_CHARS)
+        .map_or(text.len(), |(i, _)| i);
+    text.truncate(end);
+    text.push_str(&format!("… [truncated, {} chars total]", len));
+}
+
+/// Truncate the free-text fields of a serialized issue, including comments
+pub fn truncate(issue: &mut Value) {
+    let Value::Object(map) = issue else {
+        return;
+    };
+    for field in TEXT_FIELDS {
+        if let Some(Value::String(text)) = map.get_mut(*field) {
+            truncate_text(text);
+        }
+    }
+    if let Some(Value::Array(comments)) = map.get_mut("comments") {
+        for comment in comments {
+            if let Some(Value::String(text)) = comment.get_mut("text") {
+                truncate_text(text);
+            }
+        }
+    }
+}
+
+/// Sort, paginate, project and truncate issues into a listing
+///
+/// `cursor` is the already looked-up cursor issue. The result has `count`
+/// (issues on this page), `total` (across all pages), `next_cursor` and
+/// `issues`.
+pub fn page(
+    issues: Vec<&Issue>,
+    sort: &SortSpec,
+    cursor: Option<&Issue>,
+    limit: Option<usize>,
+    fields: &[String],
+) -> trx_core::Result<Value> {
+    let page = paginate(issues, sort, cursor, limit)?;
+    let items = page
+        .items
+        .iter()
+        .map(|issue| {
+            let mut value = select_fields(issue, fields)?;
+            truncate(&mut value);
+            Ok(value)
+        })
+        .collect::<trx_core::Result<Vec<_>>>()?;
+
+    Ok(json!({
+        "count": items.len(),
+        "total": page.total,
+        "next_cursor": page.next_cursor,
+        "issues": items,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_truncates_and_paginates() {
+        let mut a = Issue::new("trx-a".into(), "A".into());
+        a.description = Some("é".repeat(MAX_TEXT_CHARS + 20));
+        a.add_comment(None, "short".into());
+        let b = Issue::new("trx-b".into(), "B".into());
+        let sort: SortSpec = "id".parse().unwrap();
+
+        let first = page(vec![&b, &a], &sort, None, Some(1), &[]).unwrap();
+        assert_eq!(first["count"], 1);
+        assert_eq!(first["total"], 2);
+        assert_eq!(first["next_cursor"], "trx-a");
+        let description = first["issues"][0]["description"].as_str().unwrap();
+        assert!(description.starts_with(&"é".repeat(MAX_TEXT_CHARS)));
+        assert!(
+            description.ends_with(&format!("[truncated, {} chars total]", MAX_TEXT_CHARS + 20))
+        );
+        assert_eq!(first["issues"][0]["comments"][0]["text"], "short");
+
+        let second = page(vec![&b, &a], &sort, Some(&a), Some(1), &["id".into()]).unwrap();
+        assert_eq!(second["issues"], json!([{ "id": "trx-b" }]));
+        assert_eq!(second["next_cursor"], Value::Null);
+    }
+}
@@ -4,7 +4,6 @@
 //! with the trx issue tracker.
 
 use anyhow::Context;
-use mcp_server::router::RouterService;
 use mcp_spec::ResourceContents;
 use mcp_spec::content::Content;
 use mcp_spec::handler::{PromptError, ResourceError, ToolError};
@@ -16,7 +15,18 @@ use serde::Deserialize;
 use serde_json::{Value, json};
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
-use trx_core::{Issue, IssueType, Status, Store, generate_id};
+use trx_core::query::parse_fields;
+use trx_core::{
+    BatchOp, DEFAULT_MCP_PORT, Filter, Issue, IssueGraph, IssueType, Service, ServiceManager,
+    SortSpec, Status, StoreLock, UnifiedStore, check_priority, generate_id,
+};
+
+mod http;
+mod insights;
+mod listing;
+mod prompts;
+mod resources;
+mod server;
 
 /// MCP router for trx
 #[derive(Clone)]
@@ -25,7 +35,7 @@ struct TrxMcpRouter {
 }
 
 struct TrxMcpInner {
-    store: RwLock<Store>,
+    store: RwLock<UnifiedStore>,
     root_path: PathBuf,
 }
 
@@ -33,6 +43,19 @@ struct TrxMcpInner {
 // Argument types for tools
 // ============================================================================
 
+/// Sorting and pagination shared by the listing tools
+#[derive(Debug, Default, Deserialize)]
+struct PageArgs {
+    #[serde(default)]
+    sort: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    cursor: Option<String>,
+    #[serde(default)]
+    fields: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct ListIssuesArgs {
     #[serde(default)]
@@ -43,6 +66,18 @@ struct ListIssuesArgs {
     priority: Option<u8>,
     #[serde(default)]
     include_tombstones: Option<bool>,
+    #[serde(flatten)]
+    page: PageArgs,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchIssuesArgs {
+    #[serde(default)]
+    query: Option<String>,
+    #[serde(default)]
+    include_tombstones: Option<bool>,
+    #[serde(flatten)]
+    page: PageArgs,
 }
 
 #[derive(Debug, Deserialize)]
@@ -88,6 +123,48 @@ struct UpdateIssueArgs {
     notes: Option<String>,
 }
 
+/// Fields `trx.issues.bulk_update` sets on every issue
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IssuePatch {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    priority: Option<u8>,
+    #[serde(default)]
+    issue_type: Option<String>,
+    #[serde(default)]
+    labels: Option<Vec<String>>,
+    #[serde(default)]
+    assignee: Option<String>,
+    #[serde(default)]
+    notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BulkUpdateArgs {
+    ids: Vec<String>,
+    patch: IssuePatch,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentArgs {
+    id: String,
+    text: String,
+    #[serde(default)]
+    author: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SyncArgs {
+    #[serde(default)]
+    message: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct CloseIssueArgs {
     id: String,
@@ -118,12 +195,87 @@ struct RemoveDependencyArgs {
     depends_on: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct ClaimIssueArgs {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    agent: Option<String>,
+    #[serde(default)]
+    lease_seconds: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeartbeatArgs {
+    id: String,
+    #[serde(default)]
+    agent: Option<String>,
+    #[serde(default)]
+    lease_seconds: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseIssueArgs {
+    id: String,
+    #[serde(default)]
+    agent: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InsightsArgs {
+    #[serde(default)]
+    top: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DependencyTreeArgs {
+    id: String,
+    #[serde(default)]
+    direction: Option<String>,
+    #[serde(default)]
+    max_depth: Option<usize>,
+}
+
+/// Page size for `trx.issues.search` when no limit is given
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Most issues a single `trx.issues.bulk_update` may touch
+const MAX_BULK_IDS: usize = 1000;
+
+/// Lease length when a claim or heartbeat doesn't give one
+const DEFAULT_LEASE_SECONDS: u64 = 15 * 60;
+
+/// Agent identity for claims: the `agent` argument, then `TRX_AGENT`, then
+/// one derived from this server's process ID
+///
+/// The transports fill in each session's own identity (see
+/// [`server::handle`]), so the process fallback only applies to calls that
+/// bypass them.
+fn agent_name(agent: Option<String>) -> String {
+    agent
+        .filter(|a| !a.trim().is_empty())
+        .unwrap_or_else(|| default_agent(format!("mcp-{}", std::process::id())))
+}
+
+/// Identity for calls that name no agent: `TRX_AGENT`, then `fallback`
+fn default_agent(fallback: String) -> String {
+    std::env::var("TRX_AGENT")
+        .ok()
+        .filter(|a| !a.is_empty())
+        .unwrap_or(fallback)
+}
+
+fn lease_expiry(lease_seconds: Option<u64>) -> chrono::DateTime<chrono::Utc> {
+    let seconds = lease_seconds.unwrap_or(DEFAULT_LEASE_SECONDS).max(1);
+    chrono::Utc::now() + chrono::Duration::seconds(seconds.min(i64::MAX as u64) as i64)
+}
+
 // ============================================================================
 // Router implementation
 // ============================================================================
 
 impl TrxMcpRouter {
-    fn new(store: Store, root_path: PathBuf) -> Self {
+    fn new(store: UnifiedStore, root_path: PathBuf) -> Self {
         Self {
             inner: Arc::new(TrxMcpInner {
                 store: RwLock::new(store),
@@ -135,13 +287,64 @@ impl TrxMcpRouter {
     fn json_content(&self, uri: &str, value: Value) -> Result<Vec<Content>, ToolError> {
         let text = serde_json::to_string_pretty(&value)
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
-        Ok(vec![Content::resource(
-            ResourceContents::TextResourceContents {
-                uri: uri.to_string(),
-                mime_type: Some("application/json".to_string()),
-                text,
-            },
-        )])
+        Ok(self.text_content(uri, text))
+    }
+
+    /// Single-line JSON, for the analysis tools whose output goes straight
+    /// into an agent's context
+    fn compact_content(&self, uri: &str, value: Value) -> Result<Vec<Content>, ToolError> {
+        let text =
+            serde_json::to_string(&value).map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        Ok(self.text_content(uri, text))
+    }
+
+    fn text_content(&self, uri: &str, text: String) -> Vec<Content> {
+        vec![Content::resource(ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some("application/json".to_string()),
+            text,
+        })]
+    }
+
+    /// Render a page of issues with the shared sort, cursor and field options
+    fn page_content(
+        &self,
+        uri: &str,
+        store: &UnifiedStore,
+        issues: Vec<&Issue>,
+        page: &PageArgs,
+        default_limit: Option<usize>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let sort = match page.sort.as_deref() {
+            Some(sort) => sort
+                .parse::<SortSpec>()
+                .map_err(|e| ToolError::InvalidParameters(e.to_string()))?,
+            None => SortSpec::default(),
+        };
+        let cursor =
+            match page.cursor.as_deref() {
+                Some(id) => Some(store.get(id).ok_or_else(|| {
+                    ToolError::InvalidParameters(format!("Invalid cursor: {}", id))
+                })?),
+                None => None,
+            };
+        let fields = page.fields.as_deref().map(parse_fields).unwrap_or_default();
+        let limit = page.limit.or(default_limit).map(|l| l.max(1));
+
+        let value = listing::page(issues, &sort, cursor, limit, &fields)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        self.json_content(uri, value)
+    }
+
+    /// Take the store's cross-process lock and re-read the store, so writes
+    /// start from other agents' latest changes and none can land until the
+    /// lock drops; every tool that saves goes through this
+    fn lock_and_reload(&self, store: &mut UnifiedStore) -> Result<StoreLock, ToolError> {
+        let lock = UnifiedStore::lock(&self.inner.root_path)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        *store = UnifiedStore::open_at(&self.inner.root_path)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        Ok(lock)
     }
 
     // Tool: list issues
@@ -153,24 +356,20 @@ impl TrxMcpRouter {
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
 
         let include_tombstones = args.include_tombstones.unwrap_or(false);
-        let mut issues: Vec<_> = store
-            .list(include_tombstones)
-            .into_iter()
-            .cloned()
-            .collect();
+        let mut issues = store.list(include_tombstones);
 
         // Filter by status
-        if let Some(status_str) = &args.status {
-            if let Ok(status) = status_str.parse::<Status>() {
-                issues.retain(|i| i.status == status);
-            }
+        if let Some(status_str) = &args.status
+            && let Ok(status) = status_str.parse::<Status>()
+        {
+            issues.retain(|i| i.status == status);
         }
 
         // Filter by type
-        if let Some(type_str) = &args.issue_type {
-            if let Ok(issue_type) = type_str.parse::<IssueType>() {
-                issues.retain(|i| i.issue_type == issue_type);
-            }
+        if let Some(type_str) = &args.issue_type
+            && let Ok(issue_type) = type_str.parse::<IssueType>()
+        {
+            issues.retain(|i| i.issue_type == issue_type);
         }
 
         // Filter by priority
@@ -178,47 +377,272 @@ impl TrxMcpRouter {
             issues.retain(|i| i.priority == priority);
         }
 
-        // Sort by priority, then by created_at
-        issues.sort_by(|a, b| {
-            a.priority
-                .cmp(&b.priority)
-                .then_with(|| b.created_at.cmp(&a.created_at))
-        });
+        self.page_content("trx://tools/issues/list", &store, issues, &args.page, None)
+    }
 
-        self.json_content(
-            "trx://tools/issues/list",
-            json!({
-                "count": issues.len(),
-                "issues": issues,
-            }),
+    // Tool: search issues by text and structured terms
+    fn tool_search(&self, args: SearchIssuesArgs) -> Result<Vec<Content>, ToolError> {
+        let store = self
+            .inner
+            .store
+            .read()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        let filter = args
+            .query
+            .as_deref()
+            .unwrap_or_default()
+            .parse::<Filter>()
+            .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+        let issues: Vec<_> = store
+            .list(args.include_tombstones.unwrap_or(false))
+            .into_iter()
+            .filter(|i| filter.matches(i))
+            .collect();
+
+        self.page_content(
+            "trx://tools/issues/search",
+            &store,
+            issues,
+            &args.page,
+            Some(DEFAULT_SEARCH_LIMIT),
         )
     }
 
     // Tool: list ready (unblocked) issues
-    fn tool_ready(&self) -> Result<Vec<Content>, ToolError> {
+    fn tool_ready(&self, args: PageArgs) -> Result<Vec<Content>, ToolError> {
         let store = self
             .inner
             .store
             .read()
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
 
-        let all_open: Vec<_> = store.list_open();
-        let ready: Vec<_> = all_open
-            .iter()
-            .filter(|issue| !issue.is_blocked_by(&all_open))
+        let open = store.list_open();
+        let ready = IssueGraph::from_issues(&open).ready_issues(&open);
+
+        self.page_content("trx://tools/issues/ready", &store, ready, &args, None)
+    }
+
+    // Tool: claim an issue, or the next ready one
+    fn tool_claim(&self, args: ClaimIssueArgs) -> Result<Vec<Content>, ToolError> {
+        let mut store = self
+            .inner
+            .store
+            .write()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
+
+        let agent = agent_name(args.agent);
+        let now = chrono::Utc::now();
+        let mut issue = match &args.id {
+            Some(id) => {
+                let issue = store
+                    .get(id)
+                    .cloned()
+                    .ok_or_else(|| ToolError::NotFound(format!("Issue {} not found", id)))?;
+                if !issue.status.is_open() {
+                    return Err(ToolError::InvalidParameters(format!(
+                        "Issue {} is {}",
+                        id, issue.status
+                    )));
+                }
+                if let Some(holder) = issue.claimed_by(now)
+                    && holder != agent
+                {
+                    return Err(ToolError::ExecutionError(format!(
+                        "Issue {} is claimed by {}",
+                        id, holder
+                    )));
+                }
+                issue
+            }
+            None => {
+                let open = store.list_open();
+                let mut ready = IssueGraph::from_issues(&open).ready_issues(&open);
+                ready.sort_by(|a, b| {
+                    a.priority
+                        .cmp(&b.priority)
+                        .then_with(|| a.created_at.cmp(&b.created_at))
+                });
+                ready
+                    .first()
+                    .map(|i| (*i).clone())
+                    .ok_or_else(|| ToolError::NotFound("No ready issues to claim".into()))?
+            }
+        };
+
+        issue.claim(&agent, lease_expiry(args.lease_seconds));
+        let claimed = issue.clone();
+
+        store
+            .update(issue)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        self.json_content(
+            "trx://tools/issues/claim",
+            json!({
+                "claimed": true,
+                "agent": agent,
+                "issue": claimed,
+            }),
+        )
+    }
+
+    // Tool: extend the lease on a claimed issue
+    fn tool_heartbeat(&self, args: HeartbeatArgs) -> Result<Vec<Content>, ToolError> {
+        let mut store = self
+            .inner
+            .store
+            .write()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
+
+        let agent = agent_name(args.agent);
+        let mut issue = store
+            .get(&args.id)
             .cloned()
+            .ok_or_else(|| ToolError::NotFound(format!("Issue {} not found", args.id)))?;
+
+        // An expired lease can still be renewed as long as nobody else took it
+        if issue.lease_expires_at.is_none() || issue.assignee.as_deref() != Some(agent.as_str()) {
+            return Err(ToolError::ExecutionError(format!(
+                "Issue {} is not claimed by {}",
+                args.id, agent
+            )));
+        }
+
+        issue.lease_expires_at = Some(lease_expiry(args.lease_seconds));
+        issue.updated_at = chrono::Utc::now();
+        let renewed = issue.clone();
+
+        store
+            .update(issue)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        self.json_content(
+            "trx://tools/issues/heartbeat",
+            json!({
+                "renewed": true,
+                "agent": agent,
+                "issue": renewed,
+            }),
+        )
+    }
+
+    // Tool: release a claim
+    fn tool_release(&self, args: ReleaseIssueArgs) -> Result<Vec<Content>, ToolError> {
+        let mut store = self
+            .inner
+            .store
+            .write()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
+
+        let agent = agent_name(args.agent);
+        let mut issue = store
+            .get(&args.id)
             .cloned()
-            .collect();
+            .ok_or_else(|| ToolError::NotFound(format!("Issue {} not found", args.id)))?;
+
+        if let Some(holder) = issue.claimed_by(chrono::Utc::now())
+            && holder != agent
+        {
+            return Err(ToolError::ExecutionError(format!(
+                "Issue {} is claimed by {}",
+                args.id, holder
+            )));
+        }
+        if issue.lease_expires_at.is_none() {
+            return Err(ToolError::InvalidParameters(format!(
+                "Issue {} is not claimed",
+                args.id
+            )));
+        }
+
+        issue.release();
+        let released = issue.clone();
+
+        store
+            .update(issue)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
 
         self.json_content(
-            "trx://tools/issues/ready",
+            "trx://tools/issues/release",
             json!({
-                "count": ready.len(),
-                "issues": ready,
+                "released": true,
+                "issue": released,
             }),
         )
     }
 
+    // Tool: highest-leverage ready issue
+    fn tool_next(&self) -> Result<Vec<Content>, ToolError> {
+        let store = self
+            .inner
+            .store
+            .read()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        self.compact_content(
+            "trx://tools/issues/next",
+            insights::next(&store.list_open()),
+        )
+    }
+
+    // Tool: graph insights
+    fn tool_insights(&self, args: InsightsArgs) -> Result<Vec<Content>, ToolError> {
+        let store = self
+            .inner
+            .store
+            .read()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        let top = args.top.unwrap_or(5).clamp(1, 50);
+        self.compact_content(
+            "trx://tools/graph/insights",
+            insights::insights(&store.list_open(), top),
+        )
+    }
+
+    // Tool: dependency tree of an issue
+    fn tool_dependency_tree(&self, args: DependencyTreeArgs) -> Result<Vec<Content>, ToolError> {
+        let store = self
+            .inner
+            .store
+            .read()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        let up = match args.direction.as_deref() {
+            None | Some("down") => false,
+            Some("up") => true,
+            Some(other) => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "Invalid direction '{}': expected up or down",
+                    other
+                )));
+            }
+        };
+        let max_depth = args.max_depth.unwrap_or(5).clamp(1, 20);
+        let tree = insights::dependency_tree(&store.list(false), &args.id, up, max_depth)
+            .ok_or_else(|| ToolError::NotFound(format!("Issue {} not found", args.id)))?;
+
+        self.compact_content("trx://tools/issues/dependency/tree", tree)
+    }
+
+    // Tool: critical path through open issues
+    fn tool_critical_path(&self) -> Result<Vec<Content>, ToolError> {
+        let store = self
+            .inner
+            .store
+            .read()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        self.compact_content(
+            "trx://tools/graph/critical_path",
+            insights::critical_path(&store.list_open()),
+        )
+    }
+
     // Tool: get single issue
     fn tool_get(&self, args: GetIssueArgs) -> Result<Vec<Content>, ToolError> {
         let store = self
@@ -241,6 +665,7 @@ impl TrxMcpRouter {
             .store
             .write()
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
 
         // Get prefix for ID generation
         let prefix = store.prefix().unwrap_or_else(|_| "trx".to_string());
@@ -267,12 +692,13 @@ impl TrxMcpRouter {
             issue.description = Some(desc);
         }
         if let Some(priority) = args.priority {
-            issue.priority = priority.min(4);
+            issue.priority = check_priority(priority)
+                .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
         }
-        if let Some(type_str) = args.issue_type {
-            if let Ok(t) = type_str.parse::<IssueType>() {
-                issue.issue_type = t;
-            }
+        if let Some(type_str) = args.issue_type
+            && let Ok(t) = type_str.parse::<IssueType>()
+        {
+            issue.issue_type = t;
         }
         if let Some(labels) = args.labels {
             issue.labels = labels;
@@ -306,9 +732,11 @@ impl TrxMcpRouter {
             .store
             .write()
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
 
-        let issue = store
-            .get_mut(&args.id)
+        let mut issue = store
+            .get(&args.id)
+            .cloned()
             .ok_or_else(|| ToolError::NotFound(format!("Issue {} not found", args.id)))?;
 
         // Apply updates
@@ -318,18 +746,19 @@ impl TrxMcpRouter {
         if let Some(desc) = args.description {
             issue.description = Some(desc);
         }
-        if let Some(status_str) = args.status {
-            if let Ok(status) = status_str.parse::<Status>() {
-                issue.status = status;
-            }
+        if let Some(status_str) = args.status
+            && let Ok(status) = status_str.parse::<Status>()
+        {
+            issue.set_status(status);
         }
         if let Some(priority) = args.priority {
-            issue.priority = priority.min(4);
+            issue.priority = check_priority(priority)
+                .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
         }
-        if let Some(type_str) = args.issue_type {
-            if let Ok(t) = type_str.parse::<IssueType>() {
-                issue.issue_type = t;
-            }
+        if let Some(type_str) = args.issue_type
+            && let Ok(t) = type_str.parse::<IssueType>()
+        {
+            issue.issue_type = t;
         }
         if let Some(labels) = args.labels {
             issue.labels = labels;
@@ -345,7 +774,7 @@ impl TrxMcpRouter {
         let updated = issue.clone();
 
         store
-            .save()
+            .update(issue)
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
 
         self.json_content(
@@ -357,6 +786,138 @@ impl TrxMcpRouter {
         )
     }
 
+    // Tool: apply one patch to many issues
+    fn tool_bulk_update(&self, args: BulkUpdateArgs) -> Result<Vec<Content>, ToolError> {
+        if args.ids.is_empty() || args.ids.len() > MAX_BULK_IDS {
+            return Err(ToolError::InvalidParameters(format!(
+                "ids must contain between 1 and {} issue IDs",
+                MAX_BULK_IDS
+            )));
+        }
+        let patch = args.patch;
+        if patch.title.is_none()
+            && patch.description.is_none()
+            && patch.status.is_none()
+            && patch.priority.is_none()
+            && patch.issue_type.is_none()
+            && patch.labels.is_none()
+            && patch.assignee.is_none()
+            && patch.notes.is_none()
+        {
+            return Err(ToolError::InvalidParameters("patch sets no fields".into()));
+        }
+
+        let ops: Vec<_> = args
+            .ids
+            .iter()
+            .map(|id| BatchOp::Update {
+                id: id.clone(),
+                title: patch.title.clone(),
+                description: patch.description.clone(),
+                status: patch.status.clone(),
+                priority: patch.priority,
+                issue_type: patch.issue_type.clone(),
+                labels: patch.labels.clone(),
+                assignee: patch.assignee.clone(),
+                notes: patch.notes.clone(),
+            })
+            .collect();
+
+        let mut store = self
+            .inner
+            .store
+            .write()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
+        let outcome = store
+            .apply_batch(&ops)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        // Successes are listed by ID; failures keep their error
+        let failed: Vec<_> = outcome
+            .results
+            .iter()
+            .filter(|r| !r.success)
+            .map(|r| json!({ "id": args.ids[r.index], "error": r.error }))
+            .collect();
+        self.compact_content(
+            "trx://tools/issues/bulk_update",
+            json!({
+                "applied": outcome.applied,
+                "updated": if outcome.applied { outcome.touched_ids() } else { Vec::new() },
+                "failed": failed,
+            }),
+        )
+    }
+
+    // Tool: comment on an issue
+    fn tool_comment(&self, args: CommentArgs) -> Result<Vec<Content>, ToolError> {
+        if args.text.trim().is_empty() {
+            return Err(ToolError::InvalidParameters(
+                "Comment text must not be empty".into(),
+            ));
+        }
+
+        let mut store = self
+            .inner
+            .store
+            .write()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
+
+        let mut issue = store
+            .get(&args.id)
+            .cloned()
+            .ok_or_else(|| ToolError::NotFound(format!("Issue {} not found", args.id)))?;
+
+        issue.add_comment(Some(agent_name(args.author)), args.text);
+        let comment = issue.comments.last().cloned();
+        let count = issue.comments.len();
+
+        store
+            .update(issue)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        self.json_content(
+            "trx://tools/issues/comment",
+            json!({
+                "commented": true,
+                "id": args.id,
+                "comment": comment,
+                "comments": count,
+            }),
+        )
+    }
+
+    // Tool: resolve conflicts and commit .trx/ to git
+    fn tool_sync(&self, args: SyncArgs) -> Result<Vec<Content>, ToolError> {
+        let mut store = self
+            .inner
+            .store
+            .write()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
+
+        let resolved = store
+            .resolve_conflicts()
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let message = args
+            .message
+            .filter(|m| !m.trim().is_empty())
+            .unwrap_or_else(|| "trx: sync issues".to_string());
+        let committed = trx_core::sync::commit(&store.trx_dir(), &message)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        self.json_content(
+            "trx://tools/sync",
+            json!({
+                "resolved": resolved,
+                "committed": committed,
+                "message": if committed { Some(message) } else { None },
+            }),
+        )
+    }
+
     // Tool: close issue
     fn tool_close(&self, args: CloseIssueArgs) -> Result<Vec<Content>, ToolError> {
         let mut store = self
@@ -364,16 +925,18 @@ impl TrxMcpRouter {
             .store
             .write()
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
 
-        let issue = store
-            .get_mut(&args.id)
+        let mut issue = store
+            .get(&args.id)
+            .cloned()
             .ok_or_else(|| ToolError::NotFound(format!("Issue {} not found", args.id)))?;
 
         issue.close(args.reason);
         let closed = issue.clone();
 
         store
-            .save()
+            .update(issue)
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
 
         self.json_content(
@@ -392,6 +955,7 @@ impl TrxMcpRouter {
             .store
             .write()
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
 
         store
             .delete(&args.id, args.by, args.reason)
@@ -418,6 +982,7 @@ impl TrxMcpRouter {
             .store
             .write()
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
 
         // Check that target exists
         if store.get(&args.depends_on).is_none() {
@@ -427,8 +992,9 @@ impl TrxMcpRouter {
             )));
         }
 
-        let issue = store
-            .get_mut(&args.id)
+        let mut issue = store
+            .get(&args.id)
+            .cloned()
             .ok_or_else(|| ToolError::NotFound(format!("Issue {} not found", args.id)))?;
 
         let dep_type = args
@@ -445,7 +1011,7 @@ impl TrxMcpRouter {
         let updated = issue.clone();
 
         store
-            .save()
+            .update(issue)
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
 
         self.json_content(
@@ -467,16 +1033,18 @@ impl TrxMcpRouter {
             .store
             .write()
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let _lock = self.lock_and_reload(&mut store)?;
 
-        let issue = store
-            .get_mut(&args.id)
+        let mut issue = store
+            .get(&args.id)
+            .cloned()
             .ok_or_else(|| ToolError::NotFound(format!("Issue {} not found", args.id)))?;
 
         issue.remove_dependency(&args.depends_on);
         let updated = issue.clone();
 
         store
-            .save()
+            .update(issue)
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
 
         self.json_content(
@@ -508,6 +1076,8 @@ impl mcp_server::Router for TrxMcpRouter {
     fn capabilities(&self) -> ServerCapabilities {
         mcp_server::router::CapabilitiesBuilder::new()
             .with_tools(true)
+            .with_resources(true, true)
+            .with_prompts(false)
             .build()
     }
 
@@ -515,24 +1085,49 @@ impl mcp_server::Router for TrxMcpRouter {
         vec![
             Tool::new(
                 "trx.issues.list",
-                "List all issues with optional filters.",
+                "List all issues with optional filters. Long text fields are truncated; use trx.issues.get for the full issue.",
                 json!({
                     "type": "object",
                     "properties": {
                         "status": { "type": ["string", "null"], "description": "Filter by status: open, in_progress, blocked, closed" },
                         "issue_type": { "type": ["string", "null"], "description": "Filter by type: bug, feature, task, epic, chore" },
                         "priority": { "type": ["integer", "null"], "minimum": 0, "maximum": 4, "description": "Filter by priority (0=critical to 4=backlog)" },
-                        "include_tombstones": { "type": ["boolean", "null"], "default": false }
+                        "include_tombstones": { "type": ["boolean", "null"], "default": false },
+                        "sort": { "type": ["string", "null"], "description": "Comma-separated sort keys with optional :asc/:desc, e.g. updated_at:desc,priority (default priority, then newest)" },
+                        "limit": { "type": ["integer", "null"], "minimum": 1, "description": "Page size" },
+                        "cursor": { "type": ["string", "null"], "description": "next_cursor from the previous page" },
+                        "fields": { "type": ["string", "null"], "description": "Comma-separated fields to return, e.g. id,title,status" }
                     },
                     "additionalProperties": false
                 }),
             ),
             Tool::new(
                 "trx.issues.ready",
-                "List open issues that are not blocked by other open issues.",
+                "List open issues that are not blocked by other open issues or claimed.",
                 json!({
                     "type": "object",
-                    "properties": {},
+                    "properties": {
+                        "sort": { "type": ["string", "null"], "description": "Comma-separated sort keys with optional :asc/:desc, e.g. updated_at:desc,priority (default priority, then newest)" },
+                        "limit": { "type": ["integer", "null"], "minimum": 1, "description": "Page size" },
+                        "cursor": { "type": ["string", "null"], "description": "next_cursor from the previous page" },
+                        "fields": { "type": ["string", "null"], "description": "Comma-separated fields to return, e.g. id,title,status" }
+                    },
+                    "additionalProperties": false
+                }),
+            ),
+            Tool::new(
+                "trx.issues.search",
+                "Search issues by free text and structured terms. Results are paginated (50 per page by default) and long text fields are truncated.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": ["string", "null"], "description": "Space-separated terms, all of which must match: status:open,in_progress type:bug label:x assignee:name priority:<=1, and bare words matched against title and description" },
+                        "include_tombstones": { "type": ["boolean", "null"], "default": false },
+                        "sort": { "type": ["string", "null"], "description": "Comma-separated sort keys with optional :asc/:desc, e.g. updated_at:desc,priority (default priority, then newest)" },
+                        "limit": { "type": ["integer", "null"], "minimum": 1, "description": "Page size" },
+                        "cursor": { "type": ["string", "null"], "description": "next_cursor from the previous page" },
+                        "fields": { "type": ["string", "null"], "description": "Comma-separated fields to return, e.g. id,title,status" }
+                    },
                     "additionalProperties": false
                 }),
             ),
@@ -586,6 +1181,57 @@ impl mcp_server::Router for TrxMcpRouter {
                     "additionalProperties": false
                 }),
             ),
+            Tool::new(
+                "trx.issues.bulk_update",
+                "Apply the same changes to several issues. Either every issue is updated or, if any update fails, none are.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "ids": { "type": "array", "items": { "type": "string" }, "minItems": 1, "maxItems": MAX_BULK_IDS },
+                        "patch": {
+                            "type": "object",
+                            "properties": {
+                                "title": { "type": ["string", "null"] },
+                                "description": { "type": ["string", "null"] },
+                                "status": { "type": ["string", "null"], "description": "open, in_progress, blocked, closed" },
+                                "priority": { "type": ["integer", "null"], "minimum": 0, "maximum": 4 },
+                                "issue_type": { "type": ["string", "null"] },
+                                "labels": { "type": ["array", "null"], "items": { "type": "string" }, "description": "Replaces the existing labels" },
+                                "assignee": { "type": ["string", "null"] },
+                                "notes": { "type": ["string", "null"] }
+                            },
+                            "additionalProperties": false
+                        }
+                    },
+                    "required": ["ids", "patch"],
+                    "additionalProperties": false
+                }),
+            ),
+            Tool::new(
+                "trx.issues.comment",
+                "Add a comment to an issue.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "text": { "type": "string" },
+                        "author": { "type": ["string", "null"], "description": "Defaults to TRX_AGENT or one agent per session" }
+                    },
+                    "required": ["id", "text"],
+                    "additionalProperties": false
+                }),
+            ),
+            Tool::new(
+                "trx.sync",
+                "Resolve merge conflicts in .trx/ and commit it to git, like `trx sync`.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "message": { "type": ["string", "null"], "description": "Commit message (default: trx: sync issues)" }
+                    },
+                    "additionalProperties": false
+                }),
+            ),
             Tool::new(
                 "trx.issues.close",
                 "Close an issue with optional reason.",
@@ -640,6 +1286,89 @@ impl mcp_server::Router for TrxMcpRouter {
                     "additionalProperties": false
                 }),
             ),
+            Tool::new(
+                "trx.issues.next",
+                "Pick the ready issue with the most leverage: highest priority, then most open issues unblocked, then PageRank. Returns the reason and a few alternatives.",
+                json!({
+                    "type": "object",
+                    "properties": {},
+                    "additionalProperties": false
+                }),
+            ),
+            Tool::new(
+                "trx.graph.insights",
+                "Backlog health: open/ready counts, dependency cycles, critical path, top issues by PageRank and the biggest bottlenecks.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "top": { "type": ["integer", "null"], "minimum": 1, "maximum": 50, "description": "Entries per ranking (default 5)" }
+                    },
+                    "additionalProperties": false
+                }),
+            ),
+            Tool::new(
+                "trx.issues.dependency.tree",
+                "Dependency tree of an issue: what it depends on (down) or what waits on it (up).",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "direction": { "type": ["string", "null"], "enum": ["down", "up", null], "description": "down (default) or up" },
+                        "max_depth": { "type": ["integer", "null"], "minimum": 1, "maximum": 20, "description": "Levels to expand (default 5)" }
+                    },
+                    "required": ["id"],
+                    "additionalProperties": false
+                }),
+            ),
+            Tool::new(
+                "trx.graph.critical_path",
+                "Longest chain of blocking dependencies among open issues, in the order the work must happen.",
+                json!({
+                    "type": "object",
+                    "properties": {},
+                    "additionalProperties": false
+                }),
+            ),
+            Tool::new(
+                "trx.issues.claim",
+                "Claim an issue for an agent: assigns it, marks it in_progress and takes a lease. Claimed issues are hidden from ready until the lease expires. Without an id, claims the highest-priority ready issue.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": ["string", "null"], "description": "Issue to claim (default: next ready issue)" },
+                        "agent": { "type": ["string", "null"], "description": "Agent identity (default: TRX_AGENT or one per session)" },
+                        "lease_seconds": { "type": ["integer", "null"], "minimum": 1, "description": "Lease length (default 900)" }
+                    },
+                    "additionalProperties": false
+                }),
+            ),
+            Tool::new(
+                "trx.issues.heartbeat",
+                "Extend the lease on an issue this agent has claimed.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "agent": { "type": ["string", "null"] },
+                        "lease_seconds": { "type": ["integer", "null"], "minimum": 1, "description": "New lease length from now (default 900)" }
+                    },
+                    "required": ["id"],
+                    "additionalProperties": false
+                }),
+            ),
+            Tool::new(
+                "trx.issues.release",
+                "Release a claim: unassigns the issue and returns it to open.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "agent": { "type": ["string", "null"] }
+                    },
+                    "required": ["id"],
+                    "additionalProperties": false
+                }),
+            ),
         ]
     }
 
@@ -652,6 +1381,12 @@ impl mcp_server::Router for TrxMcpRouter {
     > {
         let router = self.clone();
         let tool_name = tool_name.to_string();
+        // Tools without required arguments may be called with none at all
+        let arguments = if arguments.is_null() {
+            json!({})
+        } else {
+            arguments
+        };
         Box::pin(async move {
             match tool_name.as_str() {
                 "trx.issues.list" => {
@@ -659,7 +1394,31 @@ impl mcp_server::Router for TrxMcpRouter {
                         .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
                     router.tool_list(args)
                 }
-                "trx.issues.ready" => router.tool_ready(),
+                "trx.issues.ready" => {
+                    let args: PageArgs = serde_json::from_value(arguments)
+                        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                    router.tool_ready(args)
+                }
+                "trx.issues.search" => {
+                    let args: SearchIssuesArgs = serde_json::from_value(arguments)
+                        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                    router.tool_search(args)
+                }
+                "trx.issues.bulk_update" => {
+                    let args: BulkUpdateArgs = serde_json::from_value(arguments)
+                        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                    router.tool_bulk_update(args)
+                }
+                "trx.issues.comment" => {
+                    let args: CommentArgs = serde_json::from_value(arguments)
+                        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                    router.tool_comment(args)
+                }
+                "trx.sync" => {
+                    let args: SyncArgs = serde_json::from_value(arguments)
+                        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                    router.tool_sync(args)
+                }
                 "trx.issues.get" => {
                     let args: GetIssueArgs = serde_json::from_value(arguments)
                         .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
@@ -695,26 +1454,72 @@ impl mcp_server::Router for TrxMcpRouter {
                         .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
                     router.tool_remove_dependency(args)
                 }
+                "trx.issues.next" => router.tool_next(),
+                "trx.graph.insights" => {
+                    let args: InsightsArgs = serde_json::from_value(arguments)
+                        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                    router.tool_insights(args)
+                }
+                "trx.issues.dependency.tree" => {
+                    let args: DependencyTreeArgs = serde_json::from_value(arguments)
+                        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                    router.tool_dependency_tree(args)
+                }
+                "trx.graph.critical_path" => router.tool_critical_path(),
+                "trx.issues.claim" => {
+                    let args: ClaimIssueArgs = serde_json::from_value(arguments)
+                        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                    router.tool_claim(args)
+                }
+                "trx.issues.heartbeat" => {
+                    let args: HeartbeatArgs = serde_json::from_value(arguments)
+                        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                    router.tool_heartbeat(args)
+                }
+                "trx.issues.release" => {
+                    let args: ReleaseIssueArgs = serde_json::from_value(arguments)
+                        .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                    router.tool_release(args)
+                }
                 _ => Err(ToolError::NotFound(tool_name)),
             }
         })
     }
 
     fn list_resources(&self) -> Vec<Resource> {
-        Vec::new()
+        match self.inner.store.read() {
+            Ok(store) => resources::list(&store),
+            Err(_) => Vec::new(),
+        }
     }
 
+    // Reads are normally answered by the server loop, which keeps each
+    // content's MIME type; this returns the first content as plain text.
     fn read_resource(
         &self,
-        _uri: &str,
+        uri: &str,
     ) -> std::pin::Pin<
         Box<dyn std::future::Future<Output = Result<String, ResourceError>> + Send + 'static>,
     > {
-        Box::pin(async { Err(ResourceError::NotFound("No resources supported".into())) })
+        let router = self.clone();
+        let uri = uri.to_string();
+        Box::pin(async move {
+            let store = router
+                .inner
+                .store
+                .read()
+                .map_err(|e| ResourceError::ExecutionError(e.to_string()))?;
+            let contents = resources::read(&store, &uri)?;
+            match contents.into_iter().next() {
+                Some(ResourceContents::TextResourceContents { text, .. }) => Ok(text),
+                Some(ResourceContents::BlobResourceContents { blob, .. }) => Ok(blob),
+                None => Err(ResourceError::NotFound(uri)),
+            }
+        })
     }
 
     fn list_prompts(&self) -> Vec<Prompt> {
-        Vec::new()
+        prompts::list()
     }
 
     fn get_prompt(
@@ -723,8 +1528,9 @@ impl mcp_server::Router for TrxMcpRouter {
     ) -> std::pin::Pin<
         Box<dyn std::future::Future<Output = Result<String, PromptError>> + Send + 'static>,
     > {
-        let name = prompt_name.to_string();
-        Box::pin(async move { Err(PromptError::NotFound(name)) })
+        // Filled prompts are served by `server`; this returns the raw template
+        let result = prompts::template(&self.inner.root_path.join(".trx"), prompt_name);
+        Box::pin(async move { result })
     }
 }
 
@@ -732,24 +1538,142 @@ impl mcp_server::Router for TrxMcpRouter {
 // Main
 // ============================================================================
 
-fn parse_workdir() -> anyhow::Result<Option<PathBuf>> {
-    let mut args = std::env::args().skip(1);
+/// Command-line options
+#[derive(Debug, Default)]
+struct Args {
+    workdir: Option<PathBuf>,
+    /// Serve over HTTP; `Some(None)` means the default address
+    http: Option<Option<String>>,
+    token: Option<String>,
+}
+
+const USAGE: &str = "trx-mcp
+
+Usage:
+  trx-mcp [--workdir PATH] [--http [ADDR]] [--token TOKEN]
+
+Runs an MCP (Model Context Protocol) server for trx issue tracking, over
+stdio by default.
+
+Options:
+  -w, --workdir PATH  Repository to serve
+  --http [ADDR]       Serve streamable HTTP (/mcp) and HTTP+SSE (/sse)
+                      instead of stdio (default 127.0.0.1:3848)
+  --token TOKEN       Require 'Authorization: Bearer TOKEN' over HTTP
+                      (default: $TRX_MCP_TOKEN)";
+
+fn parse_args() -> anyhow::Result<Args> {
+    let mut parsed = Args {
+        token: std::env::var("TRX_MCP_TOKEN")
+            .ok()
+            .filter(|t| !t.is_empty()),
+        ..Args::default()
+    };
+    let mut args = std::env::args().skip(1).peekable();
     while let Some(arg) = args.next() {
         match arg.as_str() {
             "--workdir" | "-w" => {
                 let path = args.next().context("--workdir requires a path argument")?;
-                return Ok(Some(PathBuf::from(path)));
+                parsed.workdir = Some(PathBuf::from(path));
+            }
+            "--http" => {
+                let addr = args.next_if(|a| !a.starts_with('-'));
+                parsed.http = Some(addr);
+            }
+            "--token" => {
+                parsed.token = Some(args.next().context("--token requires a value")?);
             }
             "-h" | "--help" => {
-                println!(
-                    "trx-mcp\n\nUsage:\n  trx-mcp [--workdir PATH]\n\nRuns an MCP (Model Context Protocol) server over stdio for trx issue tracking."
-                );
+                println!("{}", USAGE);
                 std::process::exit(0);
             }
             _ => continue,
         }
     }
-    Ok(None)
+    Ok(parsed)
+}
+
+/// Bind the HTTP listener
+///
+/// An explicit address must be available; the default port falls back to
+/// any free port.
+async fn bind(addr: Option<&str>) -> anyhow::Result<tokio::net::TcpListener> {
+    if let Some(addr) = addr {
+        return tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind {}", addr));
+    }
+    match tokio::net::TcpListener::bind(("127.0.0.1", DEFAULT_MCP_PORT)).await {
+        Ok(listener) => Ok(listener),
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+            tracing::warn!("Port {} is in use, picking another", DEFAULT_MCP_PORT);
+            Ok(tokio::net::TcpListener::bind(("127.0.0.1", 0)).await?)
+        }
+        Err(e) => Err(anyhow::anyhow!(
+            "Failed to bind port {}: {}",
+            DEFAULT_MCP_PORT,
+            e
+        )),
+    }
+}
+
+/// Resolve when SIGINT or SIGTERM is received
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.ok();
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutting down");
+}
+
+/// Serve over HTTP, recording the PID and port like trx-api does
+async fn serve_http(
+    router: TrxMcpRouter,
+    addr: Option<&str>,
+    token: Option<String>,
+) -> anyhow::Result<()> {
+    let manager = ServiceManager::for_service(Service::Mcp)
+        .map_err(|e| anyhow::anyhow!("Failed to initialize service manager: {}", e))?;
+    if manager.is_running() {
+        let pid = manager.read_pid().unwrap_or_default();
+        anyhow::bail!("trx-mcp is already serving HTTP (PID: {})", pid);
+    }
+
+    let listener = bind(addr).await?;
+    let local = listener.local_addr()?;
+    if token.is_none() && !local.ip().is_loopback() {
+        tracing::warn!("Serving on {} without a bearer token", local);
+    }
+    tracing::info!(
+        "Serving {} on http://{}/mcp",
+        router.inner.root_path.display(),
+        local
+    );
+    manager.write_pid(std::process::id())?;
+    manager.write_port(local.port())?;
+
+    let result = axum::serve(listener, http::app(router, token))
+        .with_graceful_shutdown(shutdown_signal())
+        .await;
+    manager.cleanup();
+    Ok(result?)
 }
 
 fn init_tracing() {
@@ -765,23 +1689,148 @@ fn init_tracing() {
 async fn main() -> anyhow::Result<()> {
     init_tracing();
 
+    let args = parse_args()?;
+
     // Change to workdir if specified
-    if let Some(workdir) = parse_workdir()? {
-        std::env::set_current_dir(&workdir)
+    if let Some(workdir) = &args.workdir {
+        std::env::set_current_dir(workdir)
             .with_context(|| format!("Failed to change to workdir: {}", workdir.display()))?;
     }
 
     let cwd = std::env::current_dir()?;
 
     // Open the store
-    let store = Store::open().map_err(|e| anyhow::anyhow!("Failed to open store: {}", e))?;
+    let root = UnifiedStore::find_root().unwrap_or(cwd);
+    let store =
+        UnifiedStore::open_at(&root).map_err(|e| anyhow::anyhow!("Failed to open store: {}", e))?;
+
+    let router = TrxMcpRouter::new(store, root);
+    match &args.http {
+        Some(addr) => serve_http(router, addr.as_deref(), args.token).await,
+        None => server::run(router).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trx_core::StorageVersion;
+
+    /// A V1 repository holding `issues` open issues, `trx-000` onwards
+    pub(crate) fn init_repo(root: &std::path::Path, issues: usize) {
+        let mut store = UnifiedStore::init_at(root, "trx", StorageVersion::V1).unwrap();
+        for n in 0..issues {
+            store
+                .create(Issue::new(format!("trx-{:03}", n), format!("Issue {}", n)))
+                .unwrap();
+        }
+    }
+
+    fn claim(router: &TrxMcpRouter, id: &str, agent: &str) -> Result<Vec<Content>, ToolError> {
+        router.tool_claim(ClaimIssueArgs {
+            id: Some(id.into()),
+            agent: Some(agent.into()),
+            lease_seconds: None,
+        })
+    }
+
+    #[test]
+    fn test_claims_are_exclusive_across_stores() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        init_repo(&root, 20);
 
-    let router = TrxMcpRouter::new(store, cwd);
-    let service = RouterService(router);
-    let server = mcp_server::Server::new(service);
+        // Two agents, each with its own server and store, as with stdio
+        let routers: Vec<_> = ["agent-a", "agent-b"]
+            .into_iter()
+            .map(|agent| {
+                let store = UnifiedStore::open_at(&root).unwrap();
+                (agent, TrxMcpRouter::new(store, root.clone()))
+            })
+            .collect();
+
+        let wins: Vec<Vec<String>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = routers
+                .iter()
+                .map(|(agent, router)| {
+                    scope.spawn(move || {
+                        (0..20)
+                            .map(|n| format!("trx-{:03}", n))
+                            .filter(|id| claim(router, id, agent).is_ok())
+                            .collect()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let store = UnifiedStore::open_at(&root).unwrap();
+        let now = chrono::Utc::now();
+        assert_eq!(wins[0].len() + wins[1].len(), 20);
+        for ((agent, _), won) in routers.iter().zip(&wins) {
+            for id in won {
+                assert_eq!(store.get(id).unwrap().claimed_by(now), Some(*agent));
+            }
+        }
 
-    let transport = mcp_server::ByteTransport::new(tokio::io::stdin(), tokio::io::stdout());
-    server.run(transport).await?;
+        // The loser is refused, and only the holder may release
+        let (_, router_b) = &routers[1];
+        let held_by_a = wins[0].first().or(wins[1].first()).unwrap();
+        let holder = store.get(held_by_a).unwrap().claimed_by(now).unwrap();
+        let other = if holder == "agent-a" {
+            "agent-b"
+        } else {
+            "agent-a"
+        };
+        assert!(claim(router_b, held_by_a, other).is_err());
+        assert!(
+            router_b
+                .tool_release(ReleaseIssueArgs {
+                    id: held_by_a.clone(),
+                    agent: Some(other.into()),
+                })
+                .is_err()
+        );
+
+        let ignored = std::fs::read_to_string(root.join(".trx/.gitignore")).unwrap();
+        assert_eq!(ignored, ".lock\n");
+    }
 
-    Ok(())
+    #[test]
+    fn test_writes_keep_claims_from_other_stores() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        init_repo(&root, 2);
+        let router_a = TrxMcpRouter::new(UnifiedStore::open_at(&root).unwrap(), root.clone());
+        let router_b = TrxMcpRouter::new(UnifiedStore::open_at(&root).unwrap(), root.clone());
+
+        // B's store was read before A's claim
+        claim(&router_a, "trx-000", "agent-a").unwrap();
+        router_b
+            .tool_update(
+                serde_json::from_value(json!({ "id": "trx-001", "title": "Renamed" })).unwrap(),
+            )
+            .unwrap();
+
+        let store = UnifiedStore::open_at(&root).unwrap();
+        let now = chrono::Utc::now();
+        assert_eq!(
+            store.get("trx-000").unwrap().claimed_by(now),
+            Some("agent-a")
+        );
+        assert_eq!(store.get("trx-001").unwrap().title, "Renamed");
+    }
+
+    #[test]
+    fn test_sync_commits_through_core() {
+        // Committing is tested in trx_core::sync; outside a git repository
+        // its error comes back from the tool
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        init_repo(&root, 1);
+
+        let router = TrxMcpRouter::new(UnifiedStore::open_at(&root).unwrap(), root.clone());
+        let err = router.tool_sync(SyncArgs { message: None }).unwrap_err();
+        assert!(matches!(err, ToolError::ExecutionError(e) if e.contains("git add failed")));
+    }
 }
@@ -0,0 +1,376 @@
+//! MCP prompts
+//!
+//! Built-in prompt templates filled with live tracker data. A file named
+//! `.trx/prompts/<name>.md` replaces the built-in template of the same name;
+//! placeholders are written `{{name}}`.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use mcp_spec::handler::PromptError;
+use mcp_spec::prompt::{Prompt, PromptArgument};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use trx_core::{DependencyType, Issue, IssueGraph, IssueType, Status, UnifiedStore};
+
+/// A built-in prompt
+struct Builtin {
+    name: &'static str,
+    description: &'static str,
+    /// Name, description and whether it is required
+    arguments: &'static [(&'static str, &'static str, bool)],
+    template: &'static str,
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        name: "triage",
+        description: "Review the open backlog with PageRank and blocker information",
+        arguments: &[],
+        template: include_str!("../prompts/triage.md"),
+    },
+    Builtin {
+        name: "plan-epic",
+        description: "Propose child issues for an epic, in trx.issues.create format",
+        arguments: &[("epic_id", "ID of the epic to break down", true)],
+        template: include_str!("../prompts/plan-epic.md"),
+    },
+    Builtin {
+        name: "standup",
+        description: "Summarise issues closed, created and updated since a date",
+        arguments: &[(
+            "since",
+            "Date (YYYY-MM-DD), timestamp or age like 24h or 7d (default 24h)",
+            false,
+        )],
+        template: include_str!("../prompts/standup.md"),
+    },
+];
+
+/// Prompt definitions for `prompts/list`
+pub fn list() -> Vec<Prompt> {
+    BUILTINS
+        .iter()
+        .map(|b| {
+            let arguments = b
+                .arguments
+                .iter()
+                .map(|(name, description, required)| PromptArgument {
+                    name: name.to_string(),
+                    description: Some(description.to_string()),
+                    required: Some(*required),
+                })
+                .collect();
+            Prompt::new(b.name, Some(b.description), Some(arguments))
+        })
+        .collect()
+}
+
+/// The template for a prompt, preferring an override in `.trx/prompts/`
+pub fn template(trx_dir: &Path, name: &str) -> Result<String, PromptError> {
+    let builtin = BUILTINS
+        .iter()
+        .find(|b| b.name == name)
+        .ok_or_else(|| PromptError::NotFound(name.to_string()))?;
+
+    let path = trx_dir.join("prompts").join(format!("{}.md", name));
+    match std::fs::read_to_string(&path) {
+        Ok(template) => Ok(template),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(builtin.template.to_string()),
+        Err(e) => Err(PromptError::InternalError(format!(
+            "Failed to read {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+/// Replace `{{name}}` placeholders; unknown placeholders are left as-is
+///
+/// The template is scanned once, left to right, so placeholders inside
+/// substituted values (an issue title containing `{{backlog}}`, say) are
+/// never expanded.
+pub fn render(template: &str, vars: &HashMap<&str, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after
+            .find("}}")
+            .and_then(|end| Some((vars.get(&after[..end])?, end)))
+        {
+            Some((value, end)) => {
+                out.push_str(value);
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push('{');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Fill a prompt and return the `prompts/get` result
+pub fn get(
+    store: &UnifiedStore,
+    repo: &str,
+    name: &str,
+    arguments: &Map<String, Value>,
+) -> Result<Value, PromptError> {
+    let arg = |key: &str| {
+        arguments
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    };
+
+    let mut vars: HashMap<&str, String> = HashMap::new();
+    vars.insert("repo", repo.to_string());
+
+    match name {
+        "triage" => triage_vars(store, &mut vars),
+        "plan-epic" => {
+            let epic_id = arg("epic_id").ok_or_else(|| {
+                PromptError::InvalidParameters("Missing required argument: epic_id".into())
+            })?;
+            plan_epic_vars(store, epic_id, &mut vars)?;
+        }
+        "standup" => {
+            let since = parse_since(arg("since").unwrap_or("24h"), Utc::now())?;
+            standup_vars(store, since, &mut vars);
+        }
+        _ => return Err(PromptError::NotFound(name.to_string())),
+    }
+
+    let builtin = BUILTINS.iter().find(|b| b.name == name);
+    let text = render(&template(&store.trx_dir(), name)?, &vars);
+    Ok(json!({
+        "description": builtin.map(|b| b.description),
+        "messages": [{
+            "role": "user",
+            "content": { "type": "text", "text": text },
+        }],
+    }))
+}
+
+/// One line per issue: `- [id] title (P1, bug, status)`
+fn issue_line(issue: &Issue) -> String {
+    let mut line = format!(
+        "- [{}] {} (P{}, {}, {}",
+        issue.id, issue.title, issue.priority, issue.issue_type, issue.status
+    );
+    if let Some(assignee) = &issue.assignee {
+        line.push_str(&format!(", @{}", assignee));
+    }
+    line.push(')');
+    line
+}
+
+fn bullet_list(issues: &[&Issue]) -> String {
+    if issues.is_empty() {
+        return "(none)".to_string();
+    }
+    issues
+        .iter()
+        .map(|i| issue_line(i))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn triage_vars(store: &UnifiedStore, vars: &mut HashMap<&str, String>) {
+    let mut open = store.list_open();
+    open.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.id.cmp(&b.id)));
+    let graph = IssueGraph::from_issues(&open);
+    let pagerank = graph.pagerank(0.85, 20);
+    let blocks = graph.blocks_count();
+
+    let backlog = open
+        .iter()
+        .map(|issue| {
+            let mut line = issue_line(issue);
+            line.push_str(&format!(
+                " pagerank={:.3} blocks={}",
+                pagerank.get(&issue.id).copied().unwrap_or(0.0),
+                blocks.get(&issue.id).copied().unwrap_or(0)
+            ));
+            let blocked_by = graph.blocked_by(&issue.id);
+            if !blocked_by.is_empty() {
+                line.push_str(&format!(" blocked_by={}", blocked_by.join(",")));
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+
+    let cycles = graph.find_cycles();
+    vars.insert("open_count", open.len().to_string());
+    vars.insert("ready_count", graph.ready_issues(&open).len().to_string());
+    vars.insert(
+        "backlog",
+        if backlog.is_empty() {
+            "(no open issues)".to_string()
+        } else {
+            backlog.join("\n")
+        },
+    );
+    vars.insert(
+        "cycles",
+        if cycles.is_empty() {
+            String::new()
+        } else {
+            let cycles: Vec<_> = cycles.iter().map(|c| c.join(" -> ")).collect();
+            format!(
+                "\nThe dependency graph has cycles that must be broken: {}\n",
+                cycles.join("; ")
+            )
+        },
+    );
+}
+
+fn plan_epic_vars(
+    store: &UnifiedStore,
+    epic_id: &str,
+    vars: &mut HashMap<&str, String>,
+) -> Result<(), PromptError> {
+    let epic = store
+        .get(epic_id)
+        .ok_or_else(|| PromptError::InvalidParameters(format!("Issue {} not found", epic_id)))?;
+    if epic.issue_type != IssueType::Epic {
+        return Err(PromptError::InvalidParameters(format!(
+            "{} is a {}, not an epic",
+            epic_id, epic.issue_type
+        )));
+    }
+
+    let mut children: Vec<_> = store
+        .list(false)
+        .into_iter()
+        .filter(|i| {
+            i.dependencies
+                .iter()
+                .any(|d| d.dep_type == DependencyType::ParentChild && d.depends_on_id == epic_id)
+        })
+        .collect();
+    children.sort_by(|a, b| a.id.cmp(&b.id));
+
+    vars.insert("epic_id", epic_id.to_string());
+    vars.insert("epic", trx_core::markdown::issue_md(epic));
+    vars.insert("children", bullet_list(&children));
+    Ok(())
+}
+
+fn standup_vars(store: &UnifiedStore, since: DateTime<Utc>, vars: &mut HashMap<&str, String>) {
+    let issues = store.list(false);
+    let mut closed: Vec<_> = issues
+        .iter()
+        .copied()
+        .filter(|i| i.status == Status::Closed && i.closed_at.is_some_and(|t| t >= since))
+        .collect();
+    let mut created: Vec<_> = issues
+        .iter()
+        .copied()
+        .filter(|i| i.created_at >= since && !closed.iter().any(|c| c.id == i.id))
+        .collect();
+    let mut updated: Vec<_> = issues
+        .iter()
+        .copied()
+        .filter(|i| {
+            i.updated_at >= since
+                && !closed.iter().any(|c| c.id == i.id)
+                && !created.iter().any(|c| c.id == i.id)
+        })
+        .collect();
+    for list in [&mut closed, &mut created, &mut updated] {
+        list.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.id.cmp(&b.id)));
+    }
+
+    vars.insert("since", since.format("%Y-%m-%d %H:%M UTC").to_string());
+    vars.insert("closed", bullet_list(&closed));
+    vars.insert("created", bullet_list(&created));
+    vars.insert("updated", bullet_list(&updated));
+}
+
+/// Parse a date, RFC 3339 timestamp or age such as `24h`, `7d` or `2w`
+fn parse_since(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, PromptError> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+    }
+
+    let invalid = || {
+        PromptError::InvalidParameters(format!(
+            "Invalid since '{}': expected YYYY-MM-DD, a timestamp or an age like 24h",
+            value
+        ))
+    };
+    let unit = value.chars().last().ok_or_else(invalid)?;
+    let n: i64 = value[..value.len() - unit.len_utf8()]
+        .parse()
+        .ok()
+        .filter(|n| *n >= 0)
+        .ok_or_else(invalid)?;
+    let age = match unit {
+        'h' => Duration::try_hours(n),
+        'd' => Duration::try_days(n),
+        'w' => Duration::try_weeks(n),
+        _ => return Err(invalid()),
+    };
+    age.and_then(|age| now.checked_sub_signed(age))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_override_and_render() {
+        let dir = tempfile::tempdir().unwrap();
+        let trx_dir = dir.path();
+        std::fs::create_dir_all(trx_dir.join("prompts")).unwrap();
+
+        assert!(template(trx_dir, "triage").unwrap().contains("{{backlog}}"));
+        std::fs::write(
+            trx_dir.join("prompts/triage.md"),
+            "Backlog of {{repo}}: {{backlog}} {{x}}",
+        )
+        .unwrap();
+        let vars = HashMap::from([("repo", "web".to_string()), ("backlog", "none".to_string())]);
+        assert_eq!(
+            render(&template(trx_dir, "triage").unwrap(), &vars),
+            "Backlog of web: none {{x}}"
+        );
+        assert!(template(trx_dir, "missing").is_err());
+    }
+
+    #[test]
+    fn test_render_does_not_rescan_values() {
+        let vars = HashMap::from([
+            ("epic", "Rewrite {{backlog}} {{".to_string()),
+            ("backlog", "none".to_string()),
+        ]);
+        assert_eq!(
+            render("{{epic}}: {{backlog}} {{{backlog}}} {{", &vars),
+            "Rewrite {{backlog}} {{: none {none} {{"
+        );
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = Utc::now();
+        assert_eq!(parse_since("24h", now).unwrap(), now - Duration::hours(24));
+        assert_eq!(parse_since("2w", now).unwrap(), now - Duration::weeks(2));
+        assert_eq!(
+            parse_since("2026-01-02", now).unwrap().to_rfc3339(),
+            "2026-01-02T00:00:00+00:00"
+        );
+        assert!(parse_since("soon", now).is_err());
+        assert!(parse_since("999999999999d", now).is_err());
+        assert!(parse_since("-3d", now).is_err());
+    }
+}
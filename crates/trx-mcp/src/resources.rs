@@ -0,0 +1,273 @@
+//! MCP resources
+//!
+//! Read-only views of the tracker that agents can attach as context:
+//!
+//! - `trx://issues/{id}`: issue JSON plus its rendered markdown
+//! - `trx://issues.md`: the ISSUES.md summary
+//! - `trx://ready`: open issues with no open blockers
+//! - `trx://graph`: dependency graph of open issues
+//! - `trx://config`: the repository's config.toml
+
+use mcp_spec::ResourceContents;
+use mcp_spec::handler::ResourceError;
+use mcp_spec::resource::Resource;
+use serde_json::{Value, json};
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::time::SystemTime;
+use trx_core::{IssueGraph, UnifiedStore};
+
+pub const ISSUES_MD: &str = "trx://issues.md";
+pub const READY: &str = "trx://ready";
+pub const GRAPH: &str = "trx://graph";
+pub const CONFIG: &str = "trx://config";
+const ISSUE_PREFIX: &str = "trx://issues/";
+
+/// URI of a single issue
+pub fn issue_uri(id: &str) -> String {
+    format!("{}{}", ISSUE_PREFIX, id)
+}
+
+fn resource(uri: String, name: String, description: &str, mime_type: &str) -> Resource {
+    Resource {
+        uri,
+        name,
+        description: Some(description.to_string()),
+        mime_type: mime_type.to_string(),
+        annotations: None,
+    }
+}
+
+/// All resources: the fixed views plus one entry per open issue
+pub fn list(store: &UnifiedStore) -> Vec<Resource> {
+    let mut resources = vec![
+        resource(
+            ISSUES_MD.into(),
+            "ISSUES.md".into(),
+            "Summary of open and closed issues",
+            "text/markdown",
+        ),
+        resource(
+            READY.into(),
+            "Ready issues".into(),
+            "Open issues with no open blockers",
+            "application/json",
+        ),
+        resource(
+            GRAPH.into(),
+            "Dependency graph".into(),
+            "Nodes and dependency edges between open issues",
+            "application/json",
+        ),
+        resource(
+            CONFIG.into(),
+            "config.toml".into(),
+            "Repository configuration",
+            "application/toml",
+        ),
+    ];
+
+    let mut open = store.list_open();
+    open.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.id.cmp(&b.id)));
+    resources.extend(open.into_iter().map(|issue| {
+        resource(
+            issue_uri(&issue.id),
+            format!("{}: {}", issue.id, issue.title),
+            "Issue JSON and rendered markdown",
+            "application/json",
+        )
+    }));
+    resources
+}
+
+/// Resource templates, for clients that address issues by ID
+pub fn templates() -> Value {
+    json!({
+        "resourceTemplates": [{
+            "uriTemplate": "trx://issues/{id}",
+            "name": "Issue",
+            "description": "Issue JSON and rendered markdown",
+            "mimeType": "application/json"
+        }]
+    })
+}
+
+fn text(uri: &str, mime_type: &str, text: String) -> ResourceContents {
+    ResourceContents::TextResourceContents {
+        uri: uri.to_string(),
+        mime_type: Some(mime_type.to_string()),
+        text,
+    }
+}
+
+fn to_json(value: &impl serde::Serialize) -> Result<String, ResourceError> {
+    serde_json::to_string_pretty(value).map_err(|e| ResourceError::ExecutionError(e.to_string()))
+}
+
+/// Read the contents of a resource
+pub fn read(store: &UnifiedStore, uri: &str) -> Result<Vec<ResourceContents>, ResourceError> {
+    if let Some(id) = uri.strip_prefix(ISSUE_PREFIX) {
+        let issue = store
+            .get(id)
+            .ok_or_else(|| ResourceError::NotFound(format!("Issue {} not found", id)))?;
+        return Ok(vec![
+            text(uri, "application/json", to_json(issue)?),
+            text(uri, "text/markdown", trx_core::markdown::issue_md(issue)),
+        ]);
+    }
+
+    match uri {
+        ISSUES_MD => Ok(vec![text(uri, "text/markdown", store.issues_md())]),
+        READY => {
+            let open = store.list_open();
+            let ready = IssueGraph::from_issues(&open).ready_issues(&open);
+            let body = json!({ "count": ready.len(), "issues": ready });
+            Ok(vec![text(uri, "application/json", to_json(&body)?)])
+        }
+        GRAPH => {
+            let open = store.list_open();
+            let graph = IssueGraph::from_issues(&open);
+            let nodes: Vec<_> = open
+                .iter()
+                .map(|i| {
+                    json!({
+                        "id": i.id,
+                        "title": i.title,
+                        "status": i.status,
+                        "priority": i.priority,
+                        "issue_type": i.issue_type,
+                    })
+                })
+                .collect();
+            let body = json!({
+                "nodes": nodes,
+                "edges": graph.edges(),
+                "cycles": graph.find_cycles(),
+            });
+            Ok(vec![text(uri, "application/json", to_json(&body)?)])
+        }
+        CONFIG => {
+            let config = std::fs::read_to_string(store.trx_dir().join("config.toml"))
+                .map_err(|e| ResourceError::ExecutionError(e.to_string()))?;
+            Ok(vec![text(uri, "application/toml", config)])
+        }
+        _ => Err(ResourceError::NotFound(uri.to_string())),
+    }
+}
+
+/// State of the store as seen by resource readers, used to work out which
+/// resources changed
+#[derive(Debug, Default, PartialEq)]
+pub struct Snapshot {
+    /// Content hash of every non-deleted issue
+    issues: HashMap<String, u64>,
+    /// IDs of open issues, which are listed individually
+    open: BTreeSet<String>,
+    config_modified: Option<SystemTime>,
+}
+
+/// Resources affected by a change between two snapshots
+#[derive(Debug, Default, PartialEq)]
+pub struct Changes {
+    pub updated: Vec<String>,
+    pub list_changed: bool,
+}
+
+impl Snapshot {
+    pub fn capture(store: &UnifiedStore) -> Self {
+        let issues = store
+            .list(false)
+            .into_iter()
+            .map(|issue| {
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                serde_json::to_string(issue)
+                    .unwrap_or_default()
+                    .hash(&mut hasher);
+                (issue.id.clone(), hasher.finish())
+            })
+            .collect();
+        let open = store
+            .list_open()
+            .into_iter()
+            .map(|i| i.id.clone())
+            .collect();
+        let config_modified = std::fs::metadata(store.trx_dir().join("config.toml"))
+            .and_then(|m| m.modified())
+            .ok();
+
+        Self {
+            issues,
+            open,
+            config_modified,
+        }
+    }
+
+    /// Resources whose contents differ from `previous`
+    pub fn changes_since(&self, previous: &Snapshot) -> Changes {
+        let mut updated: BTreeSet<String> = BTreeSet::new();
+        for (id, hash) in &self.issues {
+            if previous.issues.get(id) != Some(hash) {
+                updated.insert(issue_uri(id));
+            }
+        }
+        for id in previous.issues.keys() {
+            if !self.issues.contains_key(id) {
+                updated.insert(issue_uri(id));
+            }
+        }
+
+        let mut updated: Vec<String> = updated.into_iter().collect();
+        if !updated.is_empty() {
+            updated.extend([ISSUES_MD.into(), READY.into(), GRAPH.into()]);
+        }
+        if self.config_modified != previous.config_modified {
+            updated.push(CONFIG.into());
+        }
+
+        Changes {
+            updated,
+            list_changed: self.open != previous.open,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(issues: &[(&str, u64, bool)]) -> Snapshot {
+        Snapshot {
+            issues: issues
+                .iter()
+                .map(|(id, h, _)| (id.to_string(), *h))
+                .collect(),
+            open: issues
+                .iter()
+                .filter(|(_, _, open)| *open)
+                .map(|(id, _, _)| id.to_string())
+                .collect(),
+            config_modified: None,
+        }
+    }
+
+    #[test]
+    fn test_changes_since() {
+        let before = snapshot(&[("trx-a", 1, true), ("trx-b", 2, true)]);
+        assert_eq!(before.changes_since(&before), Changes::default());
+
+        // Editing an issue touches it and the aggregate views
+        let edited = snapshot(&[("trx-a", 3, true), ("trx-b", 2, true)]);
+        let changes = edited.changes_since(&before);
+        assert_eq!(
+            changes.updated,
+            ["trx://issues/trx-a", ISSUES_MD, READY, GRAPH]
+        );
+        assert!(!changes.list_changed);
+
+        // Closing an issue removes it from the resource list
+        let closed = snapshot(&[("trx-a", 1, true), ("trx-b", 4, false)]);
+        let changes = closed.changes_since(&before);
+        assert_eq!(changes.updated[0], "trx://issues/trx-b");
+        assert!(changes.list_changed);
+    }
+}
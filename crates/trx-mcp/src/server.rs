@@ -0,0 +1,452 @@
+//! Request dispatch shared by the stdio and HTTP transports
+//!
+//! The mcp-server crate only answers requests, so trx-mcp drives the
+//! transports itself. Resource reads, subscriptions, resource templates and
+//! prompts (which take arguments the router never sees) are handled here;
+//! everything else is passed to the router. A background watcher reloads the
+//! store when `.trx/` changes on disk and notifies each session of the
+//! resources it subscribed to.
+
+use crate::TrxMcpRouter;
+use crate::prompts;
+use crate::resources::{self, Snapshot};
+use mcp_server::router::RouterService;
+use mcp_spec::ResourceContents;
+use mcp_spec::handler::{PromptError, ResourceError};
+use mcp_spec::protocol::{
+    ErrorData, INTERNAL_ERROR, INVALID_PARAMS, JsonRpcError, JsonRpcMessage, JsonRpcNotification,
+    JsonRpcRequest, JsonRpcResponse, PARSE_ERROR,
+};
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tower_service::Service;
+use trx_core::UnifiedStore;
+
+/// JSON-RPC error code for unknown resources
+const RESOURCE_NOT_FOUND: i32 = -32002;
+
+/// How often `.trx/` is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Messages queued for a client that is not reading; notifications beyond
+/// this are dropped
+const QUEUE_CAPACITY: usize = 256;
+
+/// Tools that act on behalf of an agent, with the argument naming it
+const AGENT_TOOLS: [(&str, &str); 4] = [
+    ("trx.issues.claim", "agent"),
+    ("trx.issues.heartbeat", "agent"),
+    ("trx.issues.release", "agent"),
+    ("trx.issues.comment", "author"),
+];
+
+/// One connected client: its resource subscriptions, its agent identity
+/// and the channel that carries server-initiated messages to it
+pub struct Session {
+    subscriptions: Mutex<HashSet<String>>,
+    tx: Sender<JsonRpcMessage>,
+    /// Agent identity for claims and comments that name none
+    agent: String,
+    last_seen: Mutex<Instant>,
+}
+
+impl Session {
+    /// Create a session and the receiving end of its message channel
+    pub fn new(agent: String) -> (Arc<Self>, Receiver<JsonRpcMessage>) {
+        let (tx, rx) = tokio::sync::mpsc::channel(QUEUE_CAPACITY);
+        let session = Self {
+            subscriptions: Mutex::default(),
+            tx,
+            agent,
+            last_seen: Mutex::new(Instant::now()),
+        };
+        (Arc::new(session), rx)
+    }
+
+    /// Queue a response for the client, waiting while its queue is full
+    pub async fn send(&self, message: JsonRpcMessage) {
+        self.tx.send(message).await.ok();
+    }
+
+    /// Queue a notification, dropping it if the client has fallen behind
+    pub fn notify(&self, message: JsonRpcMessage) {
+        if let Err(TrySendError::Full(_)) = self.tx.try_send(message) {
+            tracing::debug!("Dropping notification for a client that is not reading");
+        }
+    }
+
+    /// Record activity from the client
+    pub fn touch(&self) {
+        *self.last_seen.lock().unwrap() = Instant::now();
+    }
+
+    /// Time since the client was last active
+    pub fn idle(&self) -> Duration {
+        self.last_seen.lock().unwrap().elapsed()
+    }
+}
+
+/// Live sessions by ID, shared with the watcher
+pub type Sessions = Arc<Mutex<HashMap<String, Arc<Session>>>>;
+
+/// Error message for a line or body that isn't valid JSON-RPC
+pub fn parse_error(message: String) -> JsonRpcMessage {
+    JsonRpcMessage::Error(JsonRpcError {
+        jsonrpc: "2.0".into(),
+        id: None,
+        error: ErrorData {
+            code: PARSE_ERROR,
+            message,
+            data: None,
+        },
+    })
+}
+
+/// Answer a request on behalf of a session
+///
+/// Claim and comment tools called without naming an agent act as the
+/// session's agent, so sessions sharing one HTTP server never share an
+/// identity.
+pub async fn handle(
+    router: &TrxMcpRouter,
+    session: &Session,
+    mut request: JsonRpcRequest,
+) -> JsonRpcResponse {
+    if request.method == "tools/call"
+        && let Some(params) = request.params.as_mut()
+    {
+        fill_agent(params, &session.agent);
+    }
+    let id = request.id;
+    let result = match request.method.as_str() {
+        "resources/read" => read_resource(router, request.params),
+        "resources/subscribe" => subscribe(session, request.params, true),
+        "resources/unsubscribe" => subscribe(session, request.params, false),
+        "resources/templates/list" => Ok(resources::templates()),
+        "prompts/get" => get_prompt(router, request.params),
+        "ping" => Ok(json!({})),
+        _ => {
+            return match RouterService(router.clone()).call(request).await {
+                Ok(response) => response,
+                Err(e) => error_response(id, INTERNAL_ERROR, e.to_string()),
+            };
+        }
+    };
+
+    match result {
+        Ok(value) => JsonRpcResponse {
+            jsonrpc: "2.0".into(),
+            id,
+            result: Some(value),
+            error: None,
+        },
+        Err(error) => JsonRpcResponse {
+            jsonrpc: "2.0".into(),
+            id,
+            result: None,
+            error: Some(error),
+        },
+    }
+}
+
+/// Set the agent argument of an agent tool call that names none
+fn fill_agent(params: &mut Value, agent: &str) {
+    let field = params.get("name").and_then(Value::as_str).and_then(|name| {
+        AGENT_TOOLS
+            .iter()
+            .find(|(tool, _)| *tool == name)
+            .map(|(_, field)| *field)
+    });
+    let Some((params, field)) = params.as_object_mut().zip(field) else {
+        return;
+    };
+    let arguments = params
+        .entry("arguments")
+        .and_modify(|a| {
+            if a.is_null() {
+                *a = json!({});
+            }
+        })
+        .or_insert_with(|| json!({}));
+    if let Value::Object(arguments) = arguments {
+        let named = arguments
+            .get(field)
+            .and_then(Value::as_str)
+            .is_some_and(|a| !a.trim().is_empty());
+        if !named {
+            arguments.insert(field.into(), agent.into());
+        }
+    }
+}
+
+/// Serve MCP over stdin/stdout until stdin closes
+///
+/// The one session is this process's agent, identified by `TRX_AGENT` or
+/// the process ID.
+pub async fn run(router: TrxMcpRouter) -> anyhow::Result<()> {
+    let (session, mut rx) = Session::new(crate::agent_name(None));
+    let sessions: Sessions = Arc::default();
+    sessions
+        .lock()
+        .unwrap()
+        .insert("stdio".into(), session.clone());
+
+    // Single writer so responses and notifications never interleave
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(message) = rx.recv().await {
+            let Ok(line) = serde_json::to_string(&message) else {
+                continue;
+            };
+            if stdout.write_all(line.as_bytes()).await.is_err()
+                || stdout.write_all(b"\n").await.is_err()
+                || stdout.flush().await.is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    let watcher = tokio::spawn(watch(router.clone(), sessions.clone()));
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<JsonRpcMessage>(&line) {
+            Ok(JsonRpcMessage::Request(request)) => {
+                let response = handle(&router, &session, request).await;
+                session.send(JsonRpcMessage::Response(response)).await;
+            }
+            Ok(_) => {} // Notifications and responses need no reply
+            Err(e) => session.send(parse_error(e.to_string())).await,
+        }
+    }
+
+    watcher.abort();
+    sessions.lock().unwrap().clear();
+    drop(session);
+    writer.await.ok();
+    Ok(())
+}
+
+fn error_response(id: Option<u64>, code: i32, message: String) -> JsonRpcResponse {
+    JsonRpcResponse {
+        jsonrpc: "2.0".into(),
+        id,
+        result: None,
+        error: Some(ErrorData {
+            code,
+            message,
+            data: None,
+        }),
+    }
+}
+
+fn uri_param(params: Option<Value>) -> Result<String, ErrorData> {
+    params
+        .as_ref()
+        .and_then(|p| p.get("uri"))
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or_else(|| ErrorData {
+            code: INVALID_PARAMS,
+            message: "Missing resource URI".into(),
+            data: None,
+        })
+}
+
+fn read_resource(router: &TrxMcpRouter, params: Option<Value>) -> Result<Value, ErrorData> {
+    let uri = uri_param(params)?;
+    let store = router.inner.store.read().map_err(|e| ErrorData {
+        code: INTERNAL_ERROR,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    match resources::read(&store, &uri) {
+        Ok(contents) => {
+            // Built by hand: mcp-spec serializes `mime_type` in snake_case
+            let contents: Vec<Value> = contents
+                .into_iter()
+                .map(|content| match content {
+                    ResourceContents::TextResourceContents {
+                        uri,
+                        mime_type,
+                        text,
+                    } => json!({ "uri": uri, "mimeType": mime_type, "text": text }),
+                    ResourceContents::BlobResourceContents {
+                        uri,
+                        mime_type,
+                        blob,
+                    } => json!({ "uri": uri, "mimeType": mime_type, "blob": blob }),
+                })
+                .collect();
+            Ok(json!({ "contents": contents }))
+        }
+        Err(ResourceError::NotFound(message)) => Err(ErrorData {
+            code: RESOURCE_NOT_FOUND,
+            message: format!("Resource not found: {}", message),
+            data: Some(json!({ "uri": uri })),
+        }),
+        Err(e) => Err(ErrorData {
+            code: INTERNAL_ERROR,
+            message: e.to_string(),
+            data: None,
+        }),
+    }
+}
+
+fn get_prompt(router: &TrxMcpRouter, params: Option<Value>) -> Result<Value, ErrorData> {
+    let invalid = |message: String| ErrorData {
+        code: INVALID_PARAMS,
+        message,
+        data: None,
+    };
+    let params = params.unwrap_or_default();
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid("Missing prompt name".into()))?;
+    let arguments = match params.get("arguments") {
+        None | Some(Value::Null) => Default::default(),
+        Some(Value::Object(arguments)) => arguments.clone(),
+        Some(_) => return Err(invalid("Prompt arguments must be an object".into())),
+    };
+
+    let store = router.inner.store.read().map_err(|e| ErrorData {
+        code: INTERNAL_ERROR,
+        message: e.to_string(),
+        data: None,
+    })?;
+    let repo = router
+        .inner
+        .root_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| router.inner.root_path.display().to_string());
+
+    prompts::get(&store, &repo, name, &arguments).map_err(|e| match e {
+        PromptError::NotFound(name) => invalid(format!("Prompt not found: {}", name)),
+        PromptError::InvalidParameters(message) => invalid(message),
+        e => ErrorData {
+            code: INTERNAL_ERROR,
+            message: e.to_string(),
+            data: None,
+        },
+    })
+}
+
+fn subscribe(
+    session: &Session,
+    params: Option<Value>,
+    subscribe: bool,
+) -> Result<Value, ErrorData> {
+    let uri = uri_param(params)?;
+    let mut subscriptions = session.subscriptions.lock().unwrap();
+    if subscribe {
+        subscriptions.insert(uri);
+    } else {
+        subscriptions.remove(&uri);
+    }
+    Ok(json!({}))
+}
+
+fn notification(method: &str, params: Option<Value>) -> JsonRpcMessage {
+    JsonRpcMessage::Notification(JsonRpcNotification {
+        jsonrpc: "2.0".into(),
+        method: method.into(),
+        params,
+    })
+}
+
+/// Cheap fingerprint of every file under a directory: count, total size and
+/// latest modification time
+fn fingerprint(dir: &Path) -> (usize, u64, Option<SystemTime>) {
+    let mut result = (0, 0, None);
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+                continue;
+            }
+            result.0 += 1;
+            result.1 += metadata.len();
+            result.2 = result.2.max(metadata.modified().ok());
+        }
+    }
+    result
+}
+
+/// Reload the store when `.trx/` changes and notify subscribers
+pub async fn watch(router: TrxMcpRouter, sessions: Sessions) {
+    let root = router.inner.root_path.clone();
+    let trx_dir = root.join(".trx");
+    let mut last_fingerprint = fingerprint(&trx_dir);
+    let mut snapshot = match router.inner.store.read() {
+        Ok(store) => Snapshot::capture(&store),
+        Err(_) => return,
+    };
+
+    let mut interval = tokio::time::interval(WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let current = fingerprint(&trx_dir);
+        if current == last_fingerprint {
+            continue;
+        }
+        last_fingerprint = current;
+
+        let reloaded = match UnifiedStore::open_at(&root) {
+            Ok(store) => store,
+            Err(e) => {
+                tracing::warn!("Failed to reload store: {}", e);
+                continue;
+            }
+        };
+        let next = Snapshot::capture(&reloaded);
+        match router.inner.store.write() {
+            Ok(mut store) => *store = reloaded,
+            Err(_) => return,
+        }
+
+        let changes = next.changes_since(&snapshot);
+        snapshot = next;
+
+        let sessions: Vec<Arc<Session>> = sessions.lock().unwrap().values().cloned().collect();
+        for session in sessions {
+            let subscribed: Vec<String> = {
+                let subscriptions = session.subscriptions.lock().unwrap();
+                changes
+                    .updated
+                    .iter()
+                    .filter(|uri| subscriptions.contains(*uri))
+                    .cloned()
+                    .collect()
+            };
+            for uri in subscribed {
+                session.notify(notification(
+                    "notifications/resources/updated",
+                    Some(json!({ "uri": uri })),
+                ));
+            }
+            if changes.list_changed {
+                session.notify(notification("notifications/resources/list_changed", None));
+            }
+        }
+    }
+}
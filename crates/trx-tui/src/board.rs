@@ -0,0 +1,345 @@
+//! Kanban board view
+//!
+//! Groups the filtered issues into columns by status, assignee or label.
+//! The board reads the same filtered list as the issue list, so filters and
+//! search apply to both, and the cursor is the list's selection index. An
+//! issue with several labels shows up in each of their columns; `column`
+//! records which one the cursor is in.
+
+use crate::theme::Theme;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use trx_core::{Issue, Status};
+
+/// Height of a card, borders included
+const CARD_HEIGHT: u16 = 4;
+
+/// Statuses shown as columns, in workflow order
+pub const STATUS_COLUMNS: [Status; 4] = [
+    Status::Open,
+    Status::InProgress,
+    Status::Blocked,
+    Status::Closed,
+];
+
+/// What the columns are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    Status,
+    Assignee,
+    Label,
+}
+
+impl Grouping {
+    pub fn next(self) -> Self {
+        match self {
+            Grouping::Status => Grouping::Assignee,
+            Grouping::Assignee => Grouping::Label,
+            Grouping::Label => Grouping::Status,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Grouping::Status => "status",
+            Grouping::Assignee => "assignee",
+            Grouping::Label => "label",
+        }
+    }
+}
+
+/// A column and the indices of its issues
+pub struct Column {
+    pub title: String,
+    pub cards: Vec<usize>,
+}
+
+/// Split issues into columns, keeping their order within each
+///
+/// Grouped by status, every column in `STATUS_COLUMNS` is present even when
+/// empty so cards have somewhere to move. Assignee and label columns are
+/// sorted by name, with unassigned or unlabelled issues last.
+pub fn columns(issues: &[&Issue], grouping: Grouping) -> Vec<Column> {
+    let mut columns: Vec<Column> = Vec::new();
+    let push = |columns: &mut Vec<Column>, title: &str, idx: usize| match columns
+        .iter_mut()
+        .find(|c| c.title == title)
+    {
+        Some(column) => column.cards.push(idx),
+        None => columns.push(Column {
+            title: title.to_string(),
+            cards: vec![idx],
+        }),
+    };
+
+    match grouping {
+        Grouping::Status => {
+            return STATUS_COLUMNS
+                .iter()
+                .map(|status| Column {
+                    title: status.to_string(),
+                    cards: (0..issues.len())
+                        .filter(|&i| issues[i].status == *status)
+                        .collect(),
+                })
+                .collect();
+        }
+        Grouping::Assignee => {
+            for (idx, issue) in issues.iter().enumerate() {
+                push(&mut columns, issue.assignee.as_deref().unwrap_or(""), idx);
+            }
+        }
+        Grouping::Label => {
+            for (idx, issue) in issues.iter().enumerate() {
+                if issue.labels.is_empty() {
+                    push(&mut columns, "", idx);
+                }
+                for label in &issue.labels {
+                    push(&mut columns, label, idx);
+                }
+            }
+        }
+    }
+
+    columns.sort_by(|a, b| {
+        a.title
+            .is_empty()
+            .cmp(&b.title.is_empty())
+            .then_with(|| a.title.cmp(&b.title))
+    });
+    for column in &mut columns {
+        if column.title.is_empty() {
+            column.title = match grouping {
+                Grouping::Assignee => "unassigned".to_string(),
+                _ => "no label".to_string(),
+            };
+        }
+    }
+    columns
+}
+
+/// Grouping and cursor column of the board
+pub struct BoardState {
+    pub grouping: Grouping,
+    pub column: usize,
+}
+
+impl BoardState {
+    pub fn new() -> Self {
+        Self {
+            grouping: Grouping::Status,
+            column: 0,
+        }
+    }
+
+    /// Put the cursor column on one holding `selected`, staying put if the
+    /// current column already does
+    pub fn follow(&mut self, columns: &[Column], selected: usize) {
+        if columns
+            .get(self.column)
+            .is_some_and(|c| c.cards.contains(&selected))
+        {
+            return;
+        }
+        if let Some(col) = columns.iter().position(|c| c.cards.contains(&selected)) {
+            self.column = col;
+        } else {
+            self.column = self.column.min(columns.len().saturating_sub(1));
+        }
+    }
+
+    /// Move the cursor within its column, returning the newly selected issue
+    pub fn move_vertical(&self, columns: &[Column], selected: usize, down: bool) -> Option<usize> {
+        let cards = &columns.get(self.column)?.cards;
+        let row = cards.iter().position(|&c| c == selected)?;
+        let row = if down {
+            (row + 1).min(cards.len() - 1)
+        } else {
+            row.saturating_sub(1)
+        };
+        Some(cards[row])
+    }
+
+    /// Move the cursor to the nearest non-empty column on one side, keeping
+    /// roughly the same row
+    pub fn move_horizontal(
+        &mut self,
+        columns: &[Column],
+        selected: usize,
+        right: bool,
+    ) -> Option<usize> {
+        let row = columns
+            .get(self.column)
+            .and_then(|c| c.cards.iter().position(|&i| i == selected))
+            .unwrap_or(0);
+        let mut col = self.column;
+        loop {
+            col = if right { col + 1 } else { col.checked_sub(1)? };
+            let cards = &columns.get(col)?.cards;
+            if !cards.is_empty() {
+                self.column = col;
+                return Some(cards[row.min(cards.len() - 1)]);
+            }
+        }
+    }
+}
+
+/// Draw the board, keeping the cursor card in view
+pub fn render(
+    f: &mut Frame,
+    area: Rect,
+    issues: &[&Issue],
+    board: &BoardState,
+    selected: usize,
+    theme: &Theme,
+) {
+    let outer = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.border)
+        .title(format!(
+            "Board by {} ({} issues)",
+            board.grouping.name(),
+            issues.len()
+        ));
+    let inner = outer.inner(area);
+    f.render_widget(outer, area);
+
+    let columns = columns(issues, board.grouping);
+    if columns.is_empty() {
+        f.render_widget(Paragraph::new("No issues"), inner);
+        return;
+    }
+
+    let areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Ratio(1, columns.len() as u32);
+            columns.len()
+        ])
+        .split(inner);
+
+    for (col, (column, area)) in columns.iter().zip(areas.iter()).enumerate() {
+        let is_cursor_column = col == board.column;
+        let title_style = if is_cursor_column {
+            theme.accent.add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.muted)
+            .title(Span::styled(
+                format!("{} ({})", column.title, column.cards.len()),
+                title_style,
+            ));
+        let cards_area = block.inner(*area);
+        f.render_widget(block, *area);
+
+        // Scroll so the cursor card is on screen
+        let visible = (cards_area.height / CARD_HEIGHT).max(1) as usize;
+        let first = match column.cards.iter().position(|&i| i == selected) {
+            Some(row) if is_cursor_column => (row + 1).saturating_sub(visible),
+            _ => 0,
+        };
+
+        for (n, &idx) in column.cards.iter().skip(first).take(visible).enumerate() {
+            let card = Rect {
+                x: cards_area.x,
+                y: cards_area.y + n as u16 * CARD_HEIGHT,
+                width: cards_area.width,
+                height: CARD_HEIGHT,
+            };
+            render_card(
+                f,
+                card,
+                issues[idx],
+                is_cursor_column && idx == selected,
+                theme,
+            );
+        }
+    }
+}
+
+fn render_card(f: &mut Frame, area: Rect, issue: &Issue, is_cursor: bool, theme: &Theme) {
+    let border_style = if is_cursor {
+        theme.accent.add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+
+    let lines = vec![
+        Line::from(vec![
+            Span::styled(issue.id.clone(), theme.accent),
+            Span::raw(" "),
+            Span::styled(
+                format!("P{}", issue.priority),
+                theme.priority(issue.priority).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" "),
+            Span::styled(
+                issue.issue_type.to_string(),
+                theme.issue_type(issue.issue_type),
+            ),
+        ]),
+        Line::from(issue.title.clone()),
+    ];
+    let card = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+    f.render_widget(card, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(id: &str, status: Status, assignee: Option<&str>, labels: &[&str]) -> Issue {
+        let mut issue = Issue::new(id.into(), id.into());
+        issue.status = status;
+        issue.assignee = assignee.map(String::from);
+        issue.labels = labels.iter().map(|l| l.to_string()).collect();
+        issue
+    }
+
+    #[test]
+    fn test_columns_and_navigation() {
+        let a = issue("a", Status::Open, Some("sam"), &["ui", "api"]);
+        let b = issue("b", Status::Open, None, &[]);
+        let c = issue("c", Status::Blocked, Some("alex"), &["api"]);
+        let issues = [&a, &b, &c];
+
+        let by_status = columns(&issues, Grouping::Status);
+        let titles: Vec<_> = by_status.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["open", "in_progress", "blocked", "closed"]);
+        assert_eq!(by_status[0].cards, [0, 1]);
+        assert_eq!(by_status[2].cards, [2]);
+
+        let by_assignee = columns(&issues, Grouping::Assignee);
+        let titles: Vec<_> = by_assignee.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["alex", "sam", "unassigned"]);
+
+        let by_label = columns(&issues, Grouping::Label);
+        let titles: Vec<_> = by_label.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, ["api", "ui", "no label"]);
+        assert_eq!(by_label[0].cards, [0, 2]);
+
+        // Moving right skips the empty in_progress column
+        let mut board = BoardState::new();
+        assert_eq!(board.move_vertical(&by_status, 0, true), Some(1));
+        assert_eq!(board.move_horizontal(&by_status, 1, true), Some(2));
+        assert_eq!(board.column, 2);
+        assert_eq!(board.move_horizontal(&by_status, 2, true), None);
+
+        // Following an issue into a column that holds it
+        board.follow(&by_label, 0);
+        assert_eq!(board.column, 0);
+        board.column = 1;
+        board.follow(&by_label, 0);
+        assert_eq!(board.column, 1);
+    }
+}
@@ -0,0 +1,181 @@
+//! Bulk actions on the selected issues
+//!
+//! An action is picked from a menu, confirmed, then applied to every
+//! selected issue and saved with one write per repository. The before and
+//! after copies are kept as a single undo step.
+
+use trx_core::{Issue, Status};
+
+/// A change applied to every selected issue
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkAction {
+    Close,
+    Status(Status),
+    Priority(u8),
+    AddLabel(String),
+    RemoveLabel(String),
+    /// Assign, or unassign with `None`
+    Assign(Option<String>),
+    Delete,
+}
+
+impl BulkAction {
+    /// Verb phrase for prompts, e.g. "set priority P1 on"
+    pub fn describe(&self) -> String {
+        match self {
+            BulkAction::Close => "close".to_string(),
+            BulkAction::Status(status) => format!("set status {} on", status),
+            BulkAction::Priority(p) => format!("set priority P{} on", p),
+            BulkAction::AddLabel(label) => format!("add label '{}' to", label),
+            BulkAction::RemoveLabel(label) => format!("remove label '{}' from", label),
+            BulkAction::Assign(Some(who)) => format!("assign {} to", who),
+            BulkAction::Assign(None) => "unassign".to_string(),
+            BulkAction::Delete => "delete".to_string(),
+        }
+    }
+
+    /// Apply the action to a copy of `issue`, or `None` if it would not
+    /// change anything
+    pub fn apply(&self, issue: &Issue, user: Option<&str>) -> Option<Issue> {
+        let mut updated = issue.clone();
+        match self {
+            BulkAction::Close => {
+                if issue.status == Status::Closed {
+                    return None;
+                }
+                updated.close(None);
+            }
+            BulkAction::Status(status) => {
+                if issue.status == *status {
+                    return None;
+                }
+                if *status == Status::Closed {
+                    updated.close(None);
+                } else {
+                    updated.status = *status;
+                }
+                if status.is_open() {
+                    updated.closed_at = None;
+                    updated.close_reason = None;
+                }
+            }
+            BulkAction::Priority(p) => {
+                if issue.priority == *p {
+                    return None;
+                }
+                updated.priority = *p;
+            }
+            BulkAction::AddLabel(label) => {
+                if issue.labels.contains(label) {
+                    return None;
+                }
+                updated.labels.push(label.clone());
+            }
+            BulkAction::RemoveLabel(label) => {
+                if !issue.labels.contains(label) {
+                    return None;
+                }
+                updated.labels.retain(|l| l != label);
+            }
+            BulkAction::Assign(who) => {
+                if issue.assignee == *who {
+                    return None;
+                }
+                updated.assignee = who.clone();
+            }
+            BulkAction::Delete => {
+                updated.delete(user.map(String::from), None);
+            }
+        }
+        updated.updated_at = chrono::Utc::now();
+        Some(updated)
+    }
+}
+
+/// Text a bulk action asks for before confirming
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkInput {
+    AddLabel,
+    RemoveLabel,
+    Assign,
+}
+
+impl BulkInput {
+    pub fn prompt(self) -> &'static str {
+        match self {
+            BulkInput::AddLabel => "Label to add",
+            BulkInput::RemoveLabel => "Label to remove",
+            BulkInput::Assign => "Assignee (empty to unassign)",
+        }
+    }
+
+    /// The action for the entered text, if it is acceptable
+    pub fn action(self, text: &str) -> Option<BulkAction> {
+        let text = text.trim();
+        match self {
+            BulkInput::Assign => Some(BulkAction::Assign(
+                (!text.is_empty()).then(|| text.to_string()),
+            )),
+            _ if text.is_empty() => None,
+            BulkInput::AddLabel => Some(BulkAction::AddLabel(text.to_string())),
+            BulkInput::RemoveLabel => Some(BulkAction::RemoveLabel(text.to_string())),
+        }
+    }
+}
+
+/// Where the user is in choosing a bulk action
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkStep {
+    Menu,
+    Priority,
+    Input(BulkInput, String),
+    Confirm(BulkAction),
+}
+
+/// One applied bulk action: each issue's repository, before and after
+pub struct UndoStep {
+    pub description: String,
+    pub changes: Vec<(usize, Issue, Issue)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let mut issue = Issue::new("trx-a".into(), "A".into());
+        issue.labels = vec!["ui".into()];
+
+        assert!(
+            BulkAction::AddLabel("ui".into())
+                .apply(&issue, None)
+                .is_none()
+        );
+        let labelled = BulkAction::AddLabel("api".into())
+            .apply(&issue, None)
+            .unwrap();
+        assert_eq!(labelled.labels, ["ui", "api"]);
+        let unlabelled = BulkAction::RemoveLabel("ui".into())
+            .apply(&issue, None)
+            .unwrap();
+        assert!(unlabelled.labels.is_empty());
+
+        let closed = BulkAction::Close.apply(&issue, None).unwrap();
+        assert!(closed.closed_at.is_some());
+        assert!(BulkAction::Close.apply(&closed, None).is_none());
+        let reopened = BulkAction::Status(Status::Open)
+            .apply(&closed, None)
+            .unwrap();
+        assert_eq!(reopened.closed_at, None);
+        let deleted = BulkAction::Delete.apply(&issue, Some("sam")).unwrap();
+        assert_eq!(deleted.status, Status::Tombstone);
+        assert_eq!(deleted.deleted_by.as_deref(), Some("sam"));
+
+        assert_eq!(
+            BulkInput::Assign.action(" "),
+            Some(BulkAction::Assign(None))
+        );
+        assert_eq!(BulkInput::AddLabel.action(""), None);
+    }
+}
@@ -0,0 +1,142 @@
+//! Dependency state of the issues in view
+//!
+//! Rebuilt from an `IssueGraph` over every issue on each refresh, so the
+//! ready and blocked filters agree with `trx ready` and the detail pane can
+//! list an issue's open blockers and the issues waiting on it.
+
+use std::collections::{HashMap, HashSet};
+use trx_core::{Issue, IssueGraph};
+
+#[derive(Default)]
+pub struct Dependencies {
+    /// Open issues with no open blockers and no live claim
+    ready: HashSet<String>,
+    /// Open issues waiting on at least one open blocker
+    blocked: HashSet<String>,
+    /// Blocked issues with a blocker that is itself blocked
+    transitively_blocked: HashSet<String>,
+    /// Open blockers of each issue
+    blockers: HashMap<String, Vec<String>>,
+    /// Open issues each issue blocks
+    unblocks: HashMap<String, Vec<String>>,
+    /// Open issues anywhere up each issue's blocker chain
+    upstream: HashMap<String, usize>,
+    titles: HashMap<String, String>,
+}
+
+impl Dependencies {
+    /// Work out the dependency state of `issues`, closed ones included
+    pub fn new(issues: &[&Issue]) -> Self {
+        let graph = IssueGraph::from_issues(issues);
+        let open: HashSet<&str> = issues
+            .iter()
+            .filter(|i| i.status.is_open())
+            .map(|i| i.id.as_str())
+            .collect();
+
+        let mut deps = Self {
+            ready: graph
+                .ready_issues(issues)
+                .into_iter()
+                .map(|i| i.id.clone())
+                .collect(),
+            ..Self::default()
+        };
+        for issue in issues.iter().filter(|i| i.status.is_open()) {
+            deps.titles.insert(issue.id.clone(), issue.title.clone());
+            let blockers: Vec<String> = issue
+                .blocking_ids()
+                .into_iter()
+                .filter(|id| open.contains(id))
+                .map(String::from)
+                .collect();
+            if blockers.is_empty() {
+                continue;
+            }
+            for blocker in &blockers {
+                deps.unblocks
+                    .entry(blocker.clone())
+                    .or_default()
+                    .push(issue.id.clone());
+            }
+            let upstream = graph
+                .transitive_blockers(&issue.id)
+                .iter()
+                .filter(|id| open.contains(id.as_str()))
+                .count();
+            deps.upstream.insert(issue.id.clone(), upstream);
+            deps.blocked.insert(issue.id.clone());
+            deps.blockers.insert(issue.id.clone(), blockers);
+        }
+
+        deps.transitively_blocked = deps
+            .blockers
+            .iter()
+            .filter(|(_, blockers)| blockers.iter().any(|b| deps.blocked.contains(b)))
+            .map(|(id, _)| id.clone())
+            .collect();
+        deps
+    }
+
+    pub fn is_ready(&self, id: &str) -> bool {
+        self.ready.contains(id)
+    }
+
+    pub fn is_blocked(&self, id: &str) -> bool {
+        self.blocked.contains(id)
+    }
+
+    pub fn is_transitively_blocked(&self, id: &str) -> bool {
+        self.transitively_blocked.contains(id)
+    }
+
+    pub fn blockers(&self, id: &str) -> &[String] {
+        self.blockers.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn unblocks(&self, id: &str) -> &[String] {
+        self.unblocks.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Number of open issues anywhere up the blocker chain
+    pub fn upstream(&self, id: &str) -> usize {
+        self.upstream.get(id).copied().unwrap_or(0)
+    }
+
+    pub fn title(&self, id: &str) -> Option<&str> {
+        self.titles.get(id).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trx_core::DependencyType;
+
+    #[test]
+    fn test_ready_and_blocked() {
+        // c waits on b, b waits on a; d waits on a closed issue
+        let a = Issue::new("trx-a".into(), "A".into());
+        let mut b = Issue::new("trx-b".into(), "B".into());
+        b.add_dependency("trx-a".into(), DependencyType::Blocks);
+        let mut c = Issue::new("trx-c".into(), "C".into());
+        c.add_dependency("trx-b".into(), DependencyType::Blocks);
+        let mut done = Issue::new("trx-done".into(), "Done".into());
+        done.close(None);
+        let mut d = Issue::new("trx-d".into(), "D".into());
+        d.add_dependency("trx-done".into(), DependencyType::Blocks);
+
+        let deps = Dependencies::new(&[&a, &b, &c, &done, &d]);
+        assert!(deps.is_ready("trx-a") && deps.is_ready("trx-d"));
+        assert!(!deps.is_ready("trx-b") && !deps.is_ready("trx-done"));
+        assert!(deps.is_blocked("trx-b") && deps.is_blocked("trx-c"));
+        assert!(!deps.is_blocked("trx-d"));
+        assert!(deps.is_transitively_blocked("trx-c"));
+        assert!(!deps.is_transitively_blocked("trx-b"));
+
+        assert_eq!(deps.blockers("trx-c"), ["trx-b"]);
+        assert_eq!(deps.unblocks("trx-a"), ["trx-b"]);
+        assert!(deps.unblocks("trx-done").is_empty());
+        assert_eq!(deps.upstream("trx-c"), 2);
+    }
+}
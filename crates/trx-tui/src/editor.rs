@@ -0,0 +1,104 @@
+//! Editing long text in an external editor
+//!
+//! The form only takes single lines, so descriptions, notes and comments go
+//! through the user's editor on a temporary markdown file. The event loop
+//! leaves the alternate screen while the editor runs.
+
+use anyhow::{Context, Result, bail};
+use std::io::Write;
+use trx_core::Issue;
+
+/// Issue text that can be edited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditField {
+    Description,
+    Notes,
+    /// A new comment, starting empty
+    Comment,
+}
+
+impl EditField {
+    pub fn name(self) -> &'static str {
+        match self {
+            EditField::Description => "description",
+            EditField::Notes => "notes",
+            EditField::Comment => "comment",
+        }
+    }
+
+    /// Current text of the field
+    pub fn text(self, issue: &Issue) -> String {
+        match self {
+            EditField::Description => issue.description.clone().unwrap_or_default(),
+            EditField::Notes => issue.notes.clone().unwrap_or_default(),
+            EditField::Comment => String::new(),
+        }
+    }
+}
+
+/// An edit waiting for the event loop to hand over the terminal
+pub struct EditRequest {
+    pub repo: usize,
+    /// The issue as it was when the edit was asked for
+    pub base: Issue,
+    pub field: EditField,
+    pub command: Vec<String>,
+}
+
+/// Editor command line: the configured editor, then `$VISUAL`, then
+/// `$EDITOR`, then vi, skipping any that are blank
+pub fn editor_command(configured: Option<&str>) -> Vec<String> {
+    let set = |e: &String| !e.trim().is_empty();
+    let editor = configured
+        .map(String::from)
+        .filter(set)
+        .or_else(|| std::env::var("VISUAL").ok().filter(set))
+        .or_else(|| std::env::var("EDITOR").ok().filter(set))
+        .unwrap_or_else(|| "vi".to_string());
+    editor.split_whitespace().map(String::from).collect()
+}
+
+/// Open `text` in the editor and return what was saved
+pub fn edit_text(command: &[String], text: &str) -> Result<String> {
+    let Some((program, args)) = command.split_first() else {
+        bail!("No editor configured");
+    };
+    let mut file = tempfile::Builder::new()
+        .prefix("trx-")
+        .suffix(".md")
+        .tempfile()?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+
+    let status = std::process::Command::new(program)
+        .args(args)
+        .arg(file.path())
+        .status()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !status.success() {
+        bail!("Editor exited with non-zero status");
+    }
+    Ok(std::fs::read_to_string(file.path())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_text() {
+        // A "editor" that appends a line to the file it is given
+        let command: Vec<String> = ["sh", "-c", "echo '- item' >> \"$0\""]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let edited = edit_text(&command, "# Notes\n").unwrap();
+        assert_eq!(edited, "# Notes\n- item\n");
+
+        let failing = vec!["false".to_string()];
+        assert!(edit_text(&failing, "").is_err());
+        assert_eq!(editor_command(Some("code --wait")), ["code", "--wait"]);
+        // A blank setting falls through to the environment or vi
+        assert!(!editor_command(Some("  ")).is_empty());
+    }
+}
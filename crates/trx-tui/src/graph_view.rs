@@ -0,0 +1,804 @@
+//! Dependency graph view
+//!
+//! Lays out `blocks` dependencies as a layered DAG, either around one issue
+//! or across the whole open backlog. Blockers sit left of the issues they
+//! block. Edges that skip layers run through placeholder slots so they never
+//! cross a box, and edges that close a cycle run back along a lane above the
+//! graph. Issues in a cycle are drawn in red and the critical path in yellow.
+
+use crate::theme::Theme;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::collections::{HashMap, HashSet, VecDeque};
+use trx_core::{DependencyType, Issue, IssueGraph, Status};
+
+/// How many `blocks` hops around the focused issue a neighbourhood covers
+const NEIGHBOURHOOD_DEPTH: usize = 2;
+
+const BOX_WIDTH: usize = 24;
+const BOX_HEIGHT: usize = 4;
+const ROW_PITCH: usize = BOX_HEIGHT + 1;
+/// Gap between layers, where edges are routed
+const CHANNEL: usize = 6;
+const COL_PITCH: usize = BOX_WIDTH + CHANNEL;
+/// Room left of the first layer for edges coming back from the lane
+const MARGIN: usize = 3;
+
+/// Which issues the graph shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    /// Issues within a few hops of one issue
+    Neighbourhood(String),
+    /// Every open issue
+    Backlog,
+}
+
+/// What occupies a position in a layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Node(usize),
+    /// A long edge passing through the layer
+    Dummy,
+}
+
+/// An edge as a path of (layer, slot) positions: source, target, path and
+/// whether it points back up against the layering
+type Chain = (usize, usize, Vec<(usize, usize)>, bool);
+
+struct Node {
+    id: String,
+    title: String,
+    status: Status,
+    priority: u8,
+    layer: usize,
+}
+
+/// A `blocks` edge drawn from the blocker to the blocked issue
+struct Edge {
+    from: usize,
+    to: usize,
+    /// (layer, position) of every slot the edge passes, endpoints included
+    path: Vec<(usize, usize)>,
+    /// Closes a cycle, so it runs right to left
+    back: bool,
+}
+
+pub struct GraphView {
+    pub scope: Scope,
+    nodes: Vec<Node>,
+    layers: Vec<Vec<Slot>>,
+    edges: Vec<Edge>,
+    cycle: HashSet<usize>,
+    critical: Vec<usize>,
+    cursor: Option<usize>,
+    /// Issue picked as the blocker for the next edge toggle
+    pub marked: Option<String>,
+}
+
+impl GraphView {
+    /// Lay out the issues in `scope`, with the cursor on `focus` if shown
+    ///
+    /// `issues` should hold every issue that may appear, closed ones
+    /// included; the backlog scope keeps only open issues.
+    pub fn build(issues: &[&Issue], scope: Scope, focus: Option<&str>) -> Self {
+        let selected = select(issues, &scope);
+        let index: HashMap<&str, usize> = selected
+            .iter()
+            .enumerate()
+            .map(|(i, issue)| (issue.id.as_str(), i))
+            .collect();
+
+        // Edges point from blocker to blocked issue
+        let mut out: Vec<Vec<usize>> = vec![Vec::new(); selected.len()];
+        for (to, issue) in selected.iter().enumerate() {
+            for dep in &issue.dependencies {
+                if dep.dep_type == DependencyType::Blocks
+                    && let Some(&from) = index.get(dep.depends_on_id.as_str())
+                    && from != to
+                    && !out[from].contains(&to)
+                {
+                    out[from].push(to);
+                }
+            }
+        }
+
+        let back = back_edges(&out);
+        let layer_of = layers(&out, &back);
+
+        let nodes: Vec<Node> = selected
+            .iter()
+            .zip(&layer_of)
+            .map(|(issue, &layer)| Node {
+                id: issue.id.clone(),
+                title: issue.title.clone(),
+                status: issue.status,
+                priority: issue.priority,
+                layer,
+            })
+            .collect();
+
+        // Place nodes, then a dummy in every layer a long edge skips over
+        let depth = layer_of.iter().max().map_or(0, |m| m + 1);
+        let mut layers: Vec<Vec<Slot>> = vec![Vec::new(); depth];
+        for (i, node) in nodes.iter().enumerate() {
+            layers[node.layer].push(Slot::Node(i));
+        }
+        let mut chains: Vec<Chain> = Vec::new();
+        for (from, targets) in out.iter().enumerate() {
+            for &to in targets {
+                let is_back = back.contains(&(from, to));
+                let mut chain = vec![(layer_of[from], 0)];
+                if !is_back {
+                    for (layer, slots) in layers
+                        .iter_mut()
+                        .enumerate()
+                        .take(layer_of[to])
+                        .skip(layer_of[from] + 1)
+                    {
+                        slots.push(Slot::Dummy);
+                        chain.push((layer, slots.len() - 1));
+                    }
+                }
+                chain.push((layer_of[to], 0));
+                chains.push((from, to, chain, is_back));
+            }
+        }
+
+        // Slot positions before reordering, to map chains onto the new order
+        let mut positions: Vec<Vec<usize>> =
+            layers.iter().map(|l| (0..l.len()).collect()).collect();
+        order_layers(&mut layers, &mut positions, &chains, &nodes);
+
+        let node_pos = |node: usize, nodes: &[Node], layers: &[Vec<Slot>]| {
+            layers[nodes[node].layer]
+                .iter()
+                .position(|s| *s == Slot::Node(node))
+                .unwrap_or(0)
+        };
+        let edges = chains
+            .into_iter()
+            .map(|(from, to, mut path, back)| {
+                let last = path.len() - 1;
+                path[0].1 = node_pos(from, &nodes, &layers);
+                path[last].1 = node_pos(to, &nodes, &layers);
+                for (layer, pos) in &mut path[1..last] {
+                    *pos = positions[*layer][*pos];
+                }
+                Edge {
+                    from,
+                    to,
+                    path,
+                    back,
+                }
+            })
+            .collect();
+
+        let graph = IssueGraph::from_issues(&selected);
+        let cycle = graph
+            .find_cycles()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| index.get(id.as_str()).copied())
+            .collect();
+        let critical = graph
+            .critical_path()
+            .filter(|path| path.len() > 1)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|id| index.get(id.as_str()).copied())
+            .collect();
+
+        let cursor = focus
+            .and_then(|id| index.get(id).copied())
+            .or_else(|| layers.iter().flatten().find_map(slot_node));
+
+        Self {
+            scope,
+            nodes,
+            layers,
+            edges,
+            cycle,
+            critical,
+            cursor,
+            marked: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// ID of the issue under the cursor
+    pub fn selected(&self) -> Option<&str> {
+        self.cursor.map(|i| self.nodes[i].id.as_str())
+    }
+
+    pub fn has_cycles(&self) -> bool {
+        !self.cycle.is_empty()
+    }
+
+    pub fn critical_len(&self) -> usize {
+        self.critical.len()
+    }
+
+    /// Move to the node above or below in the same layer
+    pub fn move_vertical(&mut self, down: bool) {
+        let Some(cursor) = self.cursor else {
+            return;
+        };
+        let column: Vec<usize> = self.layers[self.nodes[cursor].layer]
+            .iter()
+            .filter_map(slot_node)
+            .collect();
+        let at = column.iter().position(|&n| n == cursor).unwrap_or(0);
+        let next = if down {
+            (at + 1).min(column.len() - 1)
+        } else {
+            at.saturating_sub(1)
+        };
+        self.cursor = Some(column[next]);
+    }
+
+    /// Move to the nearest node in the next layer holding issues
+    pub fn move_horizontal(&mut self, right: bool) {
+        let Some(cursor) = self.cursor else {
+            return;
+        };
+        let layer = self.nodes[cursor].layer;
+        let row = self.position(cursor);
+        let candidates: Vec<usize> = if right {
+            (layer + 1..self.layers.len()).collect()
+        } else {
+            (0..layer).rev().collect()
+        };
+        for l in candidates {
+            let nearest = self.layers[l]
+                .iter()
+                .enumerate()
+                .filter_map(|(pos, slot)| slot_node(slot).map(|n| (pos, n)))
+                .min_by_key(|(pos, _)| pos.abs_diff(row));
+            if let Some((_, node)) = nearest {
+                self.cursor = Some(node);
+                return;
+            }
+        }
+    }
+
+    /// Position of a node within its layer
+    fn position(&self, node: usize) -> usize {
+        self.layers[self.nodes[node].layer]
+            .iter()
+            .position(|s| *s == Slot::Node(node))
+            .unwrap_or(0)
+    }
+
+    fn has_back_edges(&self) -> bool {
+        self.edges.iter().any(|e| e.back)
+    }
+
+    fn top(&self) -> usize {
+        if self.has_back_edges() { 2 } else { 0 }
+    }
+
+    fn slot_xy(&self, layer: usize, pos: usize) -> (usize, usize) {
+        (MARGIN + layer * COL_PITCH, self.top() + pos * ROW_PITCH)
+    }
+
+    /// Area of the cursor's box, for scrolling it into view
+    pub fn cursor_area(&self) -> Option<(usize, usize, usize, usize)> {
+        let cursor = self.cursor?;
+        let (x, y) = self.slot_xy(self.nodes[cursor].layer, self.position(cursor));
+        Some((x, y, BOX_WIDTH, BOX_HEIGHT))
+    }
+
+    /// Draw the graph as styled lines
+    pub fn render(&self, theme: &Theme) -> Vec<Line<'static>> {
+        let rows = self.layers.iter().map(Vec::len).max().unwrap_or(0);
+        let width = MARGIN + self.layers.len() * COL_PITCH;
+        let height = self.top() + rows * ROW_PITCH;
+        let mut canvas = Canvas::new(width, height);
+
+        let on_critical = |e: &Edge| {
+            self.critical
+                .windows(2)
+                .any(|w| w[0] == e.from && w[1] == e.to)
+        };
+        let in_cycle =
+            |e: &Edge| e.back || (self.cycle.contains(&e.from) && self.cycle.contains(&e.to));
+
+        // Highlighted edges last so they win where lines share cells
+        let mut edges: Vec<(&Edge, Style)> = self
+            .edges
+            .iter()
+            .map(|e| {
+                let style = if on_critical(e) {
+                    theme.highlight
+                } else if in_cycle(e) {
+                    theme.danger
+                } else {
+                    theme.muted
+                };
+                (e, style)
+            })
+            .collect();
+        edges.sort_by_key(|(e, _)| (on_critical(e), in_cycle(e)));
+        for (edge, style) in edges {
+            self.draw_edge(&mut canvas, edge, style);
+        }
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            self.draw_node(&mut canvas, i, node, theme);
+        }
+        canvas.lines()
+    }
+
+    fn draw_edge(&self, canvas: &mut Canvas, edge: &Edge, style: Style) {
+        let port = |layer: usize, pos: usize| {
+            let (x, y) = self.slot_xy(layer, pos);
+            (x, y + 1)
+        };
+        let (to_layer, to_pos) = edge.path[edge.path.len() - 1];
+        let (tx, ty) = port(to_layer, to_pos);
+
+        if edge.back {
+            // Out to the right, along the lane, and down into the target
+            let (from_layer, from_pos) = edge.path[0];
+            let (fx, fy) = port(from_layer, from_pos);
+            let right = fx + BOX_WIDTH + 1;
+            let left = tx - 2;
+            canvas.hline(fy, fx + BOX_WIDTH, right, style);
+            canvas.vline(right, 0, fy, style);
+            canvas.hline(0, left, right, style);
+            canvas.vline(left, 0, ty, style);
+            canvas.hline(ty, left, tx - 1, style);
+        } else {
+            for pair in edge.path.windows(2) {
+                let (x1, y1) = port(pair[0].0, pair[0].1);
+                let (x2, y2) = port(pair[1].0, pair[1].1);
+                let start = x1 + BOX_WIDTH;
+                let mid = start + CHANNEL / 2 - 1;
+                let end = if pair[1] == (to_layer, to_pos) {
+                    x2 - 1
+                } else {
+                    // Run on through the placeholder slot
+                    x2 + BOX_WIDTH - 1
+                };
+                canvas.hline(y1, start, mid, style);
+                canvas.vline(mid, y1, y2, style);
+                canvas.hline(y2, mid, end, style);
+            }
+        }
+        canvas.text(tx - 1, ty, "▶", style);
+    }
+
+    fn draw_node(&self, canvas: &mut Canvas, i: usize, node: &Node, theme: &Theme) {
+        let (x, y) = self.slot_xy(node.layer, self.position(i));
+        let selected = self.cursor == Some(i);
+        let marked = self.marked.as_deref() == Some(node.id.as_str());
+
+        let border = if selected {
+            theme.accent.add_modifier(Modifier::BOLD)
+        } else if marked {
+            theme.label
+        } else if self.cycle.contains(&i) {
+            theme.danger
+        } else if self.critical.contains(&i) {
+            theme.highlight
+        } else if node.status.is_closed() {
+            theme.muted
+        } else {
+            theme.border
+        };
+        let [tl, tr, bl, br, h, v] = if selected {
+            ['┏', '┓', '┗', '┛', '━', '┃']
+        } else {
+            ['╭', '╮', '╰', '╯', '─', '│']
+        };
+
+        let inner = BOX_WIDTH - 2;
+        let horizontal: String = std::iter::repeat_n(h, inner).collect();
+        canvas.text(x, y, &format!("{}{}{}", tl, horizontal, tr), border);
+        canvas.text(x, y + 3, &format!("{}{}{}", bl, horizontal, br), border);
+        for row in [y + 1, y + 2] {
+            canvas.text(x, row, &v.to_string(), border);
+            canvas.text(x + BOX_WIDTH - 1, row, &v.to_string(), border);
+        }
+
+        let text = if node.status.is_closed() {
+            theme.muted
+        } else {
+            Style::default()
+        };
+        let mut header = format!("{} P{}", node.id, node.priority);
+        if marked {
+            header.push_str(" ★");
+        }
+        canvas.text(
+            x + 1,
+            y + 1,
+            &fit(&format!(" {}", header), inner),
+            text.patch(theme.accent),
+        );
+        canvas.text(x + 1, y + 2, &fit(&format!(" {}", node.title), inner), text);
+    }
+}
+
+fn slot_node(slot: &Slot) -> Option<usize> {
+    match slot {
+        Slot::Node(n) => Some(*n),
+        Slot::Dummy => None,
+    }
+}
+
+/// Pad or cut text to exactly `width` characters
+fn fit(text: &str, width: usize) -> String {
+    let count = text.chars().count();
+    if count <= width {
+        format!("{:<width$}", text)
+    } else {
+        let mut cut: String = text.chars().take(width - 1).collect();
+        cut.push('…');
+        cut
+    }
+}
+
+/// The issues a scope covers, most urgent first
+fn select<'a>(issues: &[&'a Issue], scope: &Scope) -> Vec<&'a Issue> {
+    let mut selected: Vec<&Issue> = match scope {
+        Scope::Backlog => issues
+            .iter()
+            .filter(|i| i.status.is_open())
+            .copied()
+            .collect(),
+        Scope::Neighbourhood(focus) => {
+            let by_id: HashMap<&str, &Issue> = issues.iter().map(|i| (i.id.as_str(), *i)).collect();
+            let mut neighbours: HashMap<&str, Vec<&str>> = HashMap::new();
+            for issue in issues {
+                for dep in issue.dependencies.iter() {
+                    if dep.dep_type == DependencyType::Blocks
+                        && by_id.contains_key(dep.depends_on_id.as_str())
+                    {
+                        neighbours
+                            .entry(issue.id.as_str())
+                            .or_default()
+                            .push(dep.depends_on_id.as_str());
+                        neighbours
+                            .entry(dep.depends_on_id.as_str())
+                            .or_default()
+                            .push(issue.id.as_str());
+                    }
+                }
+            }
+
+            let Some(&start) = by_id.get(focus.as_str()) else {
+                return Vec::new();
+            };
+            let mut seen = HashSet::from([start.id.as_str()]);
+            let mut queue = VecDeque::from([(start.id.as_str(), 0)]);
+            let mut found = vec![start];
+            while let Some((id, depth)) = queue.pop_front() {
+                if depth == NEIGHBOURHOOD_DEPTH {
+                    continue;
+                }
+                for &next in neighbours.get(id).into_iter().flatten() {
+                    if seen.insert(next) {
+                        found.push(by_id[next]);
+                        queue.push_back((next, depth + 1));
+                    }
+                }
+            }
+            found
+        }
+    };
+    selected.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.id.cmp(&b.id)));
+    selected
+}
+
+/// Edges that close a cycle, found by depth-first search
+fn back_edges(out: &[Vec<usize>]) -> HashSet<(usize, usize)> {
+    fn visit(
+        node: usize,
+        out: &[Vec<usize>],
+        state: &mut [u8],
+        back: &mut HashSet<(usize, usize)>,
+    ) {
+        state[node] = 1;
+        for &next in &out[node] {
+            match state[next] {
+                0 => visit(next, out, state, back),
+                1 => {
+                    back.insert((node, next));
+                }
+                _ => {}
+            }
+        }
+        state[node] = 2;
+    }
+
+    // 0 = unvisited, 1 = on the stack, 2 = done
+    let mut state = vec![0u8; out.len()];
+    let mut back = HashSet::new();
+    for node in 0..out.len() {
+        if state[node] == 0 {
+            visit(node, out, &mut state, &mut back);
+        }
+    }
+    back
+}
+
+/// Layer of each node: the longest chain of blockers in front of it
+fn layers(out: &[Vec<usize>], back: &HashSet<(usize, usize)>) -> Vec<usize> {
+    let mut incoming = vec![0usize; out.len()];
+    for (from, targets) in out.iter().enumerate() {
+        for &to in targets {
+            if !back.contains(&(from, to)) {
+                incoming[to] += 1;
+            }
+        }
+    }
+
+    let mut layer = vec![0usize; out.len()];
+    let mut queue: VecDeque<usize> = (0..out.len()).filter(|&n| incoming[n] == 0).collect();
+    while let Some(node) = queue.pop_front() {
+        for &to in &out[node] {
+            if back.contains(&(node, to)) {
+                continue;
+            }
+            layer[to] = layer[to].max(layer[node] + 1);
+            incoming[to] -= 1;
+            if incoming[to] == 0 {
+                queue.push_back(to);
+            }
+        }
+    }
+    layer
+}
+
+/// Order each layer by the mean position of what feeds into it, to cut
+/// down on crossings
+///
+/// `positions[layer][original]` is updated to the slot's new position.
+fn order_layers(
+    layers: &mut [Vec<Slot>],
+    positions: &mut [Vec<usize>],
+    chains: &[Chain],
+    nodes: &[Node],
+) {
+    // Incoming neighbours of each slot, by (layer, original position)
+    let mut preds: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+    let original = |layers: &[Vec<Slot>], layer: usize, node: usize| {
+        layers[layer]
+            .iter()
+            .position(|s| *s == Slot::Node(node))
+            .unwrap_or(0)
+    };
+    for (from, to, chain, back) in chains {
+        if *back {
+            continue;
+        }
+        let mut path = chain.clone();
+        let last = path.len() - 1;
+        path[0].1 = original(layers, nodes[*from].layer, *from);
+        path[last].1 = original(layers, nodes[*to].layer, *to);
+        for pair in path.windows(2) {
+            preds.entry(pair[1]).or_default().push(pair[0]);
+        }
+    }
+
+    for layer in 1..layers.len() {
+        let mut order: Vec<(f64, usize)> = (0..layers[layer].len())
+            .map(|orig| {
+                let score = match preds.get(&(layer, orig)) {
+                    Some(p) => {
+                        p.iter().map(|(l, o)| positions[*l][*o] as f64).sum::<f64>()
+                            / p.len() as f64
+                    }
+                    None => f64::MAX,
+                };
+                (score, orig)
+            })
+            .collect();
+        order.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+        let slots = layers[layer].clone();
+        for (new, (_, orig)) in order.iter().enumerate() {
+            layers[layer][new] = slots[*orig];
+            positions[layer][*orig] = new;
+        }
+    }
+}
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+#[derive(Clone, Copy, Default)]
+struct Cell {
+    ch: Option<char>,
+    lines: u8,
+    style: Style,
+}
+
+/// Character grid that merges crossing lines into junctions
+struct Canvas {
+    width: usize,
+    cells: Vec<Vec<Cell>>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            cells: vec![vec![Cell::default(); width]; height],
+        }
+    }
+
+    fn line(&mut self, x: usize, y: usize, lines: u8, style: Style) {
+        if let Some(cell) = self.cells.get_mut(y).and_then(|r| r.get_mut(x)) {
+            cell.lines |= lines;
+            cell.style = style;
+        }
+    }
+
+    fn hline(&mut self, y: usize, x1: usize, x2: usize, style: Style) {
+        let (a, b) = (x1.min(x2), x1.max(x2));
+        for x in a..=b {
+            let mut lines = 0;
+            if x > a {
+                lines |= LEFT;
+            }
+            if x < b {
+                lines |= RIGHT;
+            }
+            self.line(x, y, if a == b { LEFT | RIGHT } else { lines }, style);
+        }
+    }
+
+    fn vline(&mut self, x: usize, y1: usize, y2: usize, style: Style) {
+        let (a, b) = (y1.min(y2), y1.max(y2));
+        for y in a..=b {
+            let mut lines = 0;
+            if y > a {
+                lines |= UP;
+            }
+            if y < b {
+                lines |= DOWN;
+            }
+            self.line(x, y, lines, style);
+        }
+    }
+
+    fn text(&mut self, x: usize, y: usize, text: &str, style: Style) {
+        for (i, ch) in text.chars().enumerate() {
+            if x + i >= self.width {
+                break;
+            }
+            if let Some(cell) = self.cells.get_mut(y).and_then(|r| r.get_mut(x + i)) {
+                cell.ch = Some(ch);
+                cell.style = style;
+            }
+        }
+    }
+
+    fn lines(self) -> Vec<Line<'static>> {
+        self.cells
+            .into_iter()
+            .map(|row| {
+                let mut spans: Vec<Span<'static>> = Vec::new();
+                let mut text = String::new();
+                let mut style = Style::default();
+                for cell in row {
+                    let ch = cell.ch.unwrap_or_else(|| glyph(cell.lines));
+                    if cell.style != style && !text.is_empty() {
+                        spans.push(Span::styled(std::mem::take(&mut text), style));
+                    }
+                    style = cell.style;
+                    text.push(ch);
+                }
+                spans.push(Span::styled(text.trim_end().to_string(), style));
+                Line::from(spans)
+            })
+            .collect()
+    }
+}
+
+fn glyph(lines: u8) -> char {
+    let h = lines & (LEFT | RIGHT) != 0;
+    let v = lines & (UP | DOWN) != 0;
+    match (h, v) {
+        (false, false) => ' ',
+        (true, false) => '─',
+        (false, true) => '│',
+        (true, true) => match lines {
+            l if l == DOWN | RIGHT => '┌',
+            l if l == DOWN | LEFT => '┐',
+            l if l == UP | RIGHT => '└',
+            l if l == UP | LEFT => '┘',
+            l if l == UP | DOWN | RIGHT => '├',
+            l if l == UP | DOWN | LEFT => '┤',
+            l if l == LEFT | RIGHT | DOWN => '┬',
+            l if l == LEFT | RIGHT | UP => '┴',
+            _ => '┼',
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lines: &[Line]) -> String {
+        lines
+            .iter()
+            .map(|l| {
+                l.spans
+                    .iter()
+                    .map(|s| s.content.as_ref())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_layout_and_navigation() {
+        // a blocks b and c, b blocks c; d is unrelated
+        let a = Issue::new("trx-a".into(), "A".into());
+        let mut b = Issue::new("trx-b".into(), "B".into());
+        let mut c = Issue::new("trx-c".into(), "C".into());
+        let d = Issue::new("trx-d".into(), "D".into());
+        b.add_dependency("trx-a".into(), DependencyType::Blocks);
+        c.add_dependency("trx-b".into(), DependencyType::Blocks);
+        c.add_dependency("trx-a".into(), DependencyType::Blocks);
+        let issues: Vec<&Issue> = vec![&a, &b, &c, &d];
+
+        let mut view = GraphView::build(&issues, Scope::Backlog, Some("trx-a"));
+        let layers: Vec<_> = view
+            .nodes
+            .iter()
+            .map(|n| (n.id.as_str(), n.layer))
+            .collect();
+        assert!(layers.contains(&("trx-a", 0)));
+        assert!(layers.contains(&("trx-b", 1)));
+        assert!(layers.contains(&("trx-c", 2)));
+        // a -> c skips layer 1 through a placeholder
+        assert!(view.layers[1].contains(&Slot::Dummy));
+        assert_eq!(view.critical_len(), 3);
+        assert!(!view.has_cycles());
+
+        view.move_horizontal(true);
+        assert_eq!(view.selected(), Some("trx-b"));
+        view.move_horizontal(true);
+        assert_eq!(view.selected(), Some("trx-c"));
+        view.move_horizontal(false);
+        view.move_horizontal(false);
+        view.move_vertical(true);
+        assert_eq!(view.selected(), Some("trx-d"));
+
+        let drawn = text(&view.render(&Theme::dark()));
+        assert!(drawn.contains("trx-a P2"));
+        assert!(drawn.contains('▶'));
+
+        // The neighbourhood of d is just d
+        let view = GraphView::build(&issues, Scope::Neighbourhood("trx-d".into()), None);
+        assert_eq!(view.nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_cycle_routes_back_edge() {
+        let mut a = Issue::new("trx-a".into(), "A".into());
+        let mut b = Issue::new("trx-b".into(), "B".into());
+        a.add_dependency("trx-b".into(), DependencyType::Blocks);
+        b.add_dependency("trx-a".into(), DependencyType::Blocks);
+        let issues: Vec<&Issue> = vec![&a, &b];
+
+        let view = GraphView::build(&issues, Scope::Neighbourhood("trx-a".into()), None);
+        assert!(view.has_cycles());
+        assert_eq!(view.critical_len(), 0);
+        assert_eq!(view.edges.iter().filter(|e| e.back).count(), 1);
+        // The lane above the graph carries the back edge
+        let drawn = view.render(&Theme::dark());
+        assert!(text(&drawn[..1]).contains('─'));
+    }
+}
@@ -0,0 +1,560 @@
+//! Named commands and the keys bound to them
+//!
+//! Everything the list and board do from the keyboard is a `Command`. Keys
+//! reach commands through a `Keymap` built from the defaults and the
+//! `[tui.keys]` config section, and the `:` palette parses typed command
+//! lines into the same commands, so both go through one dispatcher.
+
+use anyhow::{Result, anyhow, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeMap;
+use std::fmt;
+use trx_core::Status;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Priority,
+    Created,
+    Updated,
+    Status,
+}
+
+impl SortKey {
+    const ALL: [SortKey; 4] = [
+        SortKey::Priority,
+        SortKey::Created,
+        SortKey::Updated,
+        SortKey::Status,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            SortKey::Priority => "priority",
+            SortKey::Created => "created",
+            SortKey::Updated => "updated",
+            SortKey::Status => "status",
+        }
+    }
+}
+
+/// A layout or preset filter, switched to with `view <name>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    List,
+    Board,
+    Graph,
+    Dashboard,
+    /// Default filters
+    All,
+    Ready,
+    Blocked,
+    /// Open issues nobody is assigned to
+    Triage,
+}
+
+impl View {
+    const ALL: [View; 8] = [
+        View::List,
+        View::Board,
+        View::Graph,
+        View::Dashboard,
+        View::All,
+        View::Ready,
+        View::Blocked,
+        View::Triage,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            View::List => "list",
+            View::Board => "board",
+            View::Graph => "graph",
+            View::Dashboard => "dashboard",
+            View::All => "all",
+            View::Ready => "ready",
+            View::Blocked => "blocked",
+            View::Triage => "triage",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Quit,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    ScrollUp,
+    ScrollDown,
+    Add,
+    Edit,
+    EditText,
+    Close,
+    Status(Status),
+    Priority(u8),
+    AddLabel(String),
+    RemoveLabel(String),
+    /// Assign, or unassign with `None`
+    Assign(Option<String>),
+    Select,
+    SelectAll,
+    ClearSelection,
+    Bulk,
+    Undo,
+    Search,
+    Filter,
+    FilterStatus,
+    FilterType,
+    FilterPriority,
+    FilterLabel,
+    SortMenu,
+    Sort(SortKey),
+    Refresh,
+    Graph,
+    Dashboard,
+    Board,
+    CardLeft,
+    CardRight,
+    Group,
+    View(View),
+    Palette,
+    Help,
+}
+
+/// Commands without arguments: name, command and help text
+const SIMPLE: &[(&str, Command, &str)] = &[
+    ("quit", Command::Quit, "Quit"),
+    ("up", Command::Up, "Move up"),
+    ("down", Command::Down, "Move down"),
+    ("left", Command::Left, "Move left (board)"),
+    ("right", Command::Right, "Move right (board)"),
+    ("page-up", Command::PageUp, "Page up"),
+    ("page-down", Command::PageDown, "Page down"),
+    ("top", Command::Top, "Go to top"),
+    ("bottom", Command::Bottom, "Go to bottom"),
+    ("scroll-up", Command::ScrollUp, "Scroll details up"),
+    ("scroll-down", Command::ScrollDown, "Scroll details down"),
+    ("add", Command::Add, "Add issue"),
+    ("edit", Command::Edit, "Edit issue"),
+    (
+        "edit-text",
+        Command::EditText,
+        "Edit description, notes or comment in $EDITOR",
+    ),
+    ("close", Command::Close, "Close issue"),
+    ("unassign", Command::Assign(None), "Unassign"),
+    ("select", Command::Select, "Toggle selection"),
+    ("select-all", Command::SelectAll, "Select all"),
+    (
+        "clear-selection",
+        Command::ClearSelection,
+        "Clear selection",
+    ),
+    ("bulk", Command::Bulk, "Bulk action on selection"),
+    ("undo", Command::Undo, "Undo last bulk action"),
+    ("search", Command::Search, "Search"),
+    ("filter", Command::Filter, "Filter menu"),
+    ("filter-status", Command::FilterStatus, "Filter by status"),
+    ("filter-type", Command::FilterType, "Filter by type"),
+    (
+        "filter-priority",
+        Command::FilterPriority,
+        "Filter by priority",
+    ),
+    ("filter-label", Command::FilterLabel, "Filter by label"),
+    ("sort-menu", Command::SortMenu, "Sort menu"),
+    ("refresh", Command::Refresh, "Refresh"),
+    ("graph", Command::Graph, "Dependency graph"),
+    ("dashboard", Command::Dashboard, "Statistics dashboard"),
+    ("board", Command::Board, "Toggle board view"),
+    (
+        "card-left",
+        Command::CardLeft,
+        "Move card to previous status",
+    ),
+    ("card-right", Command::CardRight, "Move card to next status"),
+    ("group", Command::Group, "Change board grouping"),
+    ("palette", Command::Palette, "Command palette"),
+    ("help", Command::Help, "Help"),
+];
+
+const STATUSES: [Status; 4] = [
+    Status::Open,
+    Status::InProgress,
+    Status::Blocked,
+    Status::Closed,
+];
+
+impl Command {
+    /// Parse a command line such as "close" or "label add backend"
+    pub fn parse(line: &str) -> Result<Command> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            bail!("Empty command");
+        };
+        let rest: Vec<&str> = words.collect();
+        let arg = rest.join(" ");
+
+        if let Some((_, command, _)) = SIMPLE.iter().find(|(n, _, _)| *n == name) {
+            if !rest.is_empty() {
+                bail!("'{}' takes no arguments", name);
+            }
+            return Ok(command.clone());
+        }
+
+        let command = match name {
+            "status" => Command::Status(
+                arg.parse()
+                    .map_err(|_| anyhow!("Unknown status '{}'", arg))?,
+            ),
+            "priority" => {
+                let p = arg.trim_start_matches(['P', 'p']);
+                match p.parse() {
+                    Ok(p) if p <= 4 => Command::Priority(p),
+                    _ => bail!("Priority must be 0-4, got '{}'", arg),
+                }
+            }
+            "label" => match rest.split_first() {
+                Some((&"add", label)) if !label.is_empty() => Command::AddLabel(label.join(" ")),
+                Some((&"remove", label)) if !label.is_empty() => {
+                    Command::RemoveLabel(label.join(" "))
+                }
+                _ => bail!("Usage: label add|remove <label>"),
+            },
+            "assign" if !arg.is_empty() => Command::Assign(Some(arg)),
+            "assign" => bail!("Usage: assign <name>"),
+            "sort" => Command::Sort(
+                SortKey::ALL
+                    .into_iter()
+                    .find(|k| k.name() == arg)
+                    .ok_or_else(|| anyhow!("Unknown sort '{}'", arg))?,
+            ),
+            "view" => Command::View(
+                View::ALL
+                    .into_iter()
+                    .find(|v| v.name() == arg)
+                    .ok_or_else(|| anyhow!("Unknown view '{}'", arg))?,
+            ),
+            _ => bail!("Unknown command '{}'", name),
+        };
+        Ok(command)
+    }
+
+    /// Every command that needs no free text, for the palette
+    pub fn catalogue() -> Vec<Command> {
+        let mut commands: Vec<Command> = SIMPLE.iter().map(|(_, c, _)| c.clone()).collect();
+        commands.extend(STATUSES.into_iter().map(Command::Status));
+        commands.extend((0..=4).map(Command::Priority));
+        commands.extend(SortKey::ALL.into_iter().map(Command::Sort));
+        commands.extend(View::ALL.into_iter().map(Command::View));
+        commands
+    }
+
+    /// Help text
+    pub fn describe(&self) -> String {
+        if let Some((_, _, help)) = SIMPLE.iter().find(|(_, c, _)| c == self) {
+            return help.to_string();
+        }
+        match self {
+            Command::Status(status) => format!("Set status {}", status),
+            Command::Priority(p) => format!("Set priority P{}", p),
+            Command::AddLabel(label) => format!("Add label '{}'", label),
+            Command::RemoveLabel(label) => format!("Remove label '{}'", label),
+            Command::Assign(Some(who)) => format!("Assign to {}", who),
+            Command::Sort(key) => format!("Sort by {}", key.name()),
+            Command::View(view) => format!("Show {} view", view.name()),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((name, _, _)) = SIMPLE.iter().find(|(_, c, _)| c == self) {
+            return write!(f, "{}", name);
+        }
+        match self {
+            Command::Status(status) => write!(f, "status {}", status),
+            Command::Priority(p) => write!(f, "priority {}", p),
+            Command::AddLabel(label) => write!(f, "label add {}", label),
+            Command::RemoveLabel(label) => write!(f, "label remove {}", label),
+            Command::Assign(Some(who)) => write!(f, "assign {}", who),
+            Command::Sort(key) => write!(f, "sort {}", key.name()),
+            Command::View(view) => write!(f, "view {}", view.name()),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// A key with its modifiers, Shift folded into the character for letters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn from_event(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if !matches!(event.code, KeyCode::Char(_)) {
+            modifiers |= event.modifiers & KeyModifiers::SHIFT;
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+
+    /// Parse keys such as "x", "G", "ctrl-d", "alt-enter", "space" or "f5"
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let (modifier, len) = if lower.starts_with("ctrl-") && rest.len() > 5 {
+                (KeyModifiers::CONTROL, 5)
+            } else if lower.starts_with("alt-") && rest.len() > 4 {
+                (KeyModifiers::ALT, 4)
+            } else if lower.starts_with("shift-") && rest.len() > 6 {
+                (KeyModifiers::SHIFT, 6)
+            } else {
+                break;
+            };
+            modifiers |= modifier;
+            rest = &rest[len..];
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => {
+                if modifiers.contains(KeyModifiers::SHIFT) {
+                    modifiers.remove(KeyModifiers::SHIFT);
+                    KeyCode::Char(c.to_ascii_uppercase())
+                } else if modifiers.contains(KeyModifiers::CONTROL) {
+                    // Terminals report ctrl-letter in lower case
+                    KeyCode::Char(c.to_ascii_lowercase())
+                } else {
+                    KeyCode::Char(c)
+                }
+            }
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "esc" => KeyCode::Esc,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => bail!("Unknown key '{}'", text),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::BackTab => write!(f, "backtab"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            code => write!(f, "{}", format!("{:?}", code).to_lowercase()),
+        }
+    }
+}
+
+/// Default bindings, in the order the help lists them
+const DEFAULTS: &[(&str, &str)] = &[
+    ("j", "down"),
+    ("down", "down"),
+    ("k", "up"),
+    ("up", "up"),
+    ("h", "left"),
+    ("left", "left"),
+    ("l", "right"),
+    ("right", "right"),
+    ("g", "top"),
+    ("home", "top"),
+    ("G", "bottom"),
+    ("end", "bottom"),
+    ("ctrl-d", "page-down"),
+    ("pagedown", "page-down"),
+    ("ctrl-u", "page-up"),
+    ("pageup", "page-up"),
+    ("J", "scroll-down"),
+    ("K", "scroll-up"),
+    ("space", "select"),
+    ("ctrl-a", "select-all"),
+    ("V", "clear-selection"),
+    ("x", "bulk"),
+    ("u", "undo"),
+    ("a", "add"),
+    ("e", "edit"),
+    ("E", "edit-text"),
+    ("c", "close"),
+    ("1", "status open"),
+    ("2", "status in_progress"),
+    ("3", "status blocked"),
+    ("4", "status closed"),
+    ("/", "search"),
+    ("s", "sort-menu"),
+    ("f", "filter"),
+    ("t", "filter-type"),
+    ("p", "filter-priority"),
+    ("v", "graph"),
+    ("D", "dashboard"),
+    ("b", "board"),
+    ("H", "card-left"),
+    ("L", "card-right"),
+    ("tab", "group"),
+    ("r", "refresh"),
+    (":", "palette"),
+    ("?", "help"),
+    ("q", "quit"),
+    ("ctrl-c", "quit"),
+];
+
+/// Keys bound to commands in the list and board
+pub struct Keymap {
+    bindings: Vec<(Key, Command)>,
+}
+
+impl Keymap {
+    /// The default bindings with `overrides` from `[tui.keys]` applied; a
+    /// command of "none" unbinds the key
+    pub fn new(overrides: &BTreeMap<String, String>) -> Result<Self> {
+        let mut bindings = Vec::new();
+        for (key, command) in DEFAULTS {
+            bindings.push((Key::parse(key)?, Command::parse(command)?));
+        }
+        for (key, command) in overrides {
+            let parsed = Key::parse(key).map_err(|e| anyhow!("Invalid [tui.keys] entry: {}", e))?;
+            bindings.retain(|(k, _)| *k != parsed);
+            if command.trim() != "none" {
+                let command = Command::parse(command)
+                    .map_err(|e| anyhow!("Invalid [tui.keys] entry \"{}\": {}", key, e))?;
+                bindings.push((parsed, command));
+            }
+        }
+        Ok(Self { bindings })
+    }
+
+    pub fn command(&self, event: KeyEvent) -> Option<Command> {
+        let key = Key::from_event(event);
+        self.bindings
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, c)| c.clone())
+    }
+
+    /// Keys bound to a command, e.g. "j, down"
+    pub fn keys_for(&self, command: &Command) -> String {
+        self.bindings
+            .iter()
+            .filter(|(_, c)| c == command)
+            .map(|(k, _)| k.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The first key bound to a command, for hints
+    pub fn first_key(&self, command: &Command) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|(_, c)| c == command)
+            .map(|(k, _)| k.to_string())
+    }
+
+    /// Each bound command with its keys, in binding order
+    pub fn help(&self) -> Vec<(String, String)> {
+        let mut seen: Vec<&Command> = Vec::new();
+        for (_, command) in &self.bindings {
+            if !seen.contains(&command) {
+                seen.push(command);
+            }
+        }
+        seen.into_iter()
+            .map(|c| (self.keys_for(c), c.describe()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_bind() {
+        for command in Command::catalogue() {
+            assert_eq!(Command::parse(&command.to_string()).unwrap(), command);
+        }
+        assert_eq!(
+            Command::parse("label add needs review").unwrap(),
+            Command::AddLabel("needs review".into())
+        );
+        assert_eq!(Command::parse("priority P1").unwrap(), Command::Priority(1));
+        assert!(Command::parse("priority 7").is_err());
+        assert!(Command::parse("close now").is_err());
+        assert!(Command::parse("view nowhere").is_err());
+
+        let overrides = BTreeMap::from([
+            ("x".to_string(), "close".to_string()),
+            ("ctrl-L".to_string(), "label add backend".to_string()),
+            ("q".to_string(), "none".to_string()),
+        ]);
+        let keymap = Keymap::new(&overrides).unwrap();
+        let event = |code, modifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(
+            keymap.command(event(KeyCode::Char('x'), KeyModifiers::NONE)),
+            Some(Command::Close)
+        );
+        assert_eq!(
+            keymap.command(event(KeyCode::Char('l'), KeyModifiers::CONTROL)),
+            Some(Command::AddLabel("backend".into()))
+        );
+        assert_eq!(
+            keymap.command(event(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+            Some(Command::Bottom)
+        );
+        assert_eq!(
+            keymap.command(event(KeyCode::Char('q'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(keymap.keys_for(&Command::Close), "c, x");
+        assert_eq!(keymap.keys_for(&Command::Bulk), "");
+
+        let bad = BTreeMap::from([("x".to_string(), "explode".to_string())]);
+        assert!(Keymap::new(&bad).is_err());
+        let bad = BTreeMap::from([("ctrl-nope".to_string(), "close".to_string())]);
+        assert!(Keymap::new(&bad).is_err());
+    }
+}
@@ -15,24 +15,50 @@ use ratatui::{
     Frame, Terminal,
     backend::{Backend, CrosstermBackend},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
-use trx_core::{Issue, IssueGraph, Status, Store};
+use trx_core::{DependencyType, Issue, IssueGraph, Status};
+
+mod board;
+mod bulk;
+mod deps;
+mod editor;
+mod graph_view;
+mod keymap;
+mod markdown;
+mod palette;
+mod repos;
+mod stats;
+mod theme;
+
+use board::{BoardState, Grouping, STATUS_COLUMNS};
+use bulk::{BulkAction, BulkInput, BulkStep, UndoStep};
+use deps::Dependencies;
+use editor::{EditField, EditRequest};
+use graph_view::{GraphView, Scope};
+use keymap::{Command, Keymap, SortKey, View};
+use palette::{Outcome, Palette};
+use repos::{Conflict, Repos};
+use stats::Stats;
+use theme::Theme;
 
 #[derive(Parser)]
 #[command(name = "trx-tui")]
 #[command(about = "Terminal UI viewer for trx issues")]
 #[command(version)]
 struct Cli {
-    #[arg(short, long)]
-    workspace: Option<String>,
-    #[arg(short, long)]
-    repo: Option<String>,
+    /// Workspace file listing repositories to view together
+    #[arg(short, long, global = true, value_name = "FILE")]
+    workspace: Option<PathBuf>,
+    /// Repository root to view (repeatable; defaults to the current repository)
+    #[arg(short, long = "repo", global = true, value_name = "PATH")]
+    repos: Vec<PathBuf>,
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -55,27 +81,37 @@ enum RobotMode {
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let repos = Repos::open(cli.workspace.as_deref(), &cli.repos)?;
     match cli.command {
-        Some(Commands::Robot { mode }) => run_robot_mode(mode),
-        None => run_tui(cli.workspace, cli.repo),
+        Some(Commands::Robot { mode }) => run_robot_mode(mode, &repos),
+        None => run_tui(repos),
     }
 }
 
-fn run_robot_mode(mode: RobotMode) -> Result<()> {
-    let store = Store::open()?;
-    let issues = store.list_open();
+fn run_robot_mode(mode: RobotMode, repos: &Repos) -> Result<()> {
+    let tagged = repos.issues(false);
+    let issues: Vec<&Issue> = tagged.iter().map(|(_, issue)| *issue).collect();
+    let repo_of: HashMap<&str, usize> = tagged
+        .iter()
+        .map(|(repo, issue)| (issue.id.as_str(), *repo))
+        .collect();
 
     match mode {
         RobotMode::Triage => {
-            let mut sorted: Vec<_> = issues.into_iter().collect();
-            sorted.sort_by(|a, b| a.priority.cmp(&b.priority));
+            let mut sorted = tagged.clone();
+            sorted.sort_by_key(|(_, issue)| issue.priority);
+            let sorted = sorted
+                .into_iter()
+                .map(|(repo, issue)| repos.to_json(repo, issue))
+                .collect::<Result<Vec<_>>>()?;
             println!("{}", serde_json::to_string_pretty(&sorted)?);
         }
         RobotMode::Next => {
             let graph = IssueGraph::from_issues(&issues);
             let ready = graph.ready_issues(&issues);
             if let Some(next) = ready.iter().min_by_key(|i| i.priority) {
-                println!("{}", serde_json::to_string_pretty(next)?);
+                let next = repos.to_json(repo_of[next.id.as_str()], next)?;
+                println!("{}", serde_json::to_string_pretty(&next)?);
             } else {
                 println!("null");
             }
@@ -103,16 +139,15 @@ fn run_robot_mode(mode: RobotMode) -> Result<()> {
     Ok(())
 }
 
-fn run_tui(_workspace: Option<String>, _repo: Option<String>) -> Result<()> {
+fn run_tui(repos: Repos) -> Result<()> {
+    let mut app = App::new(repos)?;
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let store = Store::open()?;
-    let mut app = App::new(store)?;
-
     let res = run_app(&mut terminal, &mut app);
 
     disable_raw_mode()?;
@@ -130,6 +165,17 @@ fn run_tui(_workspace: Option<String>, _repo: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Hand the terminal to `f`, restoring the TUI afterwards
+fn suspended<B: Backend, T>(terminal: &mut Terminal<B>, f: impl FnOnce() -> T) -> Result<T> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+    let result = f();
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+    Ok(result)
+}
+
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
     let mut last_tick = Instant::now();
     const TICK_RATE: Duration = Duration::from_millis(250);
@@ -144,10 +190,14 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
         if event::poll(timeout)?
             && let Event::Key(key) = event::read()?
         {
-            let action = parse_key_action(key);
-            if app.handle_key_action(action)? {
+            if app.handle_key(key)? {
                 return Ok(());
             }
+            if let Some(request) = app.pending_edit.take() {
+                let text = request.field.text(&request.base);
+                let edited = suspended(terminal, || editor::edit_text(&request.command, &text))?;
+                app.finish_edit(request, edited)?;
+            }
         }
 
         if last_tick.elapsed() >= TICK_RATE {
@@ -167,6 +217,11 @@ enum AppMode {
     WhichKey(WhichKeyContext),
     AddIssue,
     EditIssue,
+    Graph,
+    Dashboard,
+    Conflict,
+    Bulk,
+    Palette,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -175,6 +230,8 @@ enum WhichKeyContext {
     Priority,
     Type,
     Labels,
+    /// Which text to open in the editor
+    Edit,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -220,24 +277,64 @@ fn parse_key_action(key: KeyEvent) -> KeyAction {
     }
 }
 
+/// Like `parse_key_action`, but letters stay letters for typing text
+fn parse_text_key(key: KeyEvent) -> KeyAction {
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => KeyAction::Char(c),
+        _ => parse_key_action(key),
+    }
+}
+
+/// An issue in the list and the repository it belongs to
+struct ListedIssue {
+    repo: usize,
+    issue: Issue,
+}
+
 struct App {
-    filtered_issues: Vec<Issue>,
+    filtered_issues: Vec<ListedIssue>,
     mode: AppMode,
-    g_prefix: bool,
+    keymap: Keymap,
+    palette: Option<Palette>,
+    theme: Theme,
     search_query: String,
 
     filter_state: FilterState,
     selection: SelectionState,
     details_scroll: usize,
 
-    store: Store,
+    repos: Repos,
+    /// Ready and blocked state of every issue, rebuilt on refresh
+    deps: Dependencies,
 
     status_message: Option<String>,
     status_message_time: Option<Instant>,
 
     issue_form: IssueForm,
+
+    graph: Option<GraphView>,
+    /// Dashboard figures while the dashboard is open, recomputed with the
+    /// issue list
+    stats: Option<Stats>,
+    /// Board layout, when the board replaces the issue list
+    board: Option<BoardState>,
+
+    /// Edit held back because the issue changed on disk
+    conflict: Option<Conflict>,
+    last_watch: Instant,
+
+    /// Text to open in the editor once the event loop releases the terminal
+    pending_edit: Option<EditRequest>,
+
+    /// Bulk action being chosen for the selected issues
+    bulk: Option<BulkStep>,
+    /// Applied bulk actions, most recent last
+    undo: Vec<UndoStep>,
 }
 
+/// How often `.trx/` is checked for changes made outside the TUI
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
 struct IssueForm {
     title: String,
     description: String,
@@ -245,6 +342,8 @@ struct IssueForm {
     priority: u8,
     status: trx_core::Status,
     selected_field: FormField,
+    /// The issue as it was when editing began
+    base: Option<Issue>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -265,6 +364,7 @@ impl IssueForm {
             priority: 2,
             status: trx_core::Status::Open,
             selected_field: FormField::Title,
+            base: None,
         }
     }
 
@@ -280,6 +380,7 @@ impl IssueForm {
             priority: issue.priority,
             status: issue.status,
             selected_field: FormField::Title,
+            base: Some(issue.clone()),
         }
     }
 }
@@ -291,6 +392,9 @@ struct FilterState {
     enabled_labels: HashSet<String>,
     ready_only: bool,
     show_blocked: bool,
+    unassigned_only: bool,
+    /// Only show issues from this repository
+    repo: Option<String>,
 }
 
 struct SelectionState {
@@ -395,14 +499,20 @@ impl FilterState {
             enabled_labels: HashSet::new(),
             ready_only: false,
             show_blocked: false,
+            unassigned_only: false,
+            repo: None,
         }
     }
 
-    fn matches(&self, issue: &Issue, query: &str) -> bool {
+    fn matches(&self, repo: &str, issue: &Issue, query: &str, deps: &Dependencies) -> bool {
         if !self.show_closed && issue.status.is_closed() {
             return false;
         }
 
+        if self.repo.as_deref().is_some_and(|r| r != repo) {
+            return false;
+        }
+
         if !self.enabled_statuses.contains(&issue.status) {
             return false;
         }
@@ -417,11 +527,15 @@ impl FilterState {
             return false;
         }
 
-        if self.ready_only && issue.is_blocked_by(&Vec::new()) {
+        if self.ready_only && !deps.is_ready(&issue.id) {
             return false;
         }
 
-        if self.show_blocked && !issue.is_blocked_by(&Vec::new()) {
+        if self.show_blocked && !deps.is_blocked(&issue.id) {
+            return false;
+        }
+
+        if self.unassigned_only && issue.assignee.is_some() {
             return false;
         }
 
@@ -445,19 +559,32 @@ impl FilterState {
 }
 
 impl App {
-    fn new(store: Store) -> Result<Self> {
+    fn new(repos: Repos) -> Result<Self> {
+        let config = repos.config(0)?;
+        let colors = theme::colors_enabled(config.display.colors);
         let mut app = Self {
             filtered_issues: Vec::new(),
             mode: AppMode::Normal,
-            g_prefix: false,
+            keymap: Keymap::new(&config.tui.keys)?,
+            palette: None,
+            theme: Theme::from_config(&config.tui.theme, colors)?,
             search_query: String::new(),
             filter_state: FilterState::new(),
             selection: SelectionState::new(),
             details_scroll: 0,
-            store,
+            repos,
+            deps: Dependencies::default(),
             status_message: None,
             status_message_time: None,
             issue_form: IssueForm::new(),
+            graph: None,
+            stats: None,
+            board: None,
+            conflict: None,
+            last_watch: Instant::now(),
+            pending_edit: None,
+            bulk: None,
+            undo: Vec::new(),
         };
 
         app.apply_filters()?;
@@ -465,36 +592,64 @@ impl App {
     }
 
     fn apply_filters(&mut self) -> Result<()> {
-        let issues: Vec<&Issue> = if self.filter_state.show_closed {
-            self.store.list(false)
-        } else {
-            self.store.list_open()
-        };
+        let all = self.repos.issues(true);
+        let issues: Vec<&Issue> = all.iter().map(|(_, i)| *i).collect();
+        self.deps = Dependencies::new(&issues);
 
-        self.filtered_issues = issues
+        let current = self.current_issue().map(|i| i.id.clone());
+        self.filtered_issues = all
             .into_iter()
-            .filter(|i| self.filter_state.matches(i, &self.search_query))
-            .cloned()
+            .filter(|(repo, i)| {
+                self.filter_state
+                    .matches(self.repos.name(*repo), i, &self.search_query, &self.deps)
+            })
+            .map(|(repo, i)| ListedIssue {
+                repo,
+                issue: i.clone(),
+            })
             .collect();
 
         self.filtered_issues.sort_by(|a, b| {
-            a.priority
-                .cmp(&b.priority)
-                .then_with(|| b.created_at.cmp(&a.created_at))
+            a.issue
+                .priority
+                .cmp(&b.issue.priority)
+                .then_with(|| b.issue.created_at.cmp(&a.issue.created_at))
         });
 
+        // Keep the cursor on the same issue if it is still listed
+        if let Some(index) =
+            current.and_then(|id| self.filtered_issues.iter().position(|l| l.issue.id == id))
+        {
+            self.selection.index = index;
+        }
         let max = self.filtered_issues.len();
         if self.selection.index >= max {
             self.selection.index = max.saturating_sub(1);
         }
+        self.follow_board();
+        if self.stats.is_some() {
+            self.refresh_stats();
+        }
 
         self.show_status(format!("Showing {} issues", self.filtered_issues.len()));
         Ok(())
     }
 
-    fn handle_key_action(&mut self, action: KeyAction) -> Result<bool> {
+    fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
+        if self.mode == AppMode::Normal {
+            return match self.keymap.command(key) {
+                Some(command) => self.run_command(command),
+                None => Ok(false),
+            };
+        }
+        let action =
+            if self.mode == AppMode::Palette || matches!(self.bulk, Some(BulkStep::Input(..))) {
+                parse_text_key(key)
+            } else {
+                parse_key_action(key)
+            };
         match self.mode {
-            AppMode::Normal => self.handle_normal_mode(action),
+            AppMode::Normal => Ok(false),
             AppMode::Search => self.handle_search_mode(action),
             AppMode::Help => self.handle_help_mode(action),
             AppMode::Sort => self.handle_sort_mode(action),
@@ -502,111 +657,739 @@ impl App {
             AppMode::WhichKey(ctx) => self.handle_which_key_mode(ctx, action),
             AppMode::AddIssue => self.handle_add_issue_mode(action),
             AppMode::EditIssue => self.handle_edit_issue_mode(action),
+            AppMode::Graph => self.handle_graph_mode(action),
+            AppMode::Dashboard => self.handle_dashboard_mode(action),
+            AppMode::Conflict => self.handle_conflict_mode(action),
+            AppMode::Bulk => self.handle_bulk_mode(action),
+            AppMode::Palette => self.handle_palette_mode(action),
         }
     }
 
-    fn handle_normal_mode(&mut self, action: KeyAction) -> Result<bool> {
-        match action {
-            KeyAction::Quit => return Ok(true),
-            KeyAction::Escape => {
-                self.mode = AppMode::Normal;
-                self.g_prefix = false;
-            }
-            KeyAction::Up => self.selection.previous(),
-            KeyAction::Down => self.selection.next(self.filtered_issues.len(), 20),
-            KeyAction::PageDown => {
+    /// Run a command from a key binding or the palette, returning whether
+    /// to quit
+    fn run_command(&mut self, command: Command) -> Result<bool> {
+        if self.board.is_some() && self.run_board_command(&command)? {
+            return Ok(false);
+        }
+        match command {
+            Command::Quit => return Ok(true),
+            Command::Up => self.selection.previous(),
+            Command::Down => self.selection.next(self.filtered_issues.len(), 20),
+            Command::Left | Command::Right => {}
+            Command::PageDown => {
                 self.selection.page_down(self.filtered_issues.len(), 20);
             }
-            KeyAction::PageUp => self.selection.page_up(),
-            KeyAction::Char('g') => {
-                if self.g_prefix {
-                    self.selection.top();
-                    self.g_prefix = false;
-                } else {
-                    self.g_prefix = true;
-                }
+            Command::PageUp => self.selection.page_up(),
+            Command::Top => self.selection.top(),
+            Command::Bottom => self.selection.bottom(self.filtered_issues.len(), 20),
+            Command::ScrollDown => {
+                self.details_scroll += 1;
+                return Ok(false);
             }
-            KeyAction::Char('G') => {
-                self.selection.bottom(self.filtered_issues.len(), 20);
-                self.g_prefix = false;
-            }
-            KeyAction::Char('q') => {
-                return Ok(true);
+            Command::ScrollUp => {
+                self.details_scroll = self.details_scroll.saturating_sub(1);
+                return Ok(false);
             }
-            KeyAction::Char('a') => {
+            Command::Add => {
                 self.mode = AppMode::AddIssue;
             }
-            KeyAction::Char('e') => {
+            Command::Edit => {
                 if let Some(issue) = self.current_issue() {
                     self.issue_form = IssueForm::from_issue(issue);
                     self.mode = AppMode::EditIssue;
                 }
             }
-            KeyAction::Char('1') => {
-                let _ = self.change_issue_status(trx_core::Status::Open);
-            }
-            KeyAction::Char('2') => {
-                let _ = self.change_issue_status(trx_core::Status::InProgress);
-            }
-            KeyAction::Char('3') => {
-                let _ = self.change_issue_status(trx_core::Status::Blocked);
-            }
-            KeyAction::Char('4') => {
-                let _ = self.change_issue_status(trx_core::Status::Closed);
-            }
-            KeyAction::Char('c') => {
-                let _ = self.close_issue();
+            Command::EditText => {
+                if self.current_issue().is_some() {
+                    self.mode = AppMode::WhichKey(WhichKeyContext::Edit);
+                }
             }
-            KeyAction::Char(' ') => {
+            Command::Close => self.edit_targets(BulkAction::Close),
+            Command::Status(status) => self.edit_targets(BulkAction::Status(status)),
+            Command::Priority(p) => self.edit_targets(BulkAction::Priority(p)),
+            Command::AddLabel(label) => self.edit_targets(BulkAction::AddLabel(label)),
+            Command::RemoveLabel(label) => self.edit_targets(BulkAction::RemoveLabel(label)),
+            Command::Assign(who) => self.edit_targets(BulkAction::Assign(who)),
+            Command::Select => {
                 self.selection.toggle_selection();
                 self.selection.next(self.filtered_issues.len(), 20);
             }
-            KeyAction::SelectAll => {
+            Command::SelectAll => {
                 self.selection.select_all(self.filtered_issues.len());
                 self.show_status("All items selected".to_string());
             }
-            KeyAction::Enter => {
-                self.mode = AppMode::Normal;
-            }
-            KeyAction::Char('V') => {
+            Command::ClearSelection => {
                 self.selection.deselect_all();
                 self.show_status("Selection cleared".to_string());
             }
-            KeyAction::Char('/') => {
+            Command::Bulk => {
+                if self.selection.selected_indices.is_empty() {
+                    self.show_status("Select issues first".to_string());
+                } else {
+                    self.bulk = Some(BulkStep::Menu);
+                    self.mode = AppMode::Bulk;
+                }
+            }
+            Command::Undo => self.undo_bulk()?,
+            Command::Search => {
                 self.mode = AppMode::Search;
                 self.search_query.clear();
             }
-            KeyAction::Char('?') => {
-                self.mode = AppMode::Help;
+            Command::Filter => {
+                self.mode = AppMode::Filter;
             }
-            KeyAction::Char('s') => {
+            Command::FilterStatus => {
+                self.mode = AppMode::WhichKey(WhichKeyContext::Status);
+            }
+            Command::FilterType => {
+                self.mode = AppMode::WhichKey(WhichKeyContext::Type);
+            }
+            Command::FilterPriority => {
+                self.mode = AppMode::WhichKey(WhichKeyContext::Priority);
+            }
+            Command::FilterLabel => {
+                self.mode = AppMode::WhichKey(WhichKeyContext::Labels);
+            }
+            Command::SortMenu => {
                 self.mode = AppMode::Sort;
             }
-            KeyAction::Char('r') => {
+            Command::Sort(key) => self.sort_by(key),
+            Command::Refresh => {
                 self.apply_filters()?;
                 self.show_status("Refreshed".to_string());
             }
-            KeyAction::Char('t') => {
-                self.mode = AppMode::WhichKey(WhichKeyContext::Type);
+            Command::Graph => self.show_view(View::Graph)?,
+            Command::Dashboard => self.show_view(View::Dashboard)?,
+            Command::Board => {
+                if self.board.is_some() {
+                    self.board = None;
+                } else {
+                    self.show_view(View::Board)?;
+                }
             }
-            KeyAction::Char('p') => {
-                self.mode = AppMode::WhichKey(WhichKeyContext::Priority);
+            Command::CardLeft | Command::CardRight | Command::Group => {
+                self.show_status("Only available on the board".to_string());
             }
-            KeyAction::Char('l') => {
-                self.mode = AppMode::WhichKey(WhichKeyContext::Labels);
+            Command::View(view) => self.show_view(view)?,
+            Command::Palette => {
+                self.palette = Some(Palette::default());
+                self.mode = AppMode::Palette;
             }
-            KeyAction::Char('f') => {
-                self.mode = AppMode::Filter;
+            Command::Help => {
+                self.mode = AppMode::Help;
+            }
+        }
+
+        self.details_scroll = 0;
+        self.follow_board();
+        Ok(false)
+    }
+
+    /// Commands that act differently on the board, returning whether the
+    /// command was handled here
+    fn run_board_command(&mut self, command: &Command) -> Result<bool> {
+        let columns = board::columns(&self.listed_issues(), self.board_grouping());
+        let Some(board) = self.board.as_mut() else {
+            return Ok(false);
+        };
+        let index = self.selection.index;
+        let moved = match command {
+            Command::Up => board.move_vertical(&columns, index, false),
+            Command::Down => board.move_vertical(&columns, index, true),
+            Command::Left => board.move_horizontal(&columns, index, false),
+            Command::Right => board.move_horizontal(&columns, index, true),
+            Command::CardLeft => {
+                self.move_card(false)?;
+                return Ok(true);
+            }
+            Command::CardRight => {
+                self.move_card(true)?;
+                return Ok(true);
+            }
+            Command::Group => {
+                board.grouping = board.grouping.next();
+                board.column = 0;
+                self.follow_board();
+                return Ok(true);
+            }
+            _ => return Ok(false),
+        };
+        if let Some(index) = moved {
+            self.selection.index = index;
+        }
+        Ok(true)
+    }
+
+    /// Switch layout, or reset the filters to a preset
+    fn show_view(&mut self, view: View) -> Result<()> {
+        match view {
+            View::List => {
+                self.board = None;
+            }
+            View::Board => {
+                if self.board.is_none() {
+                    self.board = Some(BoardState::new());
+                }
+            }
+            View::Graph => {
+                let scope = match self.current_issue() {
+                    Some(issue) => Scope::Neighbourhood(issue.id.clone()),
+                    None => Scope::Backlog,
+                };
+                self.open_graph(scope, None);
+                self.mode = AppMode::Graph;
+            }
+            View::Dashboard => {
+                self.refresh_stats();
+                self.mode = AppMode::Dashboard;
+            }
+            View::All | View::Ready | View::Blocked | View::Triage => {
+                self.filter_state = FilterState::new();
+                self.filter_state.ready_only = view == View::Ready;
+                self.filter_state.show_blocked = view == View::Blocked;
+                self.filter_state.unassigned_only = view == View::Triage;
+                self.apply_filters()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply an edit to the selected issues, or the current one if none are
+    /// selected, reporting failures in the status bar
+    fn edit_targets(&mut self, action: BulkAction) {
+        let single = self.selection.selected_indices.is_empty();
+        let result = match action {
+            BulkAction::Close if single => self.close_issue(),
+            BulkAction::Status(status) if single => self.change_issue_status(status),
+            action => self.run_bulk(action),
+        };
+        if let Err(e) = result {
+            self.show_status(format!("Error: {}", e));
+        }
+    }
+
+    fn handle_palette_mode(&mut self, action: KeyAction) -> Result<bool> {
+        let Some(palette) = self.palette.as_mut() else {
+            self.mode = AppMode::Normal;
+            return Ok(false);
+        };
+        match action {
+            KeyAction::Quit => return Ok(true),
+            KeyAction::Escape => {
+                self.palette = None;
+                self.mode = AppMode::Normal;
+            }
+            KeyAction::Up => palette.move_selection(false),
+            KeyAction::Down => palette.move_selection(true),
+            KeyAction::Tab => palette.complete(),
+            KeyAction::Backspace => {
+                palette.input.pop();
+                palette.selected = 0;
+            }
+            KeyAction::Char(c) => {
+                palette.input.push(c);
+                palette.selected = 0;
+            }
+            KeyAction::Enter => match palette.submit() {
+                Outcome::Run(command) => {
+                    self.palette = None;
+                    self.mode = AppMode::Normal;
+                    return self.run_command(command);
+                }
+                Outcome::Edit => {}
+                Outcome::Error(e) => self.show_status(e),
+            },
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Move the current card to the previous or next status column
+    fn move_card(&mut self, right: bool) -> Result<()> {
+        if self.board_grouping() != Grouping::Status {
+            self.show_status("Group the board by status to move cards".to_string());
+            return Ok(());
+        }
+        let Some(issue) = self.current_issue() else {
+            return Ok(());
+        };
+        let id = issue.id.clone();
+        let Some(column) = STATUS_COLUMNS
+            .iter()
+            .position(|s| *s == issue.status)
+            .and_then(|col| {
+                if right {
+                    col.checked_add(1)
+                } else {
+                    col.checked_sub(1)
+                }
+            })
+            .filter(|&col| col < STATUS_COLUMNS.len())
+        else {
+            return Ok(());
+        };
+
+        self.change_issue_status(STATUS_COLUMNS[column])?;
+        match self.filtered_issues.iter().position(|l| l.issue.id == id) {
+            Some(index) => {
+                self.selection.index = index;
+                if let Some(board) = self.board.as_mut() {
+                    board.column = column;
+                }
+            }
+            None => self.show_status(format!(
+                "{} moved to {}, hidden by filters",
+                id, STATUS_COLUMNS[column]
+            )),
+        }
+        Ok(())
+    }
+
+    fn listed_issues(&self) -> Vec<&Issue> {
+        self.filtered_issues.iter().map(|l| &l.issue).collect()
+    }
+
+    fn board_grouping(&self) -> Grouping {
+        self.board.as_ref().map_or(Grouping::Status, |b| b.grouping)
+    }
+
+    /// Keep the board's cursor column on the selected issue
+    fn follow_board(&mut self) {
+        if self.board.is_none() {
+            return;
+        }
+        let columns = board::columns(&self.listed_issues(), self.board_grouping());
+        if let Some(board) = self.board.as_mut() {
+            board.follow(&columns, self.selection.index);
+        }
+    }
+
+    fn handle_graph_mode(&mut self, action: KeyAction) -> Result<bool> {
+        let Some(graph) = self.graph.as_mut() else {
+            self.mode = AppMode::Normal;
+            return Ok(false);
+        };
+        match action {
+            KeyAction::Quit => return Ok(true),
+            KeyAction::Escape | KeyAction::Char('q') | KeyAction::Char('v') => {
+                self.graph = None;
+                self.mode = AppMode::Normal;
+            }
+            KeyAction::Up => graph.move_vertical(false),
+            KeyAction::Down => graph.move_vertical(true),
+            KeyAction::Left => graph.move_horizontal(false),
+            KeyAction::Right => graph.move_horizontal(true),
+            KeyAction::Enter => {
+                if let Some(id) = graph.selected().map(String::from) {
+                    self.open_graph(Scope::Neighbourhood(id.clone()), Some(id));
+                }
+            }
+            KeyAction::Char('a') => {
+                let focus = graph.selected().map(String::from);
+                let scope = match (&graph.scope, &focus) {
+                    (Scope::Backlog, Some(id)) => Scope::Neighbourhood(id.clone()),
+                    _ => Scope::Backlog,
+                };
+                self.open_graph(scope, focus);
+            }
+            KeyAction::Char('m') => {
+                let selected = graph.selected().map(String::from);
+                graph.marked = if graph.marked == selected {
+                    None
+                } else {
+                    selected
+                };
+                let msg = match &graph.marked {
+                    Some(id) => format!("Marked {} as blocker; press b on the issue it blocks", id),
+                    None => "Mark cleared".to_string(),
+                };
+                self.show_status(msg);
+            }
+            KeyAction::Char('b') => {
+                if let Err(e) = self.toggle_blocks_edge() {
+                    self.show_status(format!("Error: {}", e));
+                }
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn handle_dashboard_mode(&mut self, action: KeyAction) -> Result<bool> {
+        match action {
+            KeyAction::Quit => return Ok(true),
+            KeyAction::Escape | KeyAction::Char('q') | KeyAction::Char('D') => {
+                self.stats = None;
+                self.mode = AppMode::Normal;
+            }
+            KeyAction::Char('r') => {
+                self.refresh_stats();
+                self.show_status("Refreshed".to_string());
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Recompute the dashboard from the store, limited to the repository
+    /// filter if one is set
+    fn refresh_stats(&mut self) {
+        let issues: Vec<&Issue> = self
+            .repos
+            .issues(true)
+            .into_iter()
+            .filter(|(repo, _)| {
+                self.filter_state
+                    .repo
+                    .as_deref()
+                    .is_none_or(|name| name == self.repos.name(*repo))
+            })
+            .map(|(_, i)| i)
+            .collect();
+        self.stats = Some(Stats::compute(&issues, chrono::Utc::now()));
+    }
+
+    /// Lay out the graph for a scope, keeping the mark across rebuilds
+    fn open_graph(&mut self, scope: Scope, focus: Option<String>) {
+        let marked = self.graph.as_ref().and_then(|g| g.marked.clone());
+        let issues: Vec<&Issue> = self
+            .repos
+            .issues(true)
+            .into_iter()
+            .map(|(_, issue)| issue)
+            .collect();
+        let focus = focus.or_else(|| match &scope {
+            Scope::Neighbourhood(id) => Some(id.clone()),
+            Scope::Backlog => None,
+        });
+        let mut graph = GraphView::build(&issues, scope, focus.as_deref());
+        graph.marked = marked;
+        if graph.is_empty() {
+            self.show_status("No issues to graph".to_string());
+        } else if graph.has_cycles() {
+            self.show_status("Dependency cycle detected (shown in red)".to_string());
+        }
+        self.graph = Some(graph);
+    }
+
+    /// Add or remove "marked blocks selected", refusing edges that would
+    /// close a cycle
+    fn toggle_blocks_edge(&mut self) -> Result<()> {
+        let Some(graph) = &self.graph else {
+            return Ok(());
+        };
+        let (Some(blocker), Some(target)) = (graph.marked.clone(), graph.selected()) else {
+            self.show_status("Mark a blocker with m first".to_string());
+            return Ok(());
+        };
+        let target = target.to_string();
+        if blocker == target {
+            self.show_status("An issue cannot block itself".to_string());
+            return Ok(());
+        }
+
+        let all = self.repos.issues(true);
+        let Some((repo, issue)) = all.iter().find(|(_, i)| i.id == target) else {
+            return Ok(());
+        };
+        let repo = *repo;
+        let base = (*issue).clone();
+        let mut issue = base.clone();
+        let exists = issue
+            .dependencies
+            .iter()
+            .any(|d| d.depends_on_id == blocker && d.dep_type == DependencyType::Blocks);
+
+        let msg = if exists {
+            issue
+                .dependencies
+                .retain(|d| !(d.depends_on_id == blocker && d.dep_type == DependencyType::Blocks));
+            issue.updated_at = chrono::Utc::now();
+            format!("{} no longer blocks {}", blocker, target)
+        } else {
+            let issues: Vec<&Issue> = all.iter().map(|(_, i)| *i).collect();
+            if IssueGraph::from_issues(&issues).would_create_cycle(&target, &blocker) {
+                self.show_status(format!(
+                    "Refused: {} blocking {} would create a cycle",
+                    blocker, target
+                ));
+                return Ok(());
+            }
+            issue.add_dependency(blocker.clone(), DependencyType::Blocks);
+            format!("{} now blocks {}", blocker, target)
+        };
+
+        if self.save_issue(repo, &base, issue)? {
+            self.refresh_graph(Some(target));
+            self.show_status(msg);
+        }
+        Ok(())
+    }
+
+    /// Rebuild the open graph, keeping its scope
+    fn refresh_graph(&mut self, focus: Option<String>) {
+        if let Some(graph) = &self.graph {
+            let scope = graph.scope.clone();
+            let focus = focus.or_else(|| graph.selected().map(String::from));
+            self.open_graph(scope, focus);
+        }
+    }
+
+    fn handle_bulk_mode(&mut self, action: KeyAction) -> Result<bool> {
+        let Some(step) = self.bulk.take() else {
+            self.mode = AppMode::Normal;
+            return Ok(false);
+        };
+        let next = match (step, action) {
+            (_, KeyAction::Quit) => return Ok(true),
+            (_, KeyAction::Escape) => None,
+            (BulkStep::Menu, KeyAction::Char(c)) => match c {
+                'c' => Some(BulkStep::Confirm(BulkAction::Close)),
+                '1'..='4' => {
+                    let status = STATUS_COLUMNS[c as usize - '1' as usize];
+                    Some(BulkStep::Confirm(BulkAction::Status(status)))
+                }
+                'p' => Some(BulkStep::Priority),
+                '+' => Some(BulkStep::Input(BulkInput::AddLabel, String::new())),
+                '-' => Some(BulkStep::Input(BulkInput::RemoveLabel, String::new())),
+                'a' => Some(BulkStep::Input(BulkInput::Assign, String::new())),
+                'd' => Some(BulkStep::Confirm(BulkAction::Delete)),
+                _ => Some(BulkStep::Menu),
+            },
+            (BulkStep::Priority, KeyAction::Char(c @ '0'..='4')) => {
+                Some(BulkStep::Confirm(BulkAction::Priority(c as u8 - b'0')))
+            }
+            (BulkStep::Input(input, text), KeyAction::Enter) => match input.action(&text) {
+                Some(action) => Some(BulkStep::Confirm(action)),
+                None => Some(BulkStep::Input(input, text)),
+            },
+            (BulkStep::Input(input, mut text), KeyAction::Backspace) => {
+                text.pop();
+                Some(BulkStep::Input(input, text))
+            }
+            (BulkStep::Input(input, mut text), KeyAction::Char(c)) => {
+                text.push(c);
+                Some(BulkStep::Input(input, text))
+            }
+            (BulkStep::Confirm(action), KeyAction::Char('y') | KeyAction::Enter) => {
+                self.mode = AppMode::Normal;
+                self.run_bulk(action)?;
+                return Ok(false);
+            }
+            (BulkStep::Confirm(_), KeyAction::Char('n')) => None,
+            (step, _) => Some(step),
+        };
+        if next.is_none() {
+            self.mode = AppMode::Normal;
+        }
+        self.bulk = next;
+        Ok(false)
+    }
+
+    /// Issues picked with Space, with their repositories
+    fn selected_issues(&self) -> Vec<&ListedIssue> {
+        let mut indices: Vec<usize> = self.selection.selected_indices.iter().copied().collect();
+        indices.sort_unstable();
+        indices
+            .into_iter()
+            .filter_map(|i| self.filtered_issues.get(i))
+            .collect()
+    }
+
+    /// The selected issues, or the current one if none are selected
+    fn targets(&self) -> Vec<&ListedIssue> {
+        if self.selection.selected_indices.is_empty() {
+            self.current_listed().into_iter().collect()
+        } else {
+            self.selected_issues()
+        }
+    }
+
+    /// Apply an action to every target issue as one save and undo step
+    fn run_bulk(&mut self, action: BulkAction) -> Result<()> {
+        let user = std::env::var("USER").ok();
+        let changes: Vec<(usize, Issue, Issue)> = self
+            .targets()
+            .into_iter()
+            .filter_map(|l| {
+                let updated = action.apply(&l.issue, user.as_deref())?;
+                Some((l.repo, l.issue.clone(), updated))
+            })
+            .collect();
+        if changes.is_empty() {
+            self.show_status("Nothing to change".to_string());
+            return Ok(());
+        }
+
+        let count = changes.len();
+        let changed_on_disk = self.repos.save_all(changes.clone())?;
+        self.apply_filters()?;
+        if !changed_on_disk.is_empty() {
+            self.show_status(format!(
+                "Nothing saved: {} changed on disk, review and try again",
+                changed_on_disk.join(", ")
+            ));
+            return Ok(());
+        }
+
+        let description = format!("{} {} issues", action.describe(), count);
+        self.undo.push(UndoStep {
+            description: description.clone(),
+            changes,
+        });
+        self.selection.deselect_all();
+        self.refresh_graph(None);
+        self.show_status(format!("Done: {} (u to undo)", description));
+        Ok(())
+    }
+
+    /// Revert the most recent bulk action
+    fn undo_bulk(&mut self) -> Result<()> {
+        let Some(step) = self.undo.pop() else {
+            self.show_status("Nothing to undo".to_string());
+            return Ok(());
+        };
+        let now = chrono::Utc::now();
+        let reverted = step
+            .changes
+            .into_iter()
+            .map(|(repo, mut before, after)| {
+                before.updated_at = now;
+                (repo, after, before)
+            })
+            .collect();
+        let changed_on_disk = self.repos.save_all(reverted)?;
+        self.apply_filters()?;
+        self.refresh_graph(None);
+        if changed_on_disk.is_empty() {
+            self.show_status(format!("Undid: {}", step.description));
+        } else {
+            self.show_status(format!(
+                "Cannot undo: {} changed since",
+                changed_on_disk.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Queue the current issue's text for the editor
+    fn request_edit(&mut self, field: EditField) {
+        let Some(ListedIssue { repo, issue }) = self.current_listed() else {
+            return;
+        };
+        let configured = self.repos.config(*repo).ok().and_then(|c| c.editor);
+        self.pending_edit = Some(EditRequest {
+            repo: *repo,
+            base: issue.clone(),
+            field,
+            command: editor::editor_command(configured.as_deref()),
+        });
+    }
+
+    /// Save text back from the editor
+    fn finish_edit(&mut self, request: EditRequest, edited: Result<String>) -> Result<()> {
+        let text = match edited {
+            Ok(text) => text.trim_end().to_string(),
+            Err(e) => {
+                self.show_status(format!("Error: {}", e));
+                return Ok(());
+            }
+        };
+        let EditRequest {
+            repo, base, field, ..
+        } = request;
+        if text == field.text(&base).trim_end() {
+            self.show_status(format!("{} unchanged", field.name()));
+            return Ok(());
+        }
+
+        let mut issue = base.clone();
+        let value = (!text.is_empty()).then_some(text);
+        match field {
+            EditField::Description => issue.description = value,
+            EditField::Notes => issue.notes = value,
+            EditField::Comment => {
+                let Some(text) = value else {
+                    return Ok(());
+                };
+                issue.add_comment(std::env::var("USER").ok(), text);
+            }
+        }
+        issue.updated_at = chrono::Utc::now();
+        if self.save_issue(repo, &base, issue)? {
+            self.show_status(format!("Saved {} of {}", field.name(), base.id));
+        }
+        Ok(())
+    }
+
+    /// Save an edit made to `base`, or hold it for the conflict prompt if
+    /// the issue changed on disk; returns whether it was saved
+    fn save_issue(&mut self, repo: usize, base: &Issue, issue: Issue) -> Result<bool> {
+        let conflict = self.repos.save(repo, base, issue)?;
+        self.apply_filters()?;
+        let Some(conflict) = conflict else {
+            return Ok(true);
+        };
+        if conflict.changes()?.0.is_empty() {
+            // Nothing of ours to keep, the reload already shows theirs
+            return Ok(true);
+        }
+        self.show_status(format!("{} was changed on disk", conflict.theirs.id));
+        self.conflict = Some(conflict);
+        self.mode = AppMode::Conflict;
+        Ok(false)
+    }
+
+    fn handle_conflict_mode(&mut self, action: KeyAction) -> Result<bool> {
+        let Some(conflict) = self.conflict.take() else {
+            self.mode = AppMode::Normal;
+            return Ok(false);
+        };
+        let id = conflict.theirs.id.clone();
+        match action {
+            KeyAction::Quit => return Ok(true),
+            KeyAction::Char('m') => {
+                let merged = conflict.merged()?;
+                self.mode = if self.graph.is_some() {
+                    AppMode::Graph
+                } else {
+                    AppMode::Normal
+                };
+                if self.save_issue(conflict.repo, &conflict.theirs, merged)? {
+                    self.refresh_graph(None);
+                    self.show_status(format!("Merged your changes into {}", id));
+                }
             }
-            _ => {
-                self.g_prefix = false;
+            KeyAction::Char('r') | KeyAction::Escape => {
+                self.mode = if self.graph.is_some() {
+                    AppMode::Graph
+                } else {
+                    AppMode::Normal
+                };
+                self.refresh_graph(None);
+                self.show_status(format!("Discarded your changes to {}", id));
             }
+            _ => self.conflict = Some(conflict),
         }
-
-        self.details_scroll = 0;
         Ok(false)
     }
 
+    /// Pick up changes other processes made to `.trx/`
+    fn reload_from_disk(&mut self) {
+        match self.repos.reload_changed() {
+            Ok(false) => {}
+            Ok(true) => {
+                if let Err(e) = self.apply_filters() {
+                    self.show_status(format!("Error: {}", e));
+                    return;
+                }
+                self.refresh_graph(None);
+                self.show_status("Reloaded changes from disk".to_string());
+            }
+            Err(e) => self.show_status(format!("Reload failed: {}", e)),
+        }
+    }
+
     fn handle_search_mode(&mut self, action: KeyAction) -> Result<bool> {
         match action {
             KeyAction::Quit | KeyAction::Char('q') => {
@@ -661,6 +1444,10 @@ impl App {
                 self.sort_by_status();
                 self.mode = AppMode::Normal;
             }
+            KeyAction::Char('4') => {
+                self.sort_by_updated();
+                self.mode = AppMode::Normal;
+            }
             _ => {}
         }
         Ok(false)
@@ -701,6 +1488,25 @@ impl App {
             KeyAction::Char('C') => {
                 self.toggle_type_filter(trx_core::IssueType::Chore);
             }
+            // Dependency filters, one at a time
+            KeyAction::Char('y') => {
+                self.filter_state.ready_only = !self.filter_state.ready_only;
+                self.filter_state.show_blocked = false;
+                self.apply_filters()?;
+            }
+            KeyAction::Char('d') => {
+                self.filter_state.show_blocked = !self.filter_state.show_blocked;
+                self.filter_state.ready_only = false;
+                self.apply_filters()?;
+            }
+            KeyAction::Char('n') => {
+                self.filter_state.unassigned_only = !self.filter_state.unassigned_only;
+                self.apply_filters()?;
+            }
+            // Repository filter
+            KeyAction::Char('R') => {
+                self.cycle_repo_filter();
+            }
             // Priority filters (show only that priority)
             KeyAction::Char('0') => {
                 self.filter_by_priority(Some(0));
@@ -728,12 +1534,33 @@ impl App {
 
     fn filter_by_priority(&mut self, priority: Option<u8>) {
         if let Some(p) = priority {
-            self.filtered_issues.retain(|i| i.priority == p);
+            self.filtered_issues.retain(|i| i.issue.priority == p);
             self.show_status(format!("Filtered to P{}", p));
         }
         self.mode = AppMode::Normal;
     }
 
+    /// Step the repository filter through all repositories and back to none
+    fn cycle_repo_filter(&mut self) {
+        if !self.repos.is_multi() {
+            return;
+        }
+        let next = match &self.filter_state.repo {
+            None => Some(0),
+            Some(name) => self.repos.position(name).map(|i| i + 1),
+        };
+        self.filter_state.repo = next
+            .and_then(|i| self.repos.names().nth(i))
+            .map(String::from);
+        self.apply_filters().ok();
+        let shown = self
+            .filter_state
+            .repo
+            .as_deref()
+            .unwrap_or("all repositories");
+        self.show_status(format!("Showing {}", shown));
+    }
+
     fn reset_filters(&mut self) {
         self.filter_state = FilterState::new();
         self.apply_filters().ok();
@@ -742,6 +1569,20 @@ impl App {
     }
 
     fn handle_which_key_mode(&mut self, ctx: WhichKeyContext, action: KeyAction) -> Result<bool> {
+        if ctx == WhichKeyContext::Edit {
+            let field = match action {
+                KeyAction::Char('d') => Some(EditField::Description),
+                KeyAction::Char('n') => Some(EditField::Notes),
+                KeyAction::Char('c') => Some(EditField::Comment),
+                _ => None,
+            };
+            self.mode = AppMode::Normal;
+            if let Some(field) = field {
+                self.request_edit(field);
+            }
+            return Ok(false);
+        }
+
         match action {
             KeyAction::Escape => {
                 self.mode = AppMode::Normal;
@@ -800,12 +1641,10 @@ impl App {
                 }
                 _ => {}
             },
-            KeyAction::Char('c') => {
-                if ctx == WhichKeyContext::Status {
-                    self.filter_state.show_closed = !self.filter_state.show_closed;
-                    self.apply_filters()?;
-                    self.mode = AppMode::Normal;
-                }
+            KeyAction::Char('c') if ctx == WhichKeyContext::Status => {
+                self.filter_state.show_closed = !self.filter_state.show_closed;
+                self.apply_filters()?;
+                self.mode = AppMode::Normal;
             }
             KeyAction::Char('r') => {
                 self.apply_filters()?;
@@ -836,33 +1675,48 @@ impl App {
     }
 
     fn set_priority_filter(&mut self, priority: u8) {
-        self.filtered_issues.retain(|i| i.priority == priority);
+        self.filtered_issues
+            .retain(|i| i.issue.priority == priority);
         self.show_status(format!("Filtered to P{}", priority));
         self.mode = AppMode::Normal;
     }
 
+    fn sort_by(&mut self, key: SortKey) {
+        match key {
+            SortKey::Priority => self.sort_by_priority(),
+            SortKey::Created => self.sort_by_date(),
+            SortKey::Updated => self.sort_by_updated(),
+            SortKey::Status => self.sort_by_status(),
+        }
+    }
+
     fn sort_by_priority(&mut self) {
-        self.filtered_issues
-            .sort_by(|a, b| a.priority.cmp(&b.priority));
+        self.filtered_issues.sort_by_key(|a| a.issue.priority);
         self.show_status("Sorted by priority".to_string());
     }
 
     fn sort_by_date(&mut self) {
         self.filtered_issues
-            .sort_by(|a, b| b.created_at.cmp(&a.created_at));
+            .sort_by_key(|i| std::cmp::Reverse(i.issue.created_at));
         self.show_status("Sorted by date".to_string());
     }
 
+    fn sort_by_updated(&mut self) {
+        self.filtered_issues
+            .sort_by_key(|i| std::cmp::Reverse(i.issue.updated_at));
+        self.show_status("Sorted by last update".to_string());
+    }
+
     fn sort_by_status(&mut self) {
         self.filtered_issues.sort_by(|a, b| {
-            let a_order = match a.status {
+            let a_order = match a.issue.status {
                 Status::Open => 0,
                 Status::InProgress => 1,
                 Status::Blocked => 2,
                 Status::Closed => 3,
                 Status::Tombstone => 4,
             };
-            let b_order = match b.status {
+            let b_order = match b.issue.status {
                 Status::Open => 0,
                 Status::InProgress => 1,
                 Status::Blocked => 2,
@@ -886,6 +1740,11 @@ impl App {
             self.status_message = None;
             self.status_message_time = None;
         }
+
+        if self.last_watch.elapsed() >= WATCH_INTERVAL {
+            self.last_watch = Instant::now();
+            self.reload_from_disk();
+        }
     }
 
     fn handle_add_issue_mode(&mut self, action: KeyAction) -> Result<bool> {
@@ -1001,9 +1860,10 @@ impl App {
                     self.show_status("Title cannot be empty".to_string());
                     return Ok(false);
                 }
-                self.update_issue()?;
                 self.mode = AppMode::Normal;
-                self.show_status("Issue updated".to_string());
+                if self.update_issue()? {
+                    self.show_status("Issue updated".to_string());
+                }
             }
             KeyAction::Up | KeyAction::Down => match self.issue_form.selected_field {
                 FormField::IssueType => {
@@ -1078,7 +1938,8 @@ impl App {
     fn create_issue(&mut self) -> Result<()> {
         use trx_core::generate_id;
 
-        let prefix = self.store.prefix()?;
+        let repo = self.target_repo();
+        let prefix = self.repos.store_mut(repo).prefix()?;
         let id = generate_id(&prefix);
 
         let mut issue = trx_core::Issue::new(id, self.issue_form.title.clone());
@@ -1091,61 +1952,92 @@ impl App {
         issue.priority = self.issue_form.priority;
         issue.status = self.issue_form.status;
 
-        self.store.create(issue)?;
+        self.repos.create(repo, issue)?;
         self.apply_filters()?;
         Ok(())
     }
 
-    fn update_issue(&mut self) -> Result<()> {
-        if let Some(issue) = self.current_issue() {
-            let mut updated_issue = issue.clone();
-            updated_issue.title = self.issue_form.title.clone();
-            updated_issue.description = if self.issue_form.description.trim().is_empty() {
-                None
-            } else {
-                Some(self.issue_form.description.clone())
-            };
-            updated_issue.issue_type = self.issue_form.issue_type;
-            updated_issue.priority = self.issue_form.priority;
-            updated_issue.status = self.issue_form.status;
-            updated_issue.updated_at = chrono::Utc::now();
+    /// Save the edit form, returning whether it was written
+    fn update_issue(&mut self) -> Result<bool> {
+        let Some(base) = self
+            .issue_form
+            .base
+            .clone()
+            .or_else(|| self.current_issue().cloned())
+        else {
+            return Ok(false);
+        };
+        let Some(repo) = self
+            .filtered_issues
+            .iter()
+            .find(|l| l.issue.id == base.id)
+            .map(|l| l.repo)
+        else {
+            self.show_status(format!("{} is no longer listed", base.id));
+            return Ok(false);
+        };
 
-            self.store.update(updated_issue)?;
-            self.apply_filters()?;
-        }
-        Ok(())
+        let mut updated_issue = base.clone();
+        updated_issue.title = self.issue_form.title.clone();
+        updated_issue.description = if self.issue_form.description.trim().is_empty() {
+            None
+        } else {
+            Some(self.issue_form.description.clone())
+        };
+        updated_issue.issue_type = self.issue_form.issue_type;
+        updated_issue.priority = self.issue_form.priority;
+        updated_issue.status = self.issue_form.status;
+        updated_issue.updated_at = chrono::Utc::now();
+
+        self.save_issue(repo, &base, updated_issue)
     }
 
     fn change_issue_status(&mut self, new_status: trx_core::Status) -> Result<()> {
-        if let Some(issue) = self.current_issue() {
-            let mut updated_issue = issue.clone();
-            updated_issue.status = new_status;
-            if new_status == trx_core::Status::Closed {
-                updated_issue.closed_at = Some(chrono::Utc::now());
-            }
-            updated_issue.updated_at = chrono::Utc::now();
+        if let Some(ListedIssue { repo, issue }) = self.current_listed() {
+            let repo = *repo;
+            let Some(updated_issue) = BulkAction::Status(new_status).apply(issue, None) else {
+                return Ok(());
+            };
 
-            self.store.update(updated_issue)?;
-            self.apply_filters()?;
-            self.show_status(format!("Issue status changed to {}", new_status));
+            let base = issue.clone();
+            if self.save_issue(repo, &base, updated_issue)? {
+                self.show_status(format!("Issue status changed to {}", new_status));
+            }
         }
         Ok(())
     }
 
     fn close_issue(&mut self) -> Result<()> {
-        if let Some(issue) = self.current_issue() {
+        if let Some(ListedIssue { repo, issue }) = self.current_listed() {
+            let repo = *repo;
             let mut updated_issue = issue.clone();
             updated_issue.close(None);
-            self.store.update(updated_issue)?;
-            self.apply_filters()?;
-            self.show_status("Issue closed".to_string());
+            let base = issue.clone();
+            if self.save_issue(repo, &base, updated_issue)? {
+                self.show_status("Issue closed".to_string());
+            }
         }
         Ok(())
     }
 
-    fn current_issue(&self) -> Option<&Issue> {
+    fn current_listed(&self) -> Option<&ListedIssue> {
         self.filtered_issues.get(self.selection.index)
     }
+
+    fn current_issue(&self) -> Option<&Issue> {
+        self.current_listed().map(|l| &l.issue)
+    }
+
+    /// Repository new issues go to: the filtered repository, else the
+    /// selected issue's, else the first
+    fn target_repo(&self) -> usize {
+        self.filter_state
+            .repo
+            .as_deref()
+            .and_then(|name| self.repos.position(name))
+            .or_else(|| self.current_listed().map(|l| l.repo))
+            .unwrap_or(0)
+    }
 }
 
 fn ui(f: &mut Frame, app: &mut App) {
@@ -1165,23 +2057,56 @@ fn ui(f: &mut Frame, app: &mut App) {
         ])
         .split(main_chunks[0]);
 
-    render_left_pane(f, app, content_chunks[0]);
-    render_middle_pane(f, app, content_chunks[1]);
-    render_right_pane(f, app, content_chunks[2]);
+    if app.mode == AppMode::Graph {
+        render_graph(f, app, main_chunks[0]);
+    } else if let (AppMode::Dashboard, Some(stats)) = (app.mode, &app.stats) {
+        stats::render(f, main_chunks[0], stats, &app.theme);
+    } else if let Some(board) = &app.board {
+        let board_area = Rect {
+            x: content_chunks[1].x,
+            width: content_chunks[1].width + content_chunks[2].width,
+            ..content_chunks[1]
+        };
+        render_left_pane(f, app, content_chunks[0]);
+        board::render(
+            f,
+            board_area,
+            &app.listed_issues(),
+            board,
+            app.selection.index,
+            &app.theme,
+        );
+    } else {
+        render_left_pane(f, app, content_chunks[0]);
+        render_middle_pane(f, app, content_chunks[1]);
+        render_right_pane(f, app, content_chunks[2]);
+    }
     render_status_bar(f, app, main_chunks[1]);
 
     match &app.mode {
-        AppMode::Help => render_help_overlay(f),
-        AppMode::Sort => render_sort_overlay(f),
+        AppMode::Help => render_help_overlay(f, app),
+        AppMode::Sort => render_sort_overlay(f, &app.theme),
         AppMode::Filter => render_filter_overlay(f, app),
-        AppMode::WhichKey(ctx) => render_which_key_overlay(f, *ctx),
+        AppMode::WhichKey(ctx) => render_which_key_overlay(f, *ctx, &app.theme),
+        AppMode::AddIssue if app.repos.is_multi() => {
+            let title = format!("Add Issue to {}", app.repos.name(app.target_repo()));
+            render_issue_form(f, app, &title)
+        }
         AppMode::AddIssue => render_issue_form(f, app, "Add Issue"),
         AppMode::EditIssue => render_issue_form(f, app, "Edit Issue"),
+        AppMode::Conflict => render_conflict_overlay(f, app),
+        AppMode::Bulk => render_bulk_overlay(f, app),
+        AppMode::Palette => {
+            if let Some(palette) = &app.palette {
+                palette::render(f, palette, &app.keymap, &app.theme);
+            }
+        }
         _ => {}
     }
 }
 
 fn render_left_pane(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let mut items = vec![
         ListItem::new(Line::from(vec![Span::styled(
             "Filters",
@@ -1191,7 +2116,7 @@ fn render_left_pane(f: &mut Frame, app: &App, area: Rect) {
     ];
 
     items.push(ListItem::new(Line::from(vec![
-        Span::styled("[f]", Style::default().fg(Color::Cyan)),
+        Span::styled("[f]", theme.accent),
         Span::raw(" Status filters"),
     ])));
 
@@ -1216,7 +2141,7 @@ fn render_left_pane(f: &mut Frame, app: &App, area: Rect) {
     items.push(ListItem::new(""));
 
     items.push(ListItem::new(Line::from(vec![
-        Span::styled("[t]", Style::default().fg(Color::Cyan)),
+        Span::styled("[t]", theme.accent),
         Span::raw(" Type filters"),
     ])));
 
@@ -1236,11 +2161,39 @@ fn render_left_pane(f: &mut Frame, app: &App, area: Rect) {
         ])));
     }
 
-    if app.filter_state.ready_only {
+    if app.repos.is_multi() {
+        items.push(ListItem::new(""));
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("[R]", theme.accent),
+            Span::raw(" Repository"),
+        ])));
+        for name in app.repos.names() {
+            let enabled = app.filter_state.repo.as_deref().is_none_or(|r| r == name);
+            let prefix = if enabled { "[x]" } else { "[ ]" };
+            items.push(ListItem::new(Line::from(vec![
+                Span::raw("  "),
+                Span::styled(prefix, Style::default()),
+                Span::raw(format!(" {}", name)),
+            ])));
+        }
+    }
+
+    let dependency_filter = if app.filter_state.ready_only {
+        Some(("[y]", " Ready only"))
+    } else if app.filter_state.show_blocked {
+        Some(("[d]", " Blocked only"))
+    } else {
+        None
+    };
+    let assignee_filter = app
+        .filter_state
+        .unassigned_only
+        .then_some(("[n]", " Unassigned only"));
+    for (key, label) in dependency_filter.into_iter().chain(assignee_filter) {
         items.push(ListItem::new(""));
         items.push(ListItem::new(Line::from(vec![
-            Span::styled("[r]", Style::default().fg(Color::Yellow)),
-            Span::raw(" Ready only"),
+            Span::styled(key, theme.highlight),
+            Span::raw(label),
         ])));
     }
 
@@ -1248,38 +2201,33 @@ fn render_left_pane(f: &mut Frame, app: &App, area: Rect) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Blue))
+                .border_style(theme.border)
                 .title("Navigation"),
         )
-        .highlight_style(Style::default().bg(Color::DarkGray));
+        .highlight_style(theme.selection);
 
     f.render_widget(list, area);
 }
 
 fn render_middle_pane(f: &mut Frame, app: &mut App, area: Rect) {
+    let theme = &app.theme;
+    // Repository column, only when viewing a workspace
+    let repo_width = if app.repos.is_multi() {
+        app.repos.names().map(str::len).max().unwrap_or(0)
+    } else {
+        0
+    };
+
     let items: Vec<ListItem> = app
         .filtered_issues
         .iter()
         .enumerate()
-        .map(|(idx, issue)| {
+        .map(|(idx, listed)| {
+            let issue = &listed.issue;
             let is_selected = app.selection.selected_indices.contains(&idx);
             let is_cursor = idx == app.selection.index;
 
-            let status_style = match issue.status {
-                Status::Open => Style::default().fg(Color::Green),
-                Status::InProgress => Style::default().fg(Color::Yellow),
-                Status::Blocked => Style::default().fg(Color::Red),
-                Status::Closed => Style::default().fg(Color::DarkGray),
-                Status::Tombstone => Style::default().fg(Color::DarkGray),
-            };
-
-            let priority_color = match issue.priority {
-                0 => Color::Red,
-                1 => Color::Red,
-                2 => Color::Yellow,
-                3 => Color::Green,
-                _ => Color::DarkGray,
-            };
+            let status_style = theme.status(issue.status);
 
             let title = if issue.title.len() > 50 {
                 format!("{}...", &issue.title[..47])
@@ -1296,25 +2244,33 @@ fn render_middle_pane(f: &mut Frame, app: &mut App, area: Rect) {
                 prefix = "> ".to_string();
             }
 
-            let content = Line::from(vec![
-                Span::styled(prefix, Style::default()),
-                Span::styled(issue.id.clone(), Style::default().fg(Color::Cyan)),
+            let mut spans = vec![Span::styled(prefix, Style::default())];
+            if repo_width > 0 {
+                spans.push(Span::styled(
+                    format!(
+                        "{:<width$} ",
+                        app.repos.name(listed.repo),
+                        width = repo_width
+                    ),
+                    theme.repo,
+                ));
+            }
+            spans.extend([
+                Span::styled(issue.id.clone(), theme.accent),
                 Span::raw(" "),
                 Span::styled(
                     format!("[P{}] ", issue.priority),
-                    Style::default()
-                        .fg(priority_color)
-                        .add_modifier(Modifier::BOLD),
+                    theme.priority(issue.priority).add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(
                     format!("[{}] ", issue.issue_type),
-                    Style::default().fg(Color::Blue),
+                    theme.issue_type(issue.issue_type),
                 ),
                 Span::styled(format!("{} ", issue.status), status_style),
                 Span::styled(title, Style::default()),
             ]);
 
-            ListItem::new(content)
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -1322,14 +2278,10 @@ fn render_middle_pane(f: &mut Frame, app: &mut App, area: Rect) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Blue))
+                .border_style(theme.border)
                 .title(format!("Issues ({})", app.filtered_issues.len())),
         )
-        .highlight_style(
-            Style::default()
-                .bg(Color::DarkGray)
-                .add_modifier(Modifier::BOLD),
-        );
+        .highlight_style(theme.selection.add_modifier(Modifier::BOLD));
 
     f.render_stateful_widget(
         list,
@@ -1341,14 +2293,9 @@ fn render_middle_pane(f: &mut Frame, app: &mut App, area: Rect) {
 }
 
 fn render_right_pane(f: &mut Frame, app: &mut App, area: Rect) {
-    let content = if let Some(issue) = app.current_issue() {
-        let status_style = match issue.status {
-            Status::Open => Style::default().fg(Color::Green),
-            Status::InProgress => Style::default().fg(Color::Yellow),
-            Status::Blocked => Style::default().fg(Color::Red),
-            Status::Closed => Style::default().fg(Color::DarkGray),
-            Status::Tombstone => Style::default().fg(Color::DarkGray),
-        };
+    let theme = &app.theme;
+    let content = if let Some(ListedIssue { repo, issue }) = app.current_listed() {
+        let status_style = theme.status(issue.status);
 
         let priority_text = match issue.priority {
             0 => "Critical",
@@ -1360,12 +2307,7 @@ fn render_right_pane(f: &mut Frame, app: &mut App, area: Rect) {
 
         let mut lines = vec![
             Line::from(vec![
-                Span::styled(
-                    issue.id.clone(),
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
-                ),
+                Span::styled(issue.id.clone(), theme.accent.add_modifier(Modifier::BOLD)),
                 Span::raw(" "),
                 Span::styled(
                     issue.title.clone(),
@@ -1381,16 +2323,20 @@ fn render_right_pane(f: &mut Frame, app: &mut App, area: Rect) {
                 Span::raw("Priority: "),
                 Span::styled(
                     format!("P{} ({})", issue.priority, priority_text),
-                    Style::default(),
+                    theme.priority(issue.priority),
                 ),
             ]),
             Line::from(vec![
                 Span::raw("Type:     "),
                 Span::styled(
                     format!("{}", issue.issue_type),
-                    Style::default().fg(Color::Blue),
+                    theme.issue_type(issue.issue_type),
                 ),
             ]),
+            Line::from(vec![
+                Span::raw("Repo:     "),
+                Span::styled(app.repos.name(*repo), theme.repo),
+            ]),
             Line::from(vec![
                 Span::raw("Created:  "),
                 Span::styled(
@@ -1410,12 +2356,13 @@ fn render_right_pane(f: &mut Frame, app: &mut App, area: Rect) {
         if let Some(ref desc) = issue.description {
             lines.push(Line::from(""));
             lines.push(Line::from("Description:"));
-            for line in desc.lines() {
-                lines.push(Line::from(vec![
-                    Span::raw("  "),
-                    Span::styled(line, Style::default()),
-                ]));
-            }
+            lines.extend(markdown::render(desc, "  ", theme));
+        }
+
+        if let Some(ref notes) = issue.notes {
+            lines.push(Line::from(""));
+            lines.push(Line::from("Notes:"));
+            lines.extend(markdown::render(notes, "  ", theme));
         }
 
         if !issue.dependencies.is_empty() {
@@ -1429,19 +2376,48 @@ fn render_right_pane(f: &mut Frame, app: &mut App, area: Rect) {
                             "{} -> {} ({})",
                             dep.issue_id, dep.depends_on_id, dep.dep_type
                         ),
-                        Style::default().fg(Color::Yellow),
+                        theme.highlight,
                     ),
                 ]));
             }
         }
 
+        let blockers = app.deps.blockers(&issue.id);
+        if !blockers.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from("Blocked by:"));
+            lines.extend(
+                blockers
+                    .iter()
+                    .map(|id| dependency_line(app, id, theme.danger)),
+            );
+            let upstream = app.deps.upstream(&issue.id);
+            if app.deps.is_transitively_blocked(&issue.id) {
+                lines.push(Line::from(Span::styled(
+                    format!("  waiting on {} open issues up the chain", upstream),
+                    theme.muted,
+                )));
+            }
+        }
+
+        let unblocks = app.deps.unblocks(&issue.id);
+        if !unblocks.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from("Unblocks:"));
+            lines.extend(
+                unblocks
+                    .iter()
+                    .map(|id| dependency_line(app, id, theme.success)),
+            );
+        }
+
         if !issue.labels.is_empty() {
             lines.push(Line::from(""));
             lines.push(Line::from("Labels:"));
             for label in &issue.labels {
                 lines.push(Line::from(vec![
                     Span::raw("  "),
-                    Span::styled(label.clone(), Style::default().fg(Color::Magenta)),
+                    Span::styled(label.clone(), theme.label),
                 ]));
             }
         }
@@ -1454,6 +2430,25 @@ fn render_right_pane(f: &mut Frame, app: &mut App, area: Rect) {
             ]));
         }
 
+        if !issue.comments.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(format!("Comments ({}):", issue.comments.len())));
+            for comment in &issue.comments {
+                lines.push(Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(
+                        comment.author.clone().unwrap_or_else(|| "anonymous".into()),
+                        theme.accent,
+                    ),
+                    Span::styled(
+                        format!(" {}", comment.created_at.format("%Y-%m-%d %H:%M")),
+                        theme.muted,
+                    ),
+                ]));
+                lines.extend(markdown::render(&comment.text, "    ", theme));
+            }
+        }
+
         Text::from(lines)
     } else {
         Text::from(vec![Line::from("No issue selected")])
@@ -1463,16 +2458,26 @@ fn render_right_pane(f: &mut Frame, app: &mut App, area: Rect) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Blue))
+                .border_style(theme.border)
                 .title("Details"),
         )
-        .wrap(Wrap { trim: true })
+        .wrap(Wrap { trim: false })
         .scroll((app.details_scroll as u16, 0));
 
     f.render_widget(paragraph, area);
 }
 
+/// An issue ID and title in the detail pane's dependency lists
+fn dependency_line(app: &App, id: &str, style: Style) -> Line<'static> {
+    let mut spans = vec![Span::raw("  "), Span::styled(id.to_string(), style)];
+    if let Some(title) = app.deps.title(id) {
+        spans.push(Span::raw(format!(" {}", title)));
+    }
+    Line::from(spans)
+}
+
 fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let mode_text = match app.mode {
         AppMode::Normal => "[NORMAL]",
         AppMode::Search => "[SEARCH]",
@@ -1482,22 +2487,56 @@ fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
         AppMode::WhichKey(_) => "[WHICHKEY]",
         AppMode::AddIssue => "[ADD ISSUE]",
         AppMode::EditIssue => "[EDIT ISSUE]",
+        AppMode::Graph => "[GRAPH]",
+        AppMode::Dashboard => "[DASHBOARD]",
+        AppMode::Conflict => "[CONFLICT]",
+        AppMode::Bulk => "[BULK]",
+        AppMode::Palette => "[COMMAND]",
     };
 
     let mode_style = match app.mode {
-        AppMode::Normal => Style::default()
-            .fg(Color::Green)
-            .add_modifier(Modifier::BOLD),
-        _ => Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD),
+        AppMode::Normal => theme.success.add_modifier(Modifier::BOLD),
+        _ => theme.highlight.add_modifier(Modifier::BOLD),
     };
 
     let status_content = if let Some(ref msg) = app.status_message {
         Line::from(vec![
             Span::styled(mode_text, mode_style),
             Span::raw(" | "),
-            Span::styled(msg, Style::default().fg(Color::Cyan)),
+            Span::styled(msg, theme.accent),
+        ])
+    } else if app.mode == AppMode::Graph {
+        Line::from(vec![
+            Span::styled(mode_text, mode_style),
+            Span::raw(" | "),
+            Span::raw(
+                "[hjkl]move [Enter]focus [a]all/neighbourhood [m]ark blocker [b]toggle blocks [Esc]back",
+            ),
+        ])
+    } else if app.mode == AppMode::Dashboard {
+        Line::from(vec![
+            Span::styled(mode_text, mode_style),
+            Span::raw(" | "),
+            Span::raw("[r]efresh [Esc]back"),
+        ])
+    } else if app.board.is_some() {
+        Line::from(vec![
+            Span::styled(mode_text, mode_style),
+            Span::raw(" | "),
+            Span::raw(key_hints(
+                &app.keymap,
+                &[
+                    (Command::CardLeft, "card left"),
+                    (Command::CardRight, "card right"),
+                    (Command::Group, "group by"),
+                    (Command::Edit, "edit"),
+                    (Command::Search, "search"),
+                    (Command::Filter, "filter"),
+                    (Command::Board, "list"),
+                    (Command::Palette, "command"),
+                    (Command::Quit, "quit"),
+                ],
+            )),
         ])
     } else {
         let selected_count = app.selection.selected_indices.len();
@@ -1505,73 +2544,291 @@ fn render_status_bar(f: &mut Frame, app: &App, area: Rect) {
             Span::styled(mode_text, mode_style),
             Span::raw(" | "),
             Span::raw(format!("Selected: {} | ", selected_count)),
-            Span::raw("[:a]dd [:e]dit [:c]lose [1-4]status [:s]ort [:?]help [/]search [:q]uit"),
+            Span::raw(key_hints(
+                &app.keymap,
+                &[
+                    (Command::Add, "add"),
+                    (Command::Edit, "edit"),
+                    (Command::Close, "close"),
+                    (Command::SortMenu, "sort"),
+                    (Command::Search, "search"),
+                    (Command::Palette, "command"),
+                    (Command::Help, "help"),
+                    (Command::Quit, "quit"),
+                ],
+            )),
         ])
     };
 
     let status_bar = Paragraph::new(status_content)
-        .style(Style::default().bg(Color::DarkGray))
+        .style(theme.status_bar)
         .alignment(Alignment::Left);
 
     f.render_widget(status_bar, area);
 }
 
-fn render_help_overlay(f: &mut Frame) {
-    let area = centered_rect(70, 80, f.area());
+/// "[key]label" hints for the commands that have a key bound
+fn key_hints(keymap: &Keymap, hints: &[(Command, &str)]) -> String {
+    hints
+        .iter()
+        .filter_map(|(command, label)| {
+            keymap
+                .first_key(command)
+                .map(|key| format!("[{}]{}", key, label))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn render_graph(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let Some(graph) = &app.graph else {
+        return;
+    };
+
+    let mut title = match &graph.scope {
+        Scope::Neighbourhood(id) => format!("Dependencies around {}", id),
+        Scope::Backlog => "Open backlog dependencies".to_string(),
+    };
+    if graph.has_cycles() {
+        title.push_str(" · cycles in red");
+    }
+    if graph.critical_len() > 1 {
+        title.push_str(&format!(
+            " · critical path ({}) in yellow",
+            graph.critical_len()
+        ));
+    }
+    if let Some(marked) = &graph.marked {
+        title.push_str(&format!(" · blocker: {}", marked));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.border)
+        .title(title);
+    let inner = block.inner(area);
+
+    if graph.is_empty() {
+        f.render_widget(Paragraph::new("No issues to graph").block(block), area);
+        return;
+    }
+
+    // Scroll just enough to keep the selected box in view
+    let (mut x, mut y) = (0, 0);
+    if let Some((bx, by, bw, bh)) = graph.cursor_area() {
+        let (w, h) = (inner.width as usize, inner.height as usize);
+        x = (bx + bw).saturating_sub(w).min(bx);
+        y = (by + bh).saturating_sub(h).min(by);
+    }
+
+    let paragraph = Paragraph::new(graph.render(&app.theme))
+        .block(block)
+        .scroll((y as u16, x as u16));
+    f.render_widget(paragraph, area);
+}
+
+fn render_help_overlay(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(80, 80, f.area());
 
     // Clear the background
     f.render_widget(Clear, area);
 
-    let help_text = vec![
-        Line::from(vec![Span::styled(
-            "Keyboard Shortcuts",
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .fg(Color::Cyan),
-        )]),
-        Line::from(""),
-        Line::from("Navigation:"),
-        Line::from("  j/Down     Move down"),
-        Line::from("  k/Up       Move up"),
-        Line::from("  gg         Go to top"),
-        Line::from("  G          Go to bottom"),
-        Line::from("  Ctrl-d     Page down"),
-        Line::from("  Ctrl-u     Page up"),
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.overlay_border)
+        .style(theme.overlay)
+        .title("Help (press Esc to close)");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)])
+        .split(inner);
+    let header = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled(
+                "Keyboard Shortcuts",
+                theme.accent.add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("  (rebind in [tui.keys] of .trx/config.toml)", theme.muted),
+        ]),
         Line::from(""),
-        Line::from("Selection:"),
-        Line::from("  Space      Toggle selection"),
-        Line::from("  Ctrl-a     Select all"),
-        Line::from("  V          Clear selection"),
+    ]);
+    f.render_widget(header, chunks[0]);
+
+    // Generated from the bindings in effect, in two columns
+    let lines: Vec<Line> = app
+        .keymap
+        .help()
+        .into_iter()
+        .map(|(keys, description)| {
+            Line::from(vec![
+                Span::styled(format!("  {:<14}", keys), theme.accent),
+                Span::raw(format!(" {}", description)),
+            ])
+        })
+        .collect();
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    let half = lines.len().div_ceil(2);
+    let right = lines[half..].to_vec();
+    f.render_widget(Paragraph::new(lines[..half].to_vec()), columns[0]);
+    f.render_widget(Paragraph::new(right), columns[1]);
+}
+
+fn render_conflict_overlay(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(conflict) = &app.conflict else {
+        return;
+    };
+    let area = centered_rect(60, 40, f.area());
+    f.render_widget(Clear, area);
+
+    let (ours, theirs) = conflict.changes().unwrap_or_default();
+    let overlapping = conflict.overlapping().unwrap_or_default();
+    let list = |fields: &[String]| {
+        if fields.is_empty() {
+            "nothing".to_string()
+        } else {
+            fields.join(", ")
+        }
+    };
+
+    let mut text = vec![
+        Line::from(vec![
+            Span::styled(
+                conflict.theirs.id.clone(),
+                theme.accent.add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" changed on disk since you started this change."),
+        ]),
         Line::from(""),
-        Line::from("Actions:"),
-        Line::from("  a          Add issue"),
-        Line::from("  e          Edit issue"),
-        Line::from("  c          Close issue"),
-        Line::from("  1-4        Set status (Open/InProgress/Blocked/Closed)"),
-        Line::from("  /          Search"),
-        Line::from("  s          Sort menu"),
-        Line::from("  f          Filter menu"),
-        Line::from("  r          Refresh"),
-        Line::from("  ?          Help"),
-        Line::from("  q          Quit"),
-        Line::from("  Esc        Return to normal mode"),
+        Line::from(format!("  Changed on disk: {}", list(&theirs))),
+        Line::from(format!("  Your changes:    {}", list(&ours))),
     ];
+    if !overlapping.is_empty() {
+        text.push(Line::from(Span::styled(
+            format!(
+                "  Both changed:    {} (merging keeps yours)",
+                list(&overlapping)
+            ),
+            theme.highlight,
+        )));
+    }
+    text.extend([
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("[m]", theme.success),
+            Span::raw(" Merge your changes into the disk version   "),
+            Span::styled("[r]", theme.danger),
+            Span::raw(" Reload and discard them"),
+        ]),
+    ]);
 
-    let paragraph = Paragraph::new(help_text)
+    let paragraph = Paragraph::new(text)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow))
-                .style(Style::default().bg(Color::Black))
-                .title("Help (press Esc to close)"),
+                .border_style(theme.danger)
+                .style(theme.overlay)
+                .title("Conflict"),
         )
-        .alignment(Alignment::Left)
-        .wrap(Wrap { trim: true });
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(paragraph, area);
+}
+
+fn render_bulk_overlay(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(step) = &app.bulk else {
+        return;
+    };
+    let area = centered_rect(60, 50, f.area());
+    f.render_widget(Clear, area);
+
+    let selected = app.selected_issues();
+    let key = |k: &str| Span::styled(format!("  [{}] ", k), theme.accent);
+    let mut text = vec![
+        Line::from(Span::styled(
+            format!("{} issues selected", selected.len()),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
 
+    match step {
+        BulkStep::Menu => {
+            for (k, label) in [
+                ("c", "Close"),
+                ("1-4", "Set status (Open/InProgress/Blocked/Closed)"),
+                ("p", "Set priority"),
+                ("+", "Add label"),
+                ("-", "Remove label"),
+                ("a", "Assign"),
+                ("d", "Delete"),
+            ] {
+                text.push(Line::from(vec![key(k), Span::raw(label)]));
+            }
+        }
+        BulkStep::Priority => {
+            text.push(Line::from(vec![
+                key("0-4"),
+                Span::raw("Priority (0 critical .. 4 backlog)"),
+            ]));
+        }
+        BulkStep::Input(input, value) => {
+            text.push(Line::from(vec![
+                Span::raw(format!("{}: ", input.prompt())),
+                Span::styled(format!("{}_", value), theme.highlight),
+            ]));
+        }
+        BulkStep::Confirm(action) => {
+            let style = if *action == BulkAction::Delete {
+                theme.danger
+            } else {
+                theme.highlight
+            };
+            text.push(Line::from(Span::styled(
+                format!("About to {} {} issues:", action.describe(), selected.len()),
+                style.add_modifier(Modifier::BOLD),
+            )));
+            for listed in selected.iter().take(10) {
+                text.push(Line::from(vec![
+                    Span::styled(format!("  {} ", listed.issue.id), theme.accent),
+                    Span::raw(listed.issue.title.clone()),
+                ]));
+            }
+            if selected.len() > 10 {
+                text.push(Line::from(format!("  and {} more", selected.len() - 10)));
+            }
+            text.push(Line::from(""));
+            text.push(Line::from(vec![
+                key("y"),
+                Span::raw("Confirm"),
+                key("n"),
+                Span::raw("Cancel"),
+            ]));
+        }
+    }
+    text.push(Line::from(""));
+    text.push(Line::from(vec![key("Esc"), Span::raw("Cancel")]));
+
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.overlay_border)
+            .style(theme.overlay)
+            .title("Bulk action"),
+    );
     f.render_widget(paragraph, area);
 }
 
-fn render_sort_overlay(f: &mut Frame) {
+fn render_sort_overlay(f: &mut Frame, theme: &Theme) {
     let area = centered_rect(40, 30, f.area());
 
     // Clear the background
@@ -1580,14 +2837,13 @@ fn render_sort_overlay(f: &mut Frame) {
     let sort_text = vec![
         Line::from(vec![Span::styled(
             "Sort Options",
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .fg(Color::Cyan),
+            theme.accent.add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from("  [1] Priority"),
         Line::from("  [2] Date (newest first)"),
         Line::from("  [3] Status"),
+        Line::from("  [4] Last updated"),
         Line::from(""),
         Line::from("Press number to sort, Esc to cancel"),
     ];
@@ -1596,8 +2852,8 @@ fn render_sort_overlay(f: &mut Frame) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow))
-                .style(Style::default().bg(Color::Black))
+                .border_style(theme.overlay_border)
+                .style(theme.overlay)
                 .title("Sort"),
         )
         .alignment(Alignment::Left);
@@ -1605,7 +2861,7 @@ fn render_sort_overlay(f: &mut Frame) {
     f.render_widget(paragraph, area);
 }
 
-fn render_which_key_overlay(f: &mut Frame, ctx: WhichKeyContext) {
+fn render_which_key_overlay(f: &mut Frame, ctx: WhichKeyContext, theme: &Theme) {
     let area = Rect {
         x: 0,
         y: f.area().height.saturating_sub(4),
@@ -1641,6 +2897,7 @@ fn render_which_key_overlay(f: &mut Frame, ctx: WhichKeyContext) {
             ("r", "Reset filters"),
         ],
         WhichKeyContext::Labels => vec![("r", "Reset filters")],
+        WhichKeyContext::Edit => vec![("d", "Description"), ("n", "Notes"), ("c", "New comment")],
     };
 
     let title = match ctx {
@@ -1648,13 +2905,12 @@ fn render_which_key_overlay(f: &mut Frame, ctx: WhichKeyContext) {
         WhichKeyContext::Type => "Type Filter",
         WhichKeyContext::Priority => "Priority Filter",
         WhichKeyContext::Labels => "Label Filter",
+        WhichKeyContext::Edit => "Open in Editor",
     };
 
     let mut spans = vec![Span::styled(
         format!("[{}] ", title),
-        Style::default()
-            .fg(Color::Cyan)
-            .add_modifier(Modifier::BOLD),
+        theme.accent.add_modifier(Modifier::BOLD),
     )];
 
     for (key, label) in items {
@@ -1668,8 +2924,8 @@ fn render_which_key_overlay(f: &mut Frame, ctx: WhichKeyContext) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow))
-                .style(Style::default().bg(Color::Black)),
+                .border_style(theme.overlay_border)
+                .style(theme.overlay),
         )
         .alignment(Alignment::Left);
 
@@ -1677,6 +2933,7 @@ fn render_which_key_overlay(f: &mut Frame, ctx: WhichKeyContext) {
 }
 
 fn render_filter_overlay(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
     let area = centered_rect(60, 70, f.area());
 
     // Clear the background
@@ -1684,12 +2941,10 @@ fn render_filter_overlay(f: &mut Frame, app: &App) {
 
     let check = |enabled: bool| if enabled { "[x]" } else { "[ ]" };
 
-    let filter_text = vec![
+    let mut filter_text = vec![
         Line::from(vec![Span::styled(
             "Filter Options",
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .fg(Color::Cyan),
+            theme.accent.add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from(vec![Span::styled(
@@ -1717,6 +2972,23 @@ fn render_filter_overlay(f: &mut Frame, app: &App) {
             check(app.filter_state.show_closed)
         )),
         Line::from(""),
+        Line::from(vec![Span::styled(
+            "Dependencies:",
+            Style::default().add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(format!(
+            "  [y] {} Ready only",
+            check(app.filter_state.ready_only)
+        )),
+        Line::from(format!(
+            "  [d] {} Blocked only",
+            check(app.filter_state.show_blocked)
+        )),
+        Line::from(format!(
+            "  [n] {} Unassigned only",
+            check(app.filter_state.unassigned_only)
+        )),
+        Line::from(""),
         Line::from(vec![Span::styled(
             "Type:",
             Style::default().add_modifier(Modifier::BOLD),
@@ -1769,20 +3041,35 @@ fn render_filter_overlay(f: &mut Frame, app: &App) {
         Line::from("  [0] P0 Critical   [1] P1 High   [2] P2 Medium"),
         Line::from("  [3] P3 Low        [4] P4 Backlog"),
         Line::from(""),
-        Line::from(vec![
-            Span::styled("[r]", Style::default().fg(Color::Yellow)),
-            Span::raw(" Reset all filters   "),
-            Span::styled("[Esc]", Style::default().fg(Color::Red)),
-            Span::raw(" Close"),
-        ]),
     ];
 
+    if app.repos.is_multi() {
+        filter_text.extend([
+            Line::from(vec![Span::styled(
+                "Repository:",
+                Style::default().add_modifier(Modifier::BOLD),
+            )]),
+            Line::from(format!(
+                "  [R] {} (cycle)",
+                app.filter_state.repo.as_deref().unwrap_or("All")
+            )),
+            Line::from(""),
+        ]);
+    }
+
+    filter_text.push(Line::from(vec![
+        Span::styled("[r]", theme.highlight),
+        Span::raw(" Reset all filters   "),
+        Span::styled("[Esc]", theme.danger),
+        Span::raw(" Close"),
+    ]));
+
     let paragraph = Paragraph::new(filter_text)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow))
-                .style(Style::default().bg(Color::Black))
+                .border_style(theme.overlay_border)
+                .style(theme.overlay)
                 .title("Filter (toggles apply immediately)"),
         )
         .alignment(Alignment::Left);
@@ -1791,6 +3078,7 @@ fn render_filter_overlay(f: &mut Frame, app: &App) {
 }
 
 fn render_issue_form(f: &mut Frame, app: &App, title: &str) {
+    let theme = &app.theme;
     let area = centered_rect(60, 70, f.area());
 
     // Clear the background
@@ -1800,9 +3088,7 @@ fn render_issue_form(f: &mut Frame, app: &App, title: &str) {
 
     let field_style = |selected: bool| {
         if selected {
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD)
+            theme.accent.add_modifier(Modifier::BOLD)
         } else {
             Style::default()
         }
@@ -1811,9 +3097,7 @@ fn render_issue_form(f: &mut Frame, app: &App, title: &str) {
     let text = vec![
         Line::from(vec![Span::styled(
             title,
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .fg(Color::Cyan),
+            theme.accent.add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
         Line::from(vec![
@@ -1832,9 +3116,7 @@ fn render_issue_form(f: &mut Frame, app: &App, title: &str) {
             Span::styled(
                 format!("[{}]", form.issue_type),
                 if form.selected_field == FormField::IssueType {
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD)
+                    theme.accent.add_modifier(Modifier::BOLD)
                 } else {
                     Style::default()
                 },
@@ -1849,9 +3131,7 @@ fn render_issue_form(f: &mut Frame, app: &App, title: &str) {
             Span::styled(
                 format!("P{}", form.priority),
                 if form.selected_field == FormField::Priority {
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD)
+                    theme.accent.add_modifier(Modifier::BOLD)
                 } else {
                     Style::default()
                 },
@@ -1866,9 +3146,7 @@ fn render_issue_form(f: &mut Frame, app: &App, title: &str) {
             Span::styled(
                 format!("[{}]", form.status),
                 if form.selected_field == FormField::Status {
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD)
+                    theme.accent.add_modifier(Modifier::BOLD)
                 } else {
                     Style::default()
                 },
@@ -1890,11 +3168,11 @@ fn render_issue_form(f: &mut Frame, app: &App, title: &str) {
         Line::from(""),
         Line::from(""),
         Line::from(vec![
-            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::styled("[Enter]", theme.success),
             Span::raw(" Save  "),
-            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::styled("[Esc]", theme.danger),
             Span::raw(" Cancel  "),
-            Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
+            Span::styled("[Tab]", theme.highlight),
             Span::raw(" Next field"),
         ]),
     ];
@@ -1903,8 +3181,8 @@ fn render_issue_form(f: &mut Frame, app: &App, title: &str) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Blue))
-                .style(Style::default().bg(Color::Black))
+                .border_style(theme.border)
+                .style(theme.overlay)
                 .title(title),
         )
         .alignment(Alignment::Left)
@@ -0,0 +1,231 @@
+//! Markdown rendering for the detail pane
+//!
+//! Covers what issue text usually holds: headers, bullet and numbered
+//! lists, block quotes, fenced code blocks, rules, and inline code,
+//! emphasis and links. Anything else is shown as written.
+
+use crate::theme::Theme;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+
+/// Render markdown into styled lines, each indented by `indent`
+pub fn render(text: &str, indent: &str, theme: &Theme) -> Vec<Line<'static>> {
+    let code_style = theme.code;
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for raw in text.lines() {
+        let trimmed = raw.trim_start();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push(Line::from(vec![
+                Span::raw(indent.to_string()),
+                Span::styled(format!("│ {}", raw), code_style),
+            ]));
+            continue;
+        }
+
+        let mut spans = vec![Span::raw(indent.to_string())];
+        let leading = &raw[..raw.len() - trimmed.len()];
+
+        if let Some((level, heading)) = heading(trimmed) {
+            let mut style = Style::default().add_modifier(Modifier::BOLD);
+            if level <= 2 {
+                style = style.patch(theme.accent);
+            }
+            if level == 1 {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            spans.push(Span::styled(heading.to_string(), style));
+        } else if is_rule(trimmed) {
+            spans.push(Span::styled("─".repeat(20), theme.muted));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            let style = theme.muted.add_modifier(Modifier::ITALIC);
+            spans.push(Span::styled("┃ ", style));
+            spans.extend(inline(quote.trim_start(), style, theme));
+        } else if let Some((marker, item)) = list_item(trimmed) {
+            spans.push(Span::raw(leading.to_string()));
+            spans.push(Span::styled(marker, theme.accent));
+            spans.extend(inline(item, Style::default(), theme));
+        } else {
+            spans.push(Span::raw(leading.to_string()));
+            spans.extend(inline(trimmed, Style::default(), theme));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+/// `# Title` style headers, with their level
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if rest.is_empty() {
+        return Some((level, rest));
+    }
+    rest.strip_prefix(' ').map(|text| (level, text.trim()))
+}
+
+fn is_rule(line: &str) -> bool {
+    let line = line.trim_end();
+    line.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&c| line.chars().all(|l| l == c || l == ' '))
+        && line.chars().filter(|c| !c.is_whitespace()).count() >= 3
+}
+
+/// Bullet or numbered list item: the marker to show and the item text
+fn list_item(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return match item.strip_prefix("[ ] ") {
+                Some(task) => Some(("☐ ".to_string(), task)),
+                None => match item
+                    .strip_prefix("[x] ")
+                    .or_else(|| item.strip_prefix("[X] "))
+                {
+                    Some(done) => Some(("☑ ".to_string(), done)),
+                    None => Some(("• ".to_string(), item)),
+                },
+            };
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(item) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            return Some((format!("{}. ", &line[..digits]), item));
+        }
+    }
+    None
+}
+
+/// Inline code, `**bold**`, `*italic*`/`_italic_` and `[text](url)` links
+fn inline(text: &str, base: Style, theme: &Theme) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let styled = match c {
+            '`' => delimited(rest, "`").map(|(inner, len)| {
+                (
+                    vec![Span::styled(inner.to_string(), base.patch(theme.code))],
+                    len,
+                )
+            }),
+            // Underscores inside words, as in snake_case, are literal
+            '_' if plain.chars().last().is_some_and(char::is_alphanumeric) => None,
+            '*' | '_' if rest.starts_with("**") || rest.starts_with("__") => {
+                delimited(rest, &rest[..2]).map(|(inner, len)| {
+                    (inline(inner, base.add_modifier(Modifier::BOLD), theme), len)
+                })
+            }
+            '*' | '_' => delimited(rest, &rest[..1]).map(|(inner, len)| {
+                (
+                    inline(inner, base.add_modifier(Modifier::ITALIC), theme),
+                    len,
+                )
+            }),
+            '[' => link(rest).map(|(label, url, len)| {
+                let link_style = base.patch(theme.link).add_modifier(Modifier::UNDERLINED);
+                let mut spans = inline(label, link_style, theme);
+                if url != label {
+                    spans.push(Span::styled(format!(" ({})", url), base.patch(theme.muted)));
+                }
+                (spans, len)
+            }),
+            _ => None,
+        };
+
+        match styled {
+            Some((styled, len)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut plain), base));
+                }
+                spans.extend(styled);
+                rest = &rest[len..];
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(Span::styled(plain, base));
+    }
+    spans
+}
+
+/// Text between a delimiter and its closing twin, and the length consumed
+fn delimited<'a>(text: &'a str, delim: &str) -> Option<(&'a str, usize)> {
+    let inner = text.strip_prefix(delim)?;
+    let end = inner.find(delim)?;
+    if end == 0 {
+        return None;
+    }
+    Some((&inner[..end], delim.len() * 2 + end))
+}
+
+/// `[label](url)`: the label, the URL and the length consumed
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.find("](")?;
+    let label = &text[1..label_end];
+    let after = &text[label_end + 2..];
+    let url_end = after.find(')')?;
+    Some((label, &after[..url_end], label_end + 2 + url_end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let text = "# Plan\n\nSee [the docs](https://example.com) and `cargo test`.\n\
+                    - **one**\n  2. two\n- [x] done\n> quoted\n```\nfn main() {}\n```\n---";
+        let lines: Vec<String> = render(text, "  ", &Theme::dark())
+            .iter()
+            .map(plain)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "  Plan",
+                "  ",
+                "  See the docs (https://example.com) and cargo test.",
+                "  • one",
+                "    2. two",
+                "  ☑ done",
+                "  ┃ quoted",
+                "  │ fn main() {}",
+                &format!("  {}", "─".repeat(20)),
+            ]
+        );
+
+        let rendered = render("**bold** and *it*", "", &Theme::dark());
+        let bold = rendered[0].spans.iter().find(|s| s.content == "bold");
+        assert!(bold.unwrap().style.add_modifier.contains(Modifier::BOLD));
+        // Unclosed markers are left as written
+        assert_eq!(
+            plain(&render("2 * 3 = 6_", "", &Theme::dark())[0]),
+            "2 * 3 = 6_"
+        );
+        assert_eq!(
+            plain(&render("call snake_case_name", "", &Theme::dark())[0]),
+            "call snake_case_name"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! The `:` command palette
+//!
+//! Typed text is fuzzy-matched against every command in the catalogue plus
+//! templates for the commands that take free text. Enter runs the typed line
+//! if it parses as a command, else the highlighted match; a template is
+//! copied into the input instead so its argument can be typed.
+
+use crate::keymap::{Command, Keymap};
+use crate::theme::Theme;
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+/// Commands that need an argument typed after them
+const TEMPLATES: &[&str] = &["label add ", "label remove ", "assign "];
+
+/// Most matches listed at once
+const MAX_MATCHES: usize = 12;
+
+#[derive(Default)]
+pub struct Palette {
+    pub input: String,
+    /// Index of the highlighted match
+    pub selected: usize,
+}
+
+/// What Enter does with the current input
+pub enum Outcome {
+    Run(Command),
+    /// Keep editing, e.g. after completing a template
+    Edit,
+    Error(String),
+}
+
+impl Palette {
+    /// Command lines matching the input, best first
+    pub fn matches(&self) -> Vec<String> {
+        let mut entries: Vec<String> = Command::catalogue()
+            .iter()
+            .map(Command::to_string)
+            .chain(TEMPLATES.iter().map(|t| t.to_string()))
+            .collect();
+        let pattern = self.input.trim();
+        if pattern.is_empty() {
+            return entries;
+        }
+        let mut scored: Vec<(i64, String)> = entries
+            .drain(..)
+            .filter_map(|e| fuzzy_score(pattern, &e).map(|score| (score, e)))
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, e)| e).collect()
+    }
+
+    pub fn move_selection(&mut self, down: bool) {
+        let count = self.matches().len().min(MAX_MATCHES);
+        self.selected = if down {
+            (self.selected + 1).min(count.saturating_sub(1))
+        } else {
+            self.selected.saturating_sub(1)
+        };
+    }
+
+    /// Replace the input with the highlighted match
+    pub fn complete(&mut self) {
+        if let Some(entry) = self.matches().into_iter().nth(self.selected) {
+            self.input = entry;
+            self.selected = 0;
+        }
+    }
+
+    pub fn submit(&mut self) -> Outcome {
+        let typed = Command::parse(&self.input);
+        if let Ok(command) = typed {
+            return Outcome::Run(command);
+        }
+        match self.matches().into_iter().nth(self.selected) {
+            Some(entry) if entry.ends_with(' ') => {
+                self.input = entry;
+                self.selected = 0;
+                Outcome::Edit
+            }
+            Some(entry) => match Command::parse(&entry) {
+                Ok(command) => Outcome::Run(command),
+                Err(e) => Outcome::Error(e.to_string()),
+            },
+            None => Outcome::Error(typed.err().map(|e| e.to_string()).unwrap_or_default()),
+        }
+    }
+}
+
+/// Score `candidate` against `pattern` as a case-insensitive subsequence,
+/// favouring runs of matched characters, word starts and short candidates
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut previous: Option<usize> = None;
+
+    for p in pattern.to_lowercase().chars() {
+        let found = pos + candidate[pos..].iter().position(|&c| c == p)?;
+        score += 1;
+        if previous.is_some_and(|prev| prev + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || matches!(candidate[found - 1], ' ' | '-' | '_') {
+            score += 8;
+        }
+        previous = Some(found);
+        pos = found + 1;
+    }
+    Some(score * 10 - candidate.len() as i64)
+}
+
+pub fn render(f: &mut Frame, palette: &Palette, keymap: &Keymap, theme: &Theme) {
+    let area = f.area();
+    let width = area.width.min(70);
+    let height = (MAX_MATCHES as u16 + 4).min(area.height);
+    let area = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height / 6,
+        width,
+        height,
+    };
+    f.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(": ", theme.accent),
+            Span::raw(palette.input.clone()),
+            Span::styled("_", theme.highlight),
+        ]),
+        Line::from(""),
+    ];
+    let matches = palette.matches();
+    if matches.is_empty() {
+        let hint = match Command::parse(&palette.input) {
+            Ok(command) => format!("Enter: {}", command.describe()),
+            Err(e) => e.to_string(),
+        };
+        lines.push(Line::from(Span::styled(hint, theme.muted)));
+    }
+    for (i, entry) in matches.iter().take(MAX_MATCHES).enumerate() {
+        let style = if i == palette.selected {
+            theme.selection.add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let (help, keys) = match Command::parse(entry) {
+            Ok(command) => (command.describe(), keymap.keys_for(&command)),
+            Err(_) => ("type an argument".to_string(), String::new()),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {:<22}", entry), style),
+            Span::raw(format!(" {}", help)),
+            Span::styled(
+                if keys.is_empty() {
+                    String::new()
+                } else {
+                    format!("  [{}]", keys)
+                },
+                theme.muted,
+            ),
+        ]));
+    }
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(theme.accent)
+            .style(theme.overlay)
+            .title("Command (Tab complete, Enter run, Esc cancel)"),
+    );
+    f.render_widget(paragraph, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_and_submit() {
+        assert!(fuzzy_score("srtu", "sort updated").is_some());
+        assert!(fuzzy_score("xyz", "sort updated").is_none());
+        // Consecutive and word-start matches beat scattered ones
+        assert!(fuzzy_score("clo", "close") > fuzzy_score("clo", "clear-selection"));
+
+        let mut palette = Palette {
+            input: "vtri".into(),
+            selected: 0,
+        };
+        assert_eq!(palette.matches()[0], "view triage");
+        assert!(matches!(
+            palette.submit(),
+            Outcome::Run(Command::View(crate::keymap::View::Triage))
+        ));
+
+        // A template is completed rather than run
+        palette.input = "labad".into();
+        assert!(matches!(palette.submit(), Outcome::Edit));
+        assert_eq!(palette.input, "label add ");
+        palette.input.push_str("backend");
+        assert!(matches!(
+            palette.submit(),
+            Outcome::Run(Command::AddLabel(label)) if label == "backend"
+        ));
+    }
+}
@@ -0,0 +1,483 @@
+//! Repositories shown by the TUI
+//!
+//! The TUI views one repository or a whole workspace. Repositories come from
+//! a workspace file and `--repo` flags, falling back to the repository
+//! containing the current directory, the same way trx-api collects them.
+//! Each gets its own store; issues are listed together and tagged with the
+//! index of the repository they came from so edits go back to it.
+//!
+//! Other processes write `.trx/` too: `trx sync`, a pull, a teammate's
+//! agent. A file watcher flags each repository when anything under `.trx/`
+//! changes, and only then is a fingerprint of the directory (file count,
+//! size, latest mtime) taken to tell those changes from our own writes, so
+//! an idle TUI never walks a V2 store's one-file-per-issue directory. Where
+//! the watcher cannot start, the fingerprint is polled instead.
+//!
+//! Writes always compare fingerprints first rather than trust the watcher,
+//! whose events arrive asynchronously: a stale store is reloaded before a
+//! create, and an edit made from a stale copy comes back as a `Conflict`
+//! instead of overwriting newer work.
+
+use anyhow::{Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+use trx_core::{Config, Issue, UnifiedStore, Workspace, WorkspaceRepo};
+
+/// File count, total size and latest modification time under `.trx/`
+type Fingerprint = (usize, u64, Option<SystemTime>);
+
+fn fingerprint(dir: &Path) -> Fingerprint {
+    let mut result = (0, 0, None);
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+                continue;
+            }
+            result.0 += 1;
+            result.1 += metadata.len();
+            result.2 = result.2.max(metadata.modified().ok());
+        }
+    }
+    result
+}
+
+/// Watch a directory, setting `changed` on anything but reads
+fn watch(dir: &Path, changed: Arc<AtomicBool>) -> Option<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok_and(|e| !e.kind.is_access()) {
+            changed.store(true, Ordering::Relaxed);
+        }
+    })
+    .ok()?;
+    watcher.watch(dir, RecursiveMode::Recursive).ok()?;
+    Some(watcher)
+}
+
+/// A repository and its store
+pub struct Repo {
+    pub name: String,
+    pub store: UnifiedStore,
+    root: PathBuf,
+    /// Fingerprint of `.trx/` when the store was last loaded or saved
+    fingerprint: Fingerprint,
+    /// Set by the watcher when `.trx/` may have changed
+    changed: Arc<AtomicBool>,
+    watcher: Option<RecommendedWatcher>,
+}
+
+impl Repo {
+    fn open(name: String, root: PathBuf) -> Result<Self> {
+        let fingerprint = fingerprint(&root.join(".trx"));
+        let store = UnifiedStore::open_at(&root)
+            .with_context(|| format!("Failed to open {}", root.display()))?;
+        let changed = Arc::new(AtomicBool::new(false));
+        let watcher = watch(&root.join(".trx"), changed.clone());
+        Ok(Self {
+            name,
+            store,
+            root,
+            fingerprint,
+            changed,
+            watcher,
+        })
+    }
+
+    fn is_stale(&self) -> bool {
+        fingerprint(&self.root.join(".trx")) != self.fingerprint
+    }
+
+    /// Whether `.trx/` changed since the last load or save, asking the
+    /// watcher first so unchanged repositories cost nothing
+    fn has_changed(&self) -> bool {
+        let flagged = self.watcher.is_none() || self.changed.swap(false, Ordering::Relaxed);
+        flagged && self.is_stale()
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        let fingerprint = fingerprint(&self.root.join(".trx"));
+        self.store = UnifiedStore::open_at(&self.root)
+            .with_context(|| format!("Failed to reload {}", self.root.display()))?;
+        self.fingerprint = fingerprint;
+        Ok(())
+    }
+}
+
+/// An edit to an issue that changed on disk after the edit began
+pub struct Conflict {
+    pub repo: usize,
+    /// The issue the edit started from
+    pub base: Issue,
+    /// The edited issue
+    pub ours: Issue,
+    /// The issue as it is on disk now
+    pub theirs: Issue,
+}
+
+impl Conflict {
+    /// Top-level fields that differ from `base`, timestamps aside
+    fn changed(base: &Value, other: &Value) -> Vec<String> {
+        let (Value::Object(base), Value::Object(other)) = (base, other) else {
+            return Vec::new();
+        };
+        let mut fields: Vec<String> = base
+            .keys()
+            .chain(other.keys())
+            .filter(|k| *k != "updated_at" && base.get(*k) != other.get(*k))
+            .cloned()
+            .collect();
+        fields.sort();
+        fields.dedup();
+        fields
+    }
+
+    fn values(&self) -> Result<[Value; 3]> {
+        Ok([
+            serde_json::to_value(&self.base)?,
+            serde_json::to_value(&self.ours)?,
+            serde_json::to_value(&self.theirs)?,
+        ])
+    }
+
+    /// Fields changed by the edit and fields changed on disk
+    pub fn changes(&self) -> Result<(Vec<String>, Vec<String>)> {
+        let [base, ours, theirs] = self.values()?;
+        Ok((Self::changed(&base, &ours), Self::changed(&base, &theirs)))
+    }
+
+    /// Fields changed both by the edit and on disk
+    pub fn overlapping(&self) -> Result<Vec<String>> {
+        let (ours, theirs) = self.changes()?;
+        Ok(ours.into_iter().filter(|f| theirs.contains(f)).collect())
+    }
+
+    /// The disk version with the edit's changes applied on top; where both
+    /// changed a field the edit wins
+    pub fn merged(&self) -> Result<Issue> {
+        let [base, ours, mut merged] = self.values()?;
+        for field in Self::changed(&base, &ours) {
+            if let Value::Object(merged) = &mut merged {
+                match ours.get(&field) {
+                    Some(value) => merged.insert(field, value.clone()),
+                    None => merged.remove(&field),
+                };
+            }
+        }
+        let mut issue: Issue = serde_json::from_value(merged)?;
+        issue.updated_at = chrono::Utc::now();
+        Ok(issue)
+    }
+}
+
+/// The repositories in view
+pub struct Repos {
+    repos: Vec<Repo>,
+}
+
+impl Repos {
+    /// Open every repository in the workspace file and `roots`
+    pub fn open(workspace: Option<&Path>, roots: &[PathBuf]) -> Result<Self> {
+        let mut ws = match workspace {
+            Some(path) => Workspace::load(path)
+                .with_context(|| format!("Failed to load workspace {}", path.display()))?,
+            None => Workspace::default(),
+        };
+        for root in roots {
+            ws.add(WorkspaceRepo::from_root(root.clone()))?;
+        }
+        if ws.repos.is_empty() {
+            ws.repos
+                .push(WorkspaceRepo::from_root(UnifiedStore::find_root()?));
+        }
+
+        let repos = ws
+            .repos
+            .into_iter()
+            .map(|repo| Repo::open(repo.name, repo.root))
+            .collect::<Result<_>>()?;
+        Ok(Self { repos })
+    }
+
+    /// Whether more than one repository is in view
+    pub fn is_multi(&self) -> bool {
+        self.repos.len() > 1
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.repos.iter().map(|r| r.name.as_str())
+    }
+
+    pub fn name(&self, repo: usize) -> &str {
+        &self.repos[repo].name
+    }
+
+    /// Index of a repository by name
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.repos.iter().position(|r| r.name == name)
+    }
+
+    /// The repository's configuration, if it loads
+    pub fn config(&self, repo: usize) -> Result<Config> {
+        let path = self.repos[repo].store.trx_dir().join("config.toml");
+        Config::load(&path).with_context(|| format!("Failed to load {}", path.display()))
+    }
+
+    pub fn store_mut(&mut self, repo: usize) -> &mut UnifiedStore {
+        &mut self.repos[repo].store
+    }
+
+    /// Reload every repository whose `.trx/` changed since it was last
+    /// loaded or saved, returning whether any did
+    pub fn reload_changed(&mut self) -> Result<bool> {
+        let mut reloaded = false;
+        for repo in self.repos.iter_mut().filter(|r| r.has_changed()) {
+            repo.reload()?;
+            reloaded = true;
+        }
+        Ok(reloaded)
+    }
+
+    /// Save edits to several issues, given as (repository, base, edited),
+    /// with one write per repository
+    ///
+    /// If any of the issues changed on disk since its base was read, nothing
+    /// is written and their IDs are returned.
+    pub fn save_all(&mut self, changes: Vec<(usize, Issue, Issue)>) -> Result<Vec<String>> {
+        let mut changed_on_disk = Vec::new();
+        for (repo, base, _) in &changes {
+            let entry = &mut self.repos[*repo];
+            if entry.is_stale() {
+                entry.reload()?;
+            }
+            if entry
+                .store
+                .get(&base.id)
+                .is_some_and(|theirs| theirs.updated_at != base.updated_at)
+            {
+                changed_on_disk.push(base.id.clone());
+            }
+        }
+        if !changed_on_disk.is_empty() {
+            return Ok(changed_on_disk);
+        }
+
+        for (idx, entry) in self.repos.iter_mut().enumerate() {
+            let issues: Vec<Issue> = changes
+                .iter()
+                .filter(|(repo, _, _)| *repo == idx)
+                .map(|(_, _, issue)| issue.clone())
+                .collect();
+            if issues.is_empty() {
+                continue;
+            }
+            entry.store.update_many(issues)?;
+            entry.fingerprint = fingerprint(&entry.root.join(".trx"));
+        }
+        Ok(Vec::new())
+    }
+
+    /// Create an issue, first reloading the store if `.trx/` changed on disk
+    /// so the write keeps other processes' work
+    pub fn create(&mut self, repo: usize, issue: Issue) -> Result<()> {
+        let entry = &mut self.repos[repo];
+        if entry.is_stale() {
+            entry.reload()?;
+        }
+        entry.store.create(issue)?;
+        entry.fingerprint = fingerprint(&entry.root.join(".trx"));
+        Ok(())
+    }
+
+    /// Save an edit made to `base`, unless the issue has changed on disk
+    /// since, in which case nothing is written and the conflict is returned
+    pub fn save(&mut self, repo: usize, base: &Issue, issue: Issue) -> Result<Option<Conflict>> {
+        let entry = &mut self.repos[repo];
+        if entry.is_stale() {
+            entry.reload()?;
+        }
+        if let Some(theirs) = entry.store.get(&issue.id)
+            && theirs.updated_at != base.updated_at
+        {
+            return Ok(Some(Conflict {
+                repo,
+                base: base.clone(),
+                ours: issue,
+                theirs: theirs.clone(),
+            }));
+        }
+        entry.store.update(issue)?;
+        entry.fingerprint = fingerprint(&entry.root.join(".trx"));
+        Ok(None)
+    }
+
+    /// Issues from every repository, tagged with their repository index
+    pub fn issues(&self, include_closed: bool) -> Vec<(usize, &Issue)> {
+        self.repos
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, repo)| {
+                let issues = if include_closed {
+                    repo.store.list(false)
+                } else {
+                    repo.store.list_open()
+                };
+                issues.into_iter().map(move |issue| (idx, issue))
+            })
+            .collect()
+    }
+
+    /// Serialize an issue for robot output, adding a `repo` field when
+    /// several repositories are in view
+    pub fn to_json(&self, repo: usize, issue: &Issue) -> Result<Value> {
+        let mut value = serde_json::to_value(issue)?;
+        if self.is_multi()
+            && let Value::Object(map) = &mut value
+        {
+            map.insert("repo".into(), self.name(repo).into());
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trx_core::StorageVersion;
+
+    fn init_repo(root: &Path, prefix: &str) {
+        UnifiedStore::init_at(root, prefix, StorageVersion::V1).unwrap();
+    }
+
+    #[test]
+    fn test_open_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo(&dir.path().join("api"), "api");
+        init_repo(&dir.path().join("web"), "web");
+        let path = dir.path().join("workspace.yaml");
+        std::fs::write(
+            &path,
+            "repos:\n  - api\n  - path: web\n    name: frontend\n",
+        )
+        .unwrap();
+
+        let mut repos = Repos::open(Some(&path), &[]).unwrap();
+        assert!(repos.is_multi());
+        assert_eq!(repos.names().collect::<Vec<_>>(), ["api", "frontend"]);
+
+        let web = repos.position("frontend").unwrap();
+        repos
+            .store_mut(web)
+            .create(Issue::new("web-a1".into(), "Fix layout".into()))
+            .unwrap();
+        let issues = repos.issues(false);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].0, web);
+        assert_eq!(repos.to_json(web, issues[0].1).unwrap()["repo"], "frontend");
+
+        // Reopening sees the issue in the right repository
+        let repos = Repos::open(Some(&path), &[]).unwrap();
+        assert_eq!(repos.issues(true)[0].0, web);
+    }
+
+    #[test]
+    fn test_save_detects_changes_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo(dir.path(), "trx");
+        let roots = [dir.path().to_path_buf()];
+        let mut ours = Repos::open(None, &roots).unwrap();
+        let mut base = Issue::new("trx-a1".into(), "Fix layout".into());
+        base.priority = 2;
+        ours.store_mut(0).create(base.clone()).unwrap();
+
+        // Someone else retitles the issue
+        let mut theirs = Repos::open(None, &roots).unwrap();
+        let mut retitled = base.clone();
+        retitled.title = "Fix header layout".into();
+        retitled.updated_at = chrono::Utc::now();
+        assert!(theirs.save(0, &base, retitled).unwrap().is_none());
+
+        // Our edit from the old copy conflicts rather than overwriting
+        let mut edit = base.clone();
+        edit.priority = 0;
+        edit.updated_at = chrono::Utc::now();
+        let conflict = ours.save(0, &base, edit).unwrap().unwrap();
+        let (ours_changed, theirs_changed) = conflict.changes().unwrap();
+        assert_eq!(
+            (ours_changed, theirs_changed),
+            (vec!["priority".into()], vec!["title".into()])
+        );
+        assert!(conflict.overlapping().unwrap().is_empty());
+        let merged = conflict.merged().unwrap();
+        assert_eq!(merged.title, "Fix header layout");
+        assert_eq!(merged.priority, 0);
+
+        // Saving the merge against the disk version goes through
+        assert!(ours.save(0, &conflict.theirs, merged).unwrap().is_none());
+        assert!(!ours.reload_changed().unwrap());
+        assert!(theirs.reload_changed().unwrap());
+        let issue = theirs.issues(true)[0].1.clone();
+        assert_eq!(
+            (issue.title.as_str(), issue.priority),
+            ("Fix header layout", 0)
+        );
+
+        // A bulk save refuses stale bases and writes nothing
+        let mut closed = issue.clone();
+        closed.close(None);
+        let stale = vec![(0, base.clone(), closed.clone())];
+        assert_eq!(ours.save_all(stale).unwrap(), ["trx-a1"]);
+        assert!(ours.save_all(vec![(0, issue, closed)]).unwrap().is_empty());
+        assert!(theirs.reload_changed().unwrap());
+        assert!(theirs.issues(false).is_empty());
+    }
+
+    #[test]
+    fn test_config_errors_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo(dir.path(), "trx");
+        let repos = Repos::open(None, &[dir.path().to_path_buf()]).unwrap();
+        assert_eq!(repos.config(0).unwrap().tui.keys.len(), 0);
+
+        let path = dir.path().join(".trx/config.toml");
+        std::fs::write(&path, "prefix = \"trx\"\n[tui.keys]\nx = 1\n").unwrap();
+        assert!(repos.config(0).is_err());
+    }
+
+    #[test]
+    fn test_create_keeps_changes_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        init_repo(dir.path(), "trx");
+        let roots = [dir.path().to_path_buf()];
+        let mut ours = Repos::open(None, &roots).unwrap();
+        let mut theirs = Repos::open(None, &roots).unwrap();
+
+        // Another process creates an issue we have not reloaded yet
+        theirs
+            .create(0, Issue::new("trx-b1".into(), "Theirs".into()))
+            .unwrap();
+        ours.create(0, Issue::new("trx-a1".into(), "Ours".into()))
+            .unwrap();
+
+        let reopened = Repos::open(None, &roots).unwrap();
+        let mut ids: Vec<_> = reopened
+            .issues(true)
+            .iter()
+            .map(|(_, i)| i.id.clone())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, ["trx-a1", "trx-b1"]);
+        // Our own write is not mistaken for someone else's
+        assert!(!ours.reload_changed().unwrap());
+    }
+}
@@ -0,0 +1,394 @@
+//! Statistics dashboard
+//!
+//! Everything is worked out from the loaded issues: activity over the last
+//! `DAYS` days, breakdowns of the backlog, how long issues take to close,
+//! the oldest open issues and the biggest bottlenecks by PageRank over the
+//! open dependency graph. The app recomputes it whenever issues reload.
+
+use crate::theme::Theme;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Sparkline};
+use std::collections::BTreeMap;
+use trx_core::{Issue, IssueGraph, Status};
+
+/// Days covered by the activity sparklines
+pub const DAYS: usize = 30;
+
+/// Rows in the oldest and bottleneck lists
+const TOP: usize = 5;
+
+/// A breakdown row: name, open and closed counts
+pub struct Row {
+    pub name: String,
+    pub open: usize,
+    pub closed: usize,
+}
+
+pub struct Bottleneck {
+    pub id: String,
+    pub title: String,
+    pub rank: f64,
+    /// Open issues it blocks directly
+    pub blocks: usize,
+}
+
+pub struct Stats {
+    pub by_status: Vec<(Status, usize)>,
+    /// Open issues at the end of each day, oldest first
+    pub backlog: Vec<u64>,
+    pub opened: Vec<u64>,
+    pub closed: Vec<u64>,
+    pub by_type: Vec<Row>,
+    pub by_priority: Vec<Row>,
+    /// Open issues per assignee, busiest first
+    pub by_assignee: Vec<(String, usize)>,
+    pub avg_time_to_close: Option<Duration>,
+    /// ID, title and age of the oldest open issues
+    pub oldest: Vec<(String, String, Duration)>,
+    pub bottlenecks: Vec<Bottleneck>,
+}
+
+/// When the issue was closed, ignoring the stale `closed_at` a reopened
+/// issue may keep
+fn closed_at(issue: &Issue) -> Option<DateTime<Utc>> {
+    issue.closed_at.filter(|_| issue.status == Status::Closed)
+}
+
+impl Stats {
+    pub fn compute(issues: &[&Issue], now: DateTime<Utc>) -> Self {
+        let today = now.date_naive();
+        let day = |i: usize| today - Duration::days((DAYS - 1 - i) as i64);
+        let slot = |date: NaiveDate| {
+            let age = (today - date).num_days();
+            (0..DAYS as i64)
+                .contains(&age)
+                .then(|| DAYS - 1 - age as usize)
+        };
+
+        let mut opened = vec![0; DAYS];
+        let mut closed = vec![0; DAYS];
+        for issue in issues {
+            if let Some(i) = slot(issue.created_at.date_naive()) {
+                opened[i] += 1;
+            }
+            if let Some(i) = closed_at(issue).and_then(|c| slot(c.date_naive())) {
+                closed[i] += 1;
+            }
+        }
+        let backlog = (0..DAYS)
+            .map(|i| {
+                let end = day(i);
+                issues
+                    .iter()
+                    .filter(|issue| {
+                        let closed = closed_at(issue);
+                        issue.created_at.date_naive() <= end
+                            && closed.is_none_or(|c| c.date_naive() > end)
+                            && (issue.status.is_open() || closed.is_some())
+                    })
+                    .count() as u64
+            })
+            .collect();
+
+        let by_status = [
+            Status::Open,
+            Status::InProgress,
+            Status::Blocked,
+            Status::Closed,
+        ]
+        .into_iter()
+        .map(|s| (s, issues.iter().filter(|i| i.status == s).count()))
+        .collect();
+
+        let mut by_type: BTreeMap<String, Row> = BTreeMap::new();
+        let mut by_priority: BTreeMap<u8, Row> = BTreeMap::new();
+        let mut by_assignee: BTreeMap<String, usize> = BTreeMap::new();
+        for issue in issues {
+            let open = issue.status.is_open();
+            for row in [
+                by_type
+                    .entry(issue.issue_type.to_string())
+                    .or_insert_with(|| Row::new(issue.issue_type.to_string())),
+                by_priority
+                    .entry(issue.priority)
+                    .or_insert_with(|| Row::new(format!("P{}", issue.priority))),
+            ] {
+                if open {
+                    row.open += 1;
+                } else {
+                    row.closed += 1;
+                }
+            }
+            if open {
+                let who = issue.assignee.as_deref().unwrap_or("unassigned");
+                *by_assignee.entry(who.to_string()).or_default() += 1;
+            }
+        }
+        let mut by_assignee: Vec<(String, usize)> = by_assignee.into_iter().collect();
+        by_assignee.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let close_times: Vec<Duration> = issues
+            .iter()
+            .filter_map(|i| closed_at(i).map(|c| c - i.created_at))
+            .collect();
+        let avg_time_to_close = (!close_times.is_empty())
+            .then(|| close_times.iter().sum::<Duration>() / close_times.len() as i32);
+
+        let open: Vec<&Issue> = issues
+            .iter()
+            .filter(|i| i.status.is_open())
+            .copied()
+            .collect();
+        let mut oldest: Vec<&Issue> = open.clone();
+        oldest.sort_by_key(|i| i.created_at);
+        let oldest = oldest
+            .into_iter()
+            .take(TOP)
+            .map(|i| (i.id.clone(), i.title.clone(), now - i.created_at))
+            .collect();
+
+        // Only issues holding others up count as bottlenecks
+        let graph = IssueGraph::from_issues(&open);
+        let blocks = graph.blocks_count();
+        let mut bottlenecks: Vec<Bottleneck> = graph
+            .pagerank(0.85, 20)
+            .into_iter()
+            .filter_map(|(id, rank)| {
+                let blocks = blocks.get(&id).copied().unwrap_or(0);
+                let issue = open.iter().find(|i| i.id == id)?;
+                (blocks > 0).then(|| Bottleneck {
+                    title: issue.title.clone(),
+                    id,
+                    rank,
+                    blocks,
+                })
+            })
+            .collect();
+        bottlenecks.sort_by(|a, b| b.rank.total_cmp(&a.rank).then_with(|| a.id.cmp(&b.id)));
+        bottlenecks.truncate(TOP);
+
+        Self {
+            by_status,
+            backlog,
+            opened,
+            closed,
+            by_type: by_type.into_values().collect(),
+            by_priority: by_priority.into_values().collect(),
+            by_assignee,
+            avg_time_to_close,
+            oldest,
+            bottlenecks,
+        }
+    }
+}
+
+impl Row {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            open: 0,
+            closed: 0,
+        }
+    }
+}
+
+/// "3d 4h", "5h 10m" or "12m"
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+pub fn render(f: &mut Frame, area: Rect, stats: &Stats, theme: &Theme) {
+    let outer = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.border)
+        .title("Dashboard");
+    let inner = outer.inner(area);
+    f.render_widget(outer, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(5),
+            Constraint::Min(6),
+            Constraint::Length(TOP as u16 + 2),
+        ])
+        .split(inner);
+
+    // Totals
+    let mut summary: Vec<Span> = Vec::new();
+    for (status, count) in &stats.by_status {
+        summary.push(Span::styled(format!("{} ", count), theme.status(*status)));
+        summary.push(Span::raw(format!("{}   ", status)));
+    }
+    summary.push(Span::styled("Average time to close: ", theme.muted));
+    summary.push(Span::styled(
+        stats
+            .avg_time_to_close
+            .map_or_else(|| "-".to_string(), format_duration),
+        theme.accent.add_modifier(Modifier::BOLD),
+    ));
+    f.render_widget(Paragraph::new(Line::from(summary)), rows[0]);
+
+    // Activity
+    let sparks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 3); 3])
+        .split(rows[1]);
+    for (area, title, data, style) in [
+        (sparks[0], "Open", &stats.backlog, theme.accent),
+        (sparks[1], "Opened", &stats.opened, theme.highlight),
+        (sparks[2], "Closed", &stats.closed, theme.success),
+    ] {
+        let now = data.last().copied().unwrap_or(0);
+        let total: u64 = data.iter().sum();
+        let title = if title == "Open" {
+            format!("{} ({} now, {} days)", title, now, DAYS)
+        } else {
+            format!("{} ({} in {} days)", title, total, DAYS)
+        };
+        let sparkline = Sparkline::default()
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .data(data)
+            .style(style);
+        f.render_widget(sparkline, area);
+    }
+
+    // Breakdowns
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 3); 3])
+        .split(rows[2]);
+    let header = |text: &str| {
+        Line::from(Span::styled(
+            text.to_string(),
+            theme.muted.add_modifier(Modifier::BOLD),
+        ))
+    };
+    let breakdown = |rows: &[Row]| {
+        let mut lines = vec![header(&format!("{:<12}{:>6}{:>8}", "", "open", "closed"))];
+        lines.extend(
+            rows.iter()
+                .map(|r| Line::from(format!("{:<12}{:>6}{:>8}", r.name, r.open, r.closed))),
+        );
+        lines
+    };
+    let mut assignees = vec![header(&format!("{:<16}{:>6}", "", "open"))];
+    assignees.extend(
+        stats
+            .by_assignee
+            .iter()
+            .map(|(who, n)| Line::from(format!("{:<16}{:>6}", who, n))),
+    );
+    for (area, title, lines) in [
+        (columns[0], "By type", breakdown(&stats.by_type)),
+        (columns[1], "By priority", breakdown(&stats.by_priority)),
+        (columns[2], "By assignee", assignees),
+    ] {
+        let block = Block::default().borders(Borders::ALL).title(title);
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    // Oldest open issues and bottlenecks
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[3]);
+    let mut oldest: Vec<Line> = stats
+        .oldest
+        .iter()
+        .map(|(id, title, age)| {
+            Line::from(vec![
+                Span::styled(format!("{:>8} ", format_duration(*age)), theme.muted),
+                Span::styled(id.clone(), theme.accent),
+                Span::raw(format!(" {}", title)),
+            ])
+        })
+        .collect();
+    if oldest.is_empty() {
+        oldest.push(Line::from(Span::styled("No open issues", theme.muted)));
+    }
+    let mut bottlenecks: Vec<Line> = stats
+        .bottlenecks
+        .iter()
+        .map(|b| {
+            Line::from(vec![
+                Span::styled(format!("{:.3} ", b.rank), theme.highlight),
+                Span::styled(b.id.clone(), theme.accent),
+                Span::styled(format!(" blocks {} ", b.blocks), theme.muted),
+                Span::raw(b.title.clone()),
+            ])
+        })
+        .collect();
+    if bottlenecks.is_empty() {
+        bottlenecks.push(Line::from(Span::styled(
+            "Nothing is blocking open work",
+            theme.muted,
+        )));
+    }
+    for (area, title, lines) in [
+        (columns[0], "Oldest open issues", oldest),
+        (columns[1], "Bottlenecks (PageRank)", bottlenecks),
+    ] {
+        let block = Block::default().borders(Borders::ALL).title(title);
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trx_core::DependencyType;
+
+    #[test]
+    fn test_compute() {
+        let now = Utc::now();
+        let mut a = Issue::new("trx-a".into(), "A".into());
+        a.created_at = now - Duration::days(10);
+        let mut b = Issue::new("trx-b".into(), "B".into());
+        b.created_at = now - Duration::days(3);
+        b.assignee = Some("sam".into());
+        b.add_dependency("trx-a".into(), DependencyType::Blocks);
+        let mut c = Issue::new("trx-c".into(), "C".into());
+        c.created_at = now - Duration::days(4);
+        c.close(None);
+        c.closed_at = Some(now - Duration::days(2));
+
+        let stats = Stats::compute(&[&a, &b, &c], now);
+        assert_eq!(stats.opened.iter().sum::<u64>(), 3);
+        assert_eq!(stats.closed[DAYS - 3], 1);
+        // a alone ten days ago; a, b and c four to three days ago; then c closes
+        assert_eq!(stats.backlog[DAYS - 11], 1);
+        assert_eq!(stats.backlog[DAYS - 4], 3);
+        assert_eq!(stats.backlog[DAYS - 1], 2);
+
+        assert_eq!(stats.avg_time_to_close, Some(Duration::days(2)));
+        assert_eq!(stats.oldest[0].0, "trx-a");
+        assert_eq!(stats.by_assignee[0], ("sam".to_string(), 1));
+        let task = &stats.by_type[0];
+        assert_eq!((task.open, task.closed), (2, 1));
+
+        assert_eq!(stats.bottlenecks.len(), 1);
+        assert_eq!(stats.bottlenecks[0].id, "trx-a");
+        assert_eq!(format_duration(Duration::minutes(3 * 1440 + 250)), "3d 4h");
+
+        // Reopened with its old closed_at left behind
+        c.status = Status::Open;
+        let stats = Stats::compute(&[&a, &b, &c], now);
+        assert_eq!(stats.closed.iter().sum::<u64>(), 0);
+        assert_eq!(stats.backlog[DAYS - 1], 3);
+        assert_eq!(stats.avg_time_to_close, None);
+    }
+}
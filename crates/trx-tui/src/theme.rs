@@ -0,0 +1,326 @@
+//! Colours of the TUI
+//!
+//! Renderers take styles from a `Theme` instead of naming colours, so the
+//! built-in dark, light and monochrome themes and the `[tui.theme]` config
+//! section apply everywhere. Elements are styles rather than colours so the
+//! monochrome theme can fall back on bold, dim and reversed text.
+
+use anyhow::{Result, anyhow, bail};
+use ratatui::style::{Color, Modifier, Style};
+use trx_core::{IssueType, Status, ThemeConfig};
+
+const STATUSES: [Status; 4] = [
+    Status::Open,
+    Status::InProgress,
+    Status::Blocked,
+    Status::Closed,
+];
+
+const TYPES: [IssueType; 5] = [
+    IssueType::Bug,
+    IssueType::Feature,
+    IssueType::Task,
+    IssueType::Epic,
+    IssueType::Chore,
+];
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    status: [Style; 4],
+    priority: [Style; 5],
+    types: [Style; 5],
+    /// Pane borders
+    pub border: Style,
+    pub overlay_border: Style,
+    /// Popup background
+    pub overlay: Style,
+    /// IDs, headings and keys
+    pub accent: Style,
+    /// Values being typed and things needing attention
+    pub highlight: Style,
+    /// Secondary text
+    pub muted: Style,
+    /// Cursor row background
+    pub selection: Style,
+    pub status_bar: Style,
+    pub danger: Style,
+    pub success: Style,
+    pub label: Style,
+    pub repo: Style,
+    pub link: Style,
+    pub code: Style,
+}
+
+fn fg(color: Color) -> Style {
+    Style::default().fg(color)
+}
+
+fn bg(color: Color) -> Style {
+    Style::default().bg(color)
+}
+
+fn bold() -> Style {
+    Style::default().add_modifier(Modifier::BOLD)
+}
+
+fn dim() -> Style {
+    Style::default().add_modifier(Modifier::DIM)
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            status: [
+                fg(Color::Green),
+                fg(Color::Yellow),
+                fg(Color::Red),
+                fg(Color::DarkGray),
+            ],
+            priority: [
+                fg(Color::Red),
+                fg(Color::Red),
+                fg(Color::Yellow),
+                fg(Color::Green),
+                fg(Color::DarkGray),
+            ],
+            types: [fg(Color::Blue); 5],
+            border: fg(Color::Blue),
+            overlay_border: fg(Color::Yellow),
+            overlay: bg(Color::Black),
+            accent: fg(Color::Cyan),
+            highlight: fg(Color::Yellow),
+            muted: fg(Color::DarkGray),
+            selection: bg(Color::DarkGray),
+            status_bar: bg(Color::DarkGray),
+            danger: fg(Color::Red),
+            success: fg(Color::Green),
+            label: fg(Color::Magenta),
+            repo: fg(Color::Magenta),
+            link: fg(Color::Blue),
+            code: fg(Color::Yellow),
+        }
+    }
+
+    /// For terminals with a light background, where yellow and cyan wash out
+    pub fn light() -> Self {
+        Self {
+            status: [
+                fg(Color::Green),
+                fg(Color::Magenta),
+                fg(Color::Red),
+                fg(Color::DarkGray),
+            ],
+            priority: [
+                fg(Color::Red),
+                fg(Color::Red),
+                fg(Color::Magenta),
+                fg(Color::Green),
+                fg(Color::DarkGray),
+            ],
+            types: [fg(Color::Blue); 5],
+            border: fg(Color::Blue),
+            overlay_border: fg(Color::Magenta),
+            overlay: bg(Color::White),
+            accent: fg(Color::Blue),
+            highlight: fg(Color::Magenta),
+            muted: fg(Color::DarkGray),
+            selection: bg(Color::Gray),
+            status_bar: bg(Color::Gray),
+            danger: fg(Color::Red),
+            success: fg(Color::Green),
+            label: fg(Color::Magenta),
+            repo: fg(Color::Magenta),
+            link: fg(Color::Blue),
+            code: fg(Color::Red),
+        }
+    }
+
+    /// No colours at all, for NO_COLOR and low-colour terminals
+    pub fn mono() -> Self {
+        let plain = Style::default();
+        let reversed = Style::default().add_modifier(Modifier::REVERSED);
+        Self {
+            status: [plain, bold(), bold(), dim()],
+            priority: [bold(), bold(), plain, plain, dim()],
+            types: [plain; 5],
+            border: plain,
+            overlay_border: bold(),
+            overlay: plain,
+            accent: bold(),
+            highlight: bold(),
+            muted: dim(),
+            selection: reversed,
+            status_bar: reversed,
+            danger: bold().add_modifier(Modifier::UNDERLINED),
+            success: bold(),
+            label: Style::default().add_modifier(Modifier::ITALIC),
+            repo: plain,
+            link: Style::default().add_modifier(Modifier::UNDERLINED),
+            code: plain,
+        }
+    }
+
+    /// The configured theme; with `colors` off, as when NO_COLOR is set,
+    /// always monochrome and without the configured colours
+    pub fn from_config(config: &ThemeConfig, colors: bool) -> Result<Self> {
+        let mut theme = match config.base.as_deref().unwrap_or("dark") {
+            "dark" => Self::dark(),
+            "light" => Self::light(),
+            "mono" => Self::mono(),
+            other => bail!(
+                "Invalid [tui.theme] base '{}': expected dark, light or mono",
+                other
+            ),
+        };
+
+        // Check every entry even when the colours will not be used
+        let mut set = |section: &str, name: &str, value: &str| -> Result<()> {
+            let color: Color = value.parse().map_err(|_| {
+                anyhow!(
+                    "Invalid [tui.theme.{}] entry {}: unknown colour '{}'",
+                    section,
+                    name,
+                    value
+                )
+            })?;
+            let unknown = || anyhow!("Invalid [tui.theme.{}] entry: unknown {}", section, name);
+            let style = match section {
+                "status" => {
+                    let status: Status = name.parse().map_err(|_| unknown())?;
+                    let i = STATUSES
+                        .iter()
+                        .position(|s| *s == status)
+                        .ok_or_else(unknown)?;
+                    &mut theme.status[i]
+                }
+                "priority" => {
+                    let p: usize = name
+                        .trim_start_matches(['P', 'p'])
+                        .parse()
+                        .map_err(|_| unknown())?;
+                    theme.priority.get_mut(p).ok_or_else(unknown)?
+                }
+                "type" => {
+                    let issue_type: IssueType = name.parse().map_err(|_| unknown())?;
+                    let i = TYPES
+                        .iter()
+                        .position(|t| *t == issue_type)
+                        .ok_or_else(unknown)?;
+                    &mut theme.types[i]
+                }
+                _ => theme.ui_mut(name).ok_or_else(unknown)?,
+            };
+            if colors {
+                *style = if matches!(name, "overlay" | "selection" | "status_bar") {
+                    style.bg(color)
+                } else {
+                    style.fg(color)
+                };
+            }
+            Ok(())
+        };
+        for (section, entries) in [
+            ("status", &config.status),
+            ("priority", &config.priority),
+            ("type", &config.issue_type),
+            ("ui", &config.ui),
+        ] {
+            for (name, value) in entries {
+                set(section, name, value)?;
+            }
+        }
+
+        if !colors {
+            theme = Self::mono();
+        }
+        Ok(theme)
+    }
+
+    fn ui_mut(&mut self, name: &str) -> Option<&mut Style> {
+        Some(match name {
+            "border" => &mut self.border,
+            "overlay_border" => &mut self.overlay_border,
+            "overlay" => &mut self.overlay,
+            "accent" => &mut self.accent,
+            "highlight" => &mut self.highlight,
+            "muted" => &mut self.muted,
+            "selection" => &mut self.selection,
+            "status_bar" => &mut self.status_bar,
+            "danger" => &mut self.danger,
+            "success" => &mut self.success,
+            "label" => &mut self.label,
+            "repo" => &mut self.repo,
+            "link" => &mut self.link,
+            "code" => &mut self.code,
+            _ => return None,
+        })
+    }
+
+    pub fn status(&self, status: Status) -> Style {
+        STATUSES
+            .iter()
+            .position(|s| *s == status)
+            .map_or(self.status[3], |i| self.status[i])
+    }
+
+    pub fn priority(&self, priority: u8) -> Style {
+        self.priority[(priority as usize).min(4)]
+    }
+
+    pub fn issue_type(&self, issue_type: IssueType) -> Style {
+        TYPES
+            .iter()
+            .position(|t| *t == issue_type)
+            .map_or(Style::default(), |i| self.types[i])
+    }
+}
+
+/// Whether colour output is wanted: off when NO_COLOR is set to anything
+/// non-empty or `display.colors` is false
+pub fn colors_enabled(display_colors: bool) -> bool {
+    display_colors && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_from_config() {
+        let config = ThemeConfig {
+            base: Some("light".into()),
+            status: BTreeMap::from([("in_progress".into(), "#336699".into())]),
+            priority: BTreeMap::from([("P0".into(), "lightred".into())]),
+            ui: BTreeMap::from([("selection".into(), "yellow".into())]),
+            ..ThemeConfig::default()
+        };
+        let theme = Theme::from_config(&config, true).unwrap();
+        assert_eq!(
+            theme.status(Status::InProgress).fg,
+            Some(Color::Rgb(0x33, 0x66, 0x99))
+        );
+        assert_eq!(theme.priority(0).fg, Some(Color::LightRed));
+        assert_eq!(theme.selection.bg, Some(Color::Yellow));
+        assert_eq!(theme.accent.fg, Some(Color::Blue));
+
+        // Without colours the config is still checked but not applied
+        let mono = Theme::from_config(&config, false).unwrap();
+        assert_eq!(mono.status(Status::InProgress).fg, None);
+        assert!(mono.selection.add_modifier.contains(Modifier::REVERSED));
+
+        let bad = |config: ThemeConfig| Theme::from_config(&config, true).is_err();
+        assert!(bad(ThemeConfig {
+            base: Some("solarized".into()),
+            ..ThemeConfig::default()
+        }));
+        assert!(bad(ThemeConfig {
+            ui: BTreeMap::from([("bordr".into(), "red".into())]),
+            ..ThemeConfig::default()
+        }));
+        assert!(bad(ThemeConfig {
+            issue_type: BTreeMap::from([("bug".into(), "reddish".into())]),
+            ..ThemeConfig::default()
+        }));
+    }
+}